@@ -36,12 +36,57 @@ async function getAccounts() {
         name: account.meta.name, // e.g. "Alice"
         source: account.meta.source, // e.g. "talisman", "polkadot-js"
         ty: account.type, // e.g. "sr25519"
-        address: account.address // e.g. "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+        address: account.address, // e.g. "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+        isDefault: account.meta.isDefault === true || account.meta.selected === true // set by some extensions only
     }));
     console.log(accountObjects);
     return JSON.stringify(accountObjects);
 }
 
+/**
+ *  Reports which browser extensions are installed and which authorized this site, to explain why no accounts were found.
+ *  A locked extension is installed and authorized but returns no accounts until it's unlocked.
+ *
+ *  @returns a json string like `{"installed": ["talisman"], "authorized": ["talisman"]}`
+ */
+async function getExtensionStatus() {
+    const extensionMod = await getPolkadotJsExtensionMod();
+    const installed = Object.keys(window.injectedWeb3 || {});
+    const authorized = (await extensionMod.web3Enable("Subxt Example App")).map((extension) => extension.name);
+    return JSON.stringify({installed, authorized});
+}
+
+/**
+ * Reports whether an extension can be given chain metadata, and the spec version it already knows for the chain.
+ *
+ * @param source the extension, e.g. "polkadot-js"
+ * @param genesisHash the 0x prefixed genesis hash of the chain
+ * @returns a json string like `{"supported": true, "specVersion": 9430}`, `specVersion` is null if the chain is unknown
+ */
+async function getExtensionMetadata(source, genesisHash) {
+    const extensionMod = await getPolkadotJsExtensionMod();
+    const injector = await extensionMod.web3FromSource(source);
+    const metadata = injector?.metadata;
+    if (!metadata?.provide || !metadata?.get) {
+        return JSON.stringify({supported: false, specVersion: null});
+    }
+    const known = (await metadata.get()).find((def) => def.genesisHash === genesisHash);
+    return JSON.stringify({supported: true, specVersion: known ? known.specVersion : null});
+}
+
+/**
+ * Asks an extension to store chain metadata, so its signing popup can decode the calls.
+ *
+ * @param source the extension, e.g. "polkadot-js"
+ * @param definitionAsStr a string representing a JSON `MetadataDef` object
+ * @returns {Promise<boolean>} whether the user accepted the update
+ */
+async function provideMetadata(source, definitionAsStr) {
+    const extensionMod = await getPolkadotJsExtensionMod();
+    const injector = await extensionMod.web3FromSource(source);
+    return await injector.metadata.provide(JSON.parse(definitionAsStr));
+}
+
 /**
  * Signs a payload via browser extension
  *
@@ -87,3 +132,30 @@ async function signPayload(payloadAsStr, source, address) {
         throw "The extension's injector does not have a `signPayload` function on its `signer`";
     }
 }
+
+
+/**
+ * Notifies the embedding page (e.g. a governance dashboard showing the app in an iframe) about a status change.
+ *
+ * @param statusAsStr a string representing a JSON object like this:
+ * let status = {
+ *     "source": "ref-275",
+ *     "type": "finalized", // one of "accountsLoaded", "signed", "submitted", "finalized", "failed"
+ *     "blockHash": "0xd7aad6185db012b7ffbce710b55234d6c9589170566b925ee50cfa3d7f1e6f8f"
+ * };
+ */
+function postStatus(statusAsStr) {
+    if (window.parent && window.parent !== window) {
+        window.parent.postMessage(JSON.parse(statusAsStr), "*");
+    }
+}
+
+/**
+ * Copies text to the clipboard, e.g. an encoded call or a signed extrinsic.
+ *
+ * @param text the text to copy
+ * @returns {Promise<void>}
+ */
+async function copyToClipboard(text) {
+    await navigator.clipboard.writeText(text);
+}
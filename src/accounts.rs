@@ -0,0 +1,371 @@
+use base58::ToBase58;
+use blake2::{Blake2b512, Digest};
+use serde::{Deserialize, Serialize};
+use subxt::utils::AccountId32;
+
+use crate::services::Account;
+
+/// ss58 prefix of Kusama addresses
+/// Accounts of one browser extension, keeping each account's index in the full list.
+#[derive(Clone, Debug)]
+pub struct AccountGroup<'a> {
+    /// name of the browser extension, e.g. "talisman" or "polkadot-js"
+    pub source: &'a str,
+    pub accounts: Vec<(usize, &'a Account)>,
+}
+
+/// groups `accounts` by extension, in order of first appearance, with the group of
+/// `preferred_source` (e.g. the last used extension) first.
+pub fn group_accounts_by_source<'a>(
+    accounts: &'a [Account],
+    preferred_source: Option<&str>,
+) -> Vec<AccountGroup<'a>> {
+    let mut groups: Vec<AccountGroup> = vec![];
+    for (i, account) in accounts.iter().enumerate() {
+        match groups
+            .iter_mut()
+            .find(|group| group.source == account.source)
+        {
+            Some(group) => group.accounts.push((i, account)),
+            None => groups.push(AccountGroup {
+                source: &account.source,
+                accounts: vec![(i, account)],
+            }),
+        }
+    }
+    if let Some(position) = groups
+        .iter()
+        .position(|group| Some(group.source) == preferred_source)
+    {
+        let preferred = groups.remove(position);
+        groups.insert(0, preferred);
+    }
+    groups
+}
+
+/// updates `current` with a refreshed account list: accounts keep their position (and get
+/// renamed if needed), new accounts are appended and accounts no longer available are dropped.
+pub fn merge_accounts(current: &[Account], mut refreshed: Vec<Account>) -> Vec<Account> {
+    let same = |a: &Account, b: &Account| a.source == b.source && a.address == b.address;
+    let mut merged: Vec<Account> = current
+        .iter()
+        .filter_map(|account| {
+            let i = refreshed.iter().position(|other| same(account, other))?;
+            Some(refreshed.remove(i))
+        })
+        .collect();
+    merged.extend(refreshed);
+    merged
+}
+
+/// sources of the other entries of `accounts` with the same address as the one at `index`,
+/// e.g. the same account imported in both Talisman and polkadot-js. Addresses are compared by
+/// account id, so the same key encoded with different ss58 prefixes matches.
+pub fn duplicate_sources(accounts: &[Account], index: usize) -> Vec<&str> {
+    let key = |account: &Account| {
+        account
+            .address
+            .parse::<AccountId32>()
+            .map_or_else(|_| account.address.clone(), |id| format!("{id:?}"))
+    };
+    let Some(account) = accounts.get(index) else {
+        return vec![];
+    };
+    let address = key(account);
+    accounts
+        .iter()
+        .enumerate()
+        .filter(|(i, other)| *i != index && other.source != account.source && key(other) == address)
+        .map(|(_, other)| other.source.as_str())
+        .collect()
+}
+
+/// number of recently used accounts remembered for the quick-pick
+pub const RECENT_ACCOUNTS_LEN: usize = 3;
+
+/// An account recently used for signing, persisted in `localStorage`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecentAccount {
+    pub source: String,
+    pub address: String,
+    /// milliseconds since the epoch, from `Date.now()`
+    pub used_at: f64,
+}
+
+/// puts `account` first in the `recent` accounts, most recent first, keeping at most
+/// `RECENT_ACCOUNTS_LEN` entries.
+pub fn remember_account(
+    recent: &[RecentAccount],
+    account: &Account,
+    used_at: f64,
+) -> Vec<RecentAccount> {
+    let mut updated = vec![RecentAccount {
+        source: account.source.clone(),
+        address: account.address.clone(),
+        used_at,
+    }];
+    updated.extend(
+        recent
+            .iter()
+            .filter(|entry| entry.source != account.source || entry.address != account.address)
+            .cloned(),
+    );
+    updated.truncate(RECENT_ACCOUNTS_LEN);
+    updated
+}
+
+/// the `recent` accounts still available in `accounts`, most recent first, with their index
+/// in `accounts`.
+pub fn available_recent_accounts<'a>(
+    recent: &[RecentAccount],
+    accounts: &'a [Account],
+) -> Vec<(usize, &'a Account)> {
+    recent
+        .iter()
+        .filter_map(|entry| {
+            accounts.iter().enumerate().find(|(_, account)| {
+                account.source == entry.source && account.address == entry.address
+            })
+        })
+        .collect()
+}
+
+/// max characters of a malformed accounts payload quoted in errors
+const PAYLOAD_SNIPPET_LEN: usize = 120;
+
+/// parses the accounts JSON returned by the extensions shim.
+///
+/// Entries that are not valid accounts are skipped, so a single broken entry (e.g. while an
+/// extension is upgrading) doesn't hide the others. So are accounts without an ss58 address
+/// (e.g. the Ethereum accounts of some extensions), which can't sign Kusama extrinsics. Returns the accounts and the number of
+/// skipped entries, or an error quoting the start of the payload if it's not a JSON array.
+pub fn parse_accounts(payload: &str) -> Result<(Vec<Account>, usize), anyhow::Error> {
+    let entries: Vec<serde_json::Value> = serde_json::from_str(payload).map_err(|err| {
+        let snippet: String = payload.chars().take(PAYLOAD_SNIPPET_LEN).collect();
+        let ellipsis = if snippet.len() < payload.len() {
+            "…"
+        } else {
+            ""
+        };
+        anyhow::anyhow!("The extensions returned malformed accounts ({err}): {snippet}{ellipsis}")
+    })?;
+    let total = entries.len();
+    let accounts: Vec<Account> = entries
+        .into_iter()
+        .filter_map(|entry| serde_json::from_value::<Account>(entry).ok())
+        .filter(|account| parse_account_id(&account.address).is_ok())
+        .collect();
+    let skipped = total - accounts.len();
+    Ok((accounts, skipped))
+}
+
+/// the account id of an ss58 `address` of any network
+pub fn parse_account_id(address: &str) -> Result<AccountId32, anyhow::Error> {
+    address
+        .parse()
+        .map_err(|err| anyhow::anyhow!("\"{address}\" is not an ss58 address: {err:?}"))
+}
+
+/// ss58 encodes `account_id` with the network `prefix`, see
+/// https://docs.substrate.io/reference/address-formats/
+pub fn encode_ss58(account_id: &AccountId32, prefix: u16) -> String {
+    // prefixes from 64 take two bytes, with the upper 2 bits of the first byte set to 01
+    let mut bytes = match prefix {
+        0..=63 => vec![prefix as u8],
+        _ => vec![
+            ((prefix & 0b1111_1100) >> 2) as u8 | 0b0100_0000,
+            (prefix >> 8) as u8 | ((prefix & 0b0000_0011) << 6) as u8,
+        ],
+    };
+    bytes.extend_from_slice(&account_id.0);
+    let checksum = Blake2b512::new()
+        .chain_update(b"SS58PRE")
+        .chain_update(&bytes)
+        .finalize();
+    bytes.extend_from_slice(&checksum[..2]);
+    bytes.to_base58()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Alice's dev account, generic (42) and Kusama (2) encoded
+    const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+    const ALICE_KUSAMA: &str = "HNZata7iMYWmk5RvZRTiAsSDhV8366zq2YGb3tLH5Upf74F";
+    /// Bob's dev account, generic encoded
+    const BOB: &str = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+
+    fn account(name: &str, source: &str, address: &str) -> Account {
+        Account {
+            name: name.to_string(),
+            source: source.to_string(),
+            ty: "sr25519".to_string(),
+            address: address.to_string(),
+            is_default: false,
+        }
+    }
+
+    fn names<'a>(accounts: impl IntoIterator<Item = &'a Account>) -> Vec<&'a str> {
+        accounts
+            .into_iter()
+            .map(|account| account.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn groups_by_source_with_the_preferred_first() {
+        let accounts = vec![
+            account("a", "polkadot-js", ALICE),
+            account("b", "talisman", BOB),
+            account("c", "polkadot-js", BOB),
+        ];
+        let groups = group_accounts_by_source(&accounts, None);
+        assert_eq!(
+            groups.iter().map(|group| group.source).collect::<Vec<_>>(),
+            ["polkadot-js", "talisman"]
+        );
+        assert_eq!(
+            groups[0]
+                .accounts
+                .iter()
+                .map(|(i, _)| *i)
+                .collect::<Vec<_>>(),
+            [0, 2]
+        );
+
+        let groups = group_accounts_by_source(&accounts, Some("talisman"));
+        assert_eq!(
+            groups.iter().map(|group| group.source).collect::<Vec<_>>(),
+            ["talisman", "polkadot-js"]
+        );
+        // an extension that's gone keeps the order
+        let groups = group_accounts_by_source(&accounts, Some("subwallet"));
+        assert_eq!(groups[0].source, "polkadot-js");
+    }
+
+    #[test]
+    fn merge_keeps_positions() {
+        let current = vec![
+            account("a", "polkadot-js", ALICE),
+            account("b", "talisman", BOB),
+            account("c", "polkadot-js", BOB),
+        ];
+        let refreshed = vec![
+            account("new", "subwallet", ALICE),
+            account("c", "polkadot-js", BOB),
+            account("renamed", "polkadot-js", ALICE),
+        ];
+        let merged = merge_accounts(&current, refreshed);
+        assert_eq!(names(&merged), ["renamed", "c", "new"]);
+    }
+
+    #[test]
+    fn remembers_the_most_recent_accounts() {
+        let alice = account("a", "polkadot-js", ALICE);
+        let bob = account("b", "polkadot-js", BOB);
+        let talisman_bob = account("b", "talisman", BOB);
+        let talisman_alice = account("a", "talisman", ALICE);
+
+        let recent = remember_account(&[], &alice, 1.0);
+        let recent = remember_account(&recent, &bob, 2.0);
+        let recent = remember_account(&recent, &talisman_bob, 3.0);
+        // used again, moves to the front instead of being listed twice
+        let recent = remember_account(&recent, &alice, 4.0);
+        assert_eq!(
+            recent
+                .iter()
+                .map(|entry| (entry.source.as_str(), entry.used_at))
+                .collect::<Vec<_>>(),
+            [
+                ("polkadot-js", 4.0),
+                ("talisman", 3.0),
+                ("polkadot-js", 2.0)
+            ]
+        );
+
+        let recent = remember_account(&recent, &talisman_alice, 5.0);
+        assert_eq!(recent.len(), RECENT_ACCOUNTS_LEN);
+        assert_eq!(recent[0].used_at, 5.0);
+        assert_eq!(recent[2].used_at, 3.0);
+
+        // only the ones still available
+        let accounts = vec![bob, talisman_bob];
+        let available = available_recent_accounts(&recent, &accounts);
+        assert_eq!(available.iter().map(|(i, _)| *i).collect::<Vec<_>>(), [1]);
+    }
+
+    #[test]
+    fn parse_accounts_skips_broken_entries() {
+        let payload = serde_json::json!([
+            {"name": "a", "source": "polkadot-js", "ty": "sr25519", "address": ALICE, "isDefault": true},
+            {"name": "broken", "source": "polkadot-js"},
+            "not an account",
+            {"name": "eth", "source": "talisman", "ty": "ethereum", "address": "0x8f0f8e0d6bf4dd4d0e7c1b9b2d3b5a4c6e8f0a1b"},
+            {"name": "b", "source": "talisman", "ty": "sr25519", "address": BOB},
+        ])
+        .to_string();
+        let (accounts, skipped) = parse_accounts(&payload).unwrap();
+        assert_eq!(names(&accounts), ["a", "b"]);
+        assert!(accounts[0].is_default);
+        // missing in older shims
+        assert!(!accounts[1].is_default);
+        assert_eq!(skipped, 3);
+    }
+
+    #[test]
+    fn parse_accounts_quotes_malformed_payloads() {
+        let err = parse_accounts("{\"error\": true}").unwrap_err().to_string();
+        assert!(err.ends_with(": {\"error\": true}"), "{err}");
+
+        let payload = format!("[{}", "x".repeat(200));
+        let err = parse_accounts(&payload).unwrap_err().to_string();
+        assert!(err.ends_with(&format!("[{}…", "x".repeat(PAYLOAD_SNIPPET_LEN - 1))));
+    }
+
+    #[test]
+    fn kusama_ss58_of_any_network() {
+        let kusama = |address: &str| Ok(encode_ss58(&parse_account_id(address)?, 2));
+        assert_eq!(kusama(ALICE).unwrap(), ALICE_KUSAMA);
+        assert_eq!(kusama(ALICE_KUSAMA).unwrap(), ALICE_KUSAMA);
+        // Polkadot
+        assert_eq!(
+            kusama("15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5").unwrap(),
+            ALICE_KUSAMA
+        );
+        let err: Result<String, anyhow::Error> =
+            kusama("0x8f0f8e0d6bf4dd4d0e7c1b9b2d3b5a4c6e8f0a1b");
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn encodes_two_byte_prefixes() {
+        let alice = parse_account_id(ALICE).unwrap();
+        assert_eq!(encode_ss58(&alice, 42), ALICE);
+        assert_eq!(
+            encode_ss58(&alice, 255),
+            "yGHXkYLYqxijLKKfd9Q2CB9shRVu8rPNBS53wvwGTutYg4zTg"
+        );
+        let encoded = encode_ss58(&alice, 1284);
+        assert_eq!(encoded, "VdvKmYJfD4VXA9fzz1SbmCo2eYHSzUFbaDCZSuaNKJAe8YNg6");
+        assert_eq!(parse_account_id(&encoded).unwrap(), alice);
+    }
+
+    #[test]
+    fn duplicates_across_sources_and_prefixes() {
+        let accounts = vec![
+            account("a", "polkadot-js", ALICE),
+            account("a", "talisman", ALICE_KUSAMA),
+            account("a again", "polkadot-js", ALICE),
+            account("b", "subwallet", BOB),
+            account("a", "subwallet", ALICE),
+        ];
+        assert_eq!(duplicate_sources(&accounts, 0), ["talisman", "subwallet"]);
+        assert_eq!(
+            duplicate_sources(&accounts, 1),
+            ["polkadot-js", "polkadot-js", "subwallet"]
+        );
+        assert!(duplicate_sources(&accounts, 3).is_empty());
+        assert!(duplicate_sources(&accounts, 5).is_empty());
+    }
+}
@@ -0,0 +1,341 @@
+/// Number of decimals of the KSM token, i.e. 1 KSM = 10^12 Planck.
+pub const KSM_DECIMALS: u32 = 12;
+
+/// An amount of KSM.
+///
+/// The value is always held in Planck (the smallest unit), which is what calls and storage use.
+/// Use `from_units`/`from_planck` to be explicit about the unit of the value being wrapped.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Balance(u128);
+
+impl Balance {
+    pub const fn from_planck(planck: u128) -> Self {
+        Self(planck)
+    }
+
+    /// whole units of a token with `decimals` to Planck, `ParseError::Overflow` if the amount
+    /// doesn't fit in a `u128`
+    pub fn from_units(units: u128, decimals: u32) -> Result<Self, ParseError> {
+        units
+            .checked_mul(planck_per_unit(decimals))
+            .map(Self)
+            .ok_or(ParseError::Overflow)
+    }
+
+    pub const fn as_planck(&self) -> u128 {
+        self.0
+    }
+
+    /// `percent` of the balance, rounded down. Percentages above 100 are clamped to 100.
+    pub fn percentage(&self, percent: u8) -> Self {
+        let percent = percent.min(100) as u128;
+        // split to avoid overflowing for balances close to `u128::MAX`
+        Self(self.0 / 100 * percent + self.0 % 100 * percent / 100)
+    }
+
+    /// formats the balance in units of a token with `decimals`, without trailing zeros
+    pub fn format_units(&self, decimals: u32) -> String {
+        let whole = self.0 / planck_per_unit(decimals);
+        let fraction = self.0 % planck_per_unit(decimals);
+        if fraction == 0 {
+            return whole.to_string();
+        }
+        let fraction = format!("{:0width$}", fraction, width = decimals as usize);
+        format!("{}.{}", whole, fraction.trim_end_matches('0'))
+    }
+}
+
+/// number of Planck in one unit for a token with the given `decimals`
+pub const fn planck_per_unit(decimals: u32) -> u128 {
+    10u128.pow(decimals)
+}
+
+/// Why an amount entered by the user could not be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    Empty,
+    /// e.g. "1e3", which is easy to paste from spreadsheets but ambiguous in intent
+    ScientificNotation,
+    InvalidCharacter(char),
+    MultipleDecimalPoints,
+    /// more decimals than the token has, which is carried
+    TooPrecise(u32),
+    Overflow,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "Enter an amount"),
+            Self::ScientificNotation => {
+                write!(
+                    f,
+                    "Scientific notation is not supported, enter e.g. 1000 instead of 1e3"
+                )
+            }
+            Self::InvalidCharacter(c) => write!(f, "\"{c}\" is not allowed in an amount"),
+            Self::MultipleDecimalPoints => write!(f, "The amount has more than one decimal point"),
+            Self::TooPrecise(decimals) => {
+                write!(f, "The amount has more than {decimals} decimals")
+            }
+            Self::Overflow => write!(f, "The amount is too large"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// parses an amount of a token with `decimals` as typed or pasted by the user, e.g. " 1,000.5 ".
+///
+/// Whitespace and thousands separators (`,`, `_`) are ignored, `.` is the decimal point.
+pub fn parse_amount(input: &str, decimals: u32) -> Result<Balance, ParseError> {
+    let amount: String = input
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != ',' && *c != '_')
+        .collect();
+    if amount.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    if let Some(c) = amount.chars().find(|c| !c.is_ascii_digit() && *c != '.') {
+        return Err(match c {
+            'e' | 'E' => ParseError::ScientificNotation,
+            c => ParseError::InvalidCharacter(c),
+        });
+    }
+
+    let (whole, fraction) = amount.split_once('.').unwrap_or((&amount, ""));
+    if fraction.contains('.') {
+        return Err(ParseError::MultipleDecimalPoints);
+    }
+    if whole.is_empty() && fraction.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > decimals as usize {
+        return Err(ParseError::TooPrecise(decimals));
+    }
+
+    let whole = match whole {
+        "" => 0,
+        whole => whole.parse::<u128>().map_err(|_| ParseError::Overflow)?,
+    };
+    let fraction_planck = match fraction {
+        "" => 0,
+        fraction => {
+            let fraction = format!("{:0<width$}", fraction, width = decimals as usize);
+            fraction.parse::<u128>().map_err(|_| ParseError::Overflow)?
+        }
+    };
+    Balance::from_units(whole, decimals)?
+        .as_planck()
+        .checked_add(fraction_planck)
+        .map(Balance::from_planck)
+        .ok_or(ParseError::Overflow)
+}
+
+/// parses a whole percentage as typed by the user, e.g. "25%", clamped to 100.
+///
+/// `None` if the input doesn't end with `%`, i.e. it's an amount rather than a percentage.
+pub fn parse_percentage(input: &str) -> Option<Result<u8, ParseError>> {
+    let percent = input.trim().strip_suffix('%')?.trim();
+    if percent.is_empty() {
+        return Some(Err(ParseError::Empty));
+    }
+    if let Some(c) = percent.chars().find(|c| !c.is_ascii_digit()) {
+        return Some(Err(ParseError::InvalidCharacter(c)));
+    }
+    // anything above 100 is clamped, so only the number of digits can make it overflow
+    let percent = percent
+        .parse::<u128>()
+        .map_or(100, |percent| percent.min(100));
+    Some(Ok(percent as u8))
+}
+
+/// how much the vote must be reduced for the free balance to also cover the fee, `None` if
+/// `free` already covers both `vote` and `fee`.
+pub fn fee_shortfall(free: Balance, vote: Balance, fee: Balance) -> Option<Balance> {
+    let needed = vote.as_planck().saturating_add(fee.as_planck());
+    needed
+        .checked_sub(free.as_planck())
+        .filter(|shortfall| *shortfall > 0)
+        .map(Balance::from_planck)
+}
+
+/// whether paying `fee` leaves less than the `existential_deposit` free. Locks don't reduce the
+/// free balance, but the fee does: a transfer-like withdrawal below the existential deposit
+/// would reap the account, so the runtime rejects the extrinsic instead.
+pub fn below_existential_deposit(
+    free: Balance,
+    fee: Balance,
+    existential_deposit: Balance,
+) -> bool {
+    free.as_planck().saturating_sub(fee.as_planck()) < existential_deposit.as_planck()
+}
+
+/// formats the balance in KSM without trailing zeros, e.g. "1.5" for 1_500_000_000_000 Planck
+impl std::fmt::Display for Balance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.format_units(KSM_DECIMALS))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_units_is_in_planck() {
+        assert_eq!(
+            Balance::from_units(0, KSM_DECIMALS),
+            Ok(Balance::from_planck(0))
+        );
+        assert_eq!(
+            Balance::from_units(3, KSM_DECIMALS),
+            Ok(Balance::from_planck(3_000_000_000_000))
+        );
+        assert_eq!(
+            Balance::from_units(3, 10),
+            Ok(Balance::from_planck(30_000_000_000))
+        );
+    }
+
+    #[test]
+    fn formats_without_trailing_zeros() {
+        assert_eq!(Balance::from_planck(1_500_000_000_000).to_string(), "1.5");
+        assert_eq!(Balance::from_planck(2_000_000_000_000).to_string(), "2");
+        assert_eq!(Balance::from_planck(1).to_string(), "0.000000000001");
+        assert_eq!(Balance::from_planck(15).format_units(1), "1.5");
+    }
+
+    fn ksm(input: &str) -> Result<u128, ParseError> {
+        parse_amount(input, KSM_DECIMALS).map(|balance| balance.as_planck())
+    }
+
+    #[test]
+    fn parses_pasted_amounts() {
+        assert_eq!(ksm(" 1,000.5 "), Ok(1_000_500_000_000_000));
+        assert_eq!(ksm("1_000"), Ok(1_000_000_000_000_000));
+        assert_eq!(ksm(".5"), Ok(500_000_000_000));
+        assert_eq!(ksm("1."), Ok(1_000_000_000_000));
+        assert_eq!(ksm("0.000000000001"), Ok(1));
+        // trailing zeros beyond the decimals don't add precision
+        assert_eq!(ksm("1.0000000000000"), Ok(1_000_000_000_000));
+    }
+
+    #[test]
+    fn rejects_invalid_amounts() {
+        assert_eq!(ksm(""), Err(ParseError::Empty));
+        assert_eq!(ksm(" , "), Err(ParseError::Empty));
+        assert_eq!(ksm("."), Err(ParseError::Empty));
+        assert_eq!(ksm("1e3"), Err(ParseError::ScientificNotation));
+        assert_eq!(ksm("2E-1"), Err(ParseError::ScientificNotation));
+        assert_eq!(ksm("-1"), Err(ParseError::InvalidCharacter('-')));
+        assert_eq!(ksm("1 KSM"), Err(ParseError::InvalidCharacter('K')));
+        assert_eq!(ksm("1.2.3"), Err(ParseError::MultipleDecimalPoints));
+        assert_eq!(ksm("0.0000000000001"), Err(ParseError::TooPrecise(12)));
+    }
+
+    #[test]
+    fn amounts_beyond_u128_overflow() {
+        assert_eq!(
+            ksm("340282366920938463463374607.431768211455"),
+            Ok(u128::MAX)
+        );
+        assert_eq!(
+            ksm("340282366920938463463374607.431768211456"),
+            Err(ParseError::Overflow)
+        );
+        assert_eq!(
+            ksm("340282366920938463463374608"),
+            Err(ParseError::Overflow)
+        );
+        assert_eq!(ksm(&"9".repeat(40)), Err(ParseError::Overflow));
+        assert_eq!(
+            Balance::from_units(u128::MAX / 1_000_000_000_000 + 1, KSM_DECIMALS),
+            Err(ParseError::Overflow)
+        );
+    }
+
+    #[test]
+    fn parses_percentages() {
+        assert_eq!(parse_percentage("25%"), Some(Ok(25)));
+        assert_eq!(parse_percentage(" 25 % "), Some(Ok(25)));
+        assert_eq!(parse_percentage("150%"), Some(Ok(100)));
+        assert_eq!(
+            parse_percentage(&format!("{}%", "9".repeat(40))),
+            Some(Ok(100))
+        );
+        assert_eq!(parse_percentage("%"), Some(Err(ParseError::Empty)));
+        assert_eq!(
+            parse_percentage("2.5%"),
+            Some(Err(ParseError::InvalidCharacter('.')))
+        );
+        // an amount
+        assert_eq!(parse_percentage("25"), None);
+    }
+
+    #[test]
+    fn percentage_rounds_down_without_overflowing() {
+        let max = Balance::from_planck(u128::MAX);
+        assert_eq!(max.percentage(100), max);
+        assert_eq!(max.percentage(50), Balance::from_planck(u128::MAX / 2));
+        assert_eq!(max.percentage(200), max);
+        assert_eq!(max.percentage(0), Balance::from_planck(0));
+        assert_eq!(
+            Balance::from_planck(199).percentage(1),
+            Balance::from_planck(1)
+        );
+    }
+
+    #[test]
+    fn fee_shortfall_of_the_vote() {
+        let planck = Balance::from_planck;
+        assert_eq!(fee_shortfall(planck(100), planck(90), planck(5)), None);
+        assert_eq!(fee_shortfall(planck(100), planck(95), planck(5)), None);
+        assert_eq!(
+            fee_shortfall(planck(100), planck(100), planck(5)),
+            Some(planck(5))
+        );
+        assert_eq!(
+            fee_shortfall(planck(100), planck(u128::MAX), planck(5)),
+            Some(planck(u128::MAX - 100))
+        );
+    }
+
+    #[test]
+    fn fee_below_existential_deposit() {
+        let planck = Balance::from_planck;
+        assert!(!below_existential_deposit(
+            planck(100),
+            planck(10),
+            planck(90)
+        ));
+        assert!(below_existential_deposit(
+            planck(100),
+            planck(11),
+            planck(90)
+        ));
+        // a fee above the free balance
+        assert!(below_existential_deposit(planck(5), planck(10), planck(1)));
+        assert!(!below_existential_deposit(planck(5), planck(10), planck(0)));
+    }
+
+    #[test]
+    fn amounts_round_trip_with_the_token_decimals() {
+        // e.g. `?decimals=10`: "1" is one DOT, not one KSM
+        let one = parse_amount("1", 10).unwrap();
+        assert_eq!(one.as_planck(), 10_000_000_000);
+        assert_eq!(one.format_units(10), "1");
+
+        let amount = parse_amount("12.3456789012", 10).unwrap();
+        assert_eq!(amount.as_planck(), 123_456_789_012);
+        assert_eq!(parse_amount(&amount.format_units(10), 10), Ok(amount));
+
+        assert_eq!(
+            parse_amount("0.00000000001", 10),
+            Err(ParseError::TooPrecise(10))
+        );
+        assert_eq!(parse_amount("7", 0), Ok(Balance::from_planck(7)));
+        assert_eq!(parse_amount("0.5", 0), Err(ParseError::TooPrecise(0)));
+    }
+}
@@ -0,0 +1,260 @@
+use anyhow::anyhow;
+use subxt::config::substrate::Era;
+use subxt::ext::codec::{Compact, Decode, Encode};
+use subxt::utils::{AccountId32, MultiAddress, MultiSignature};
+use subxt::Metadata;
+
+use crate::balance::Balance;
+use crate::governance::CastVote;
+use crate::multisig;
+use crate::offline::bundled_metadata;
+use crate::services::{
+    node_runtime::runtime_types::kusama_runtime::RuntimeCall,
+    node_runtime::runtime_types::pallet_conviction_voting::pallet::Call as ConvictionVotingCall,
+    node_runtime::runtime_types::pallet_multisig::pallet::Call as MultisigCall,
+};
+
+/// The call previewed to the user side by side with the call found in the signed extrinsic.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CallComparison {
+    pub preview: String,
+    pub signed: String,
+    /// the signed call is the previewed call, or wraps it (e.g. in a multisig approval)
+    pub matches: bool,
+}
+
+/// decodes the previewed `call_data` and the call inside `signed_extrinsic` and checks that
+/// the signed call is (or wraps) the previewed one.
+pub fn compare_calls(
+    call_data: &[u8],
+    signed_extrinsic: &[u8],
+) -> Result<CallComparison, anyhow::Error> {
+    let preview = RuntimeCall::decode(&mut &call_data[..])?;
+    let signed = decode_extrinsic_call(signed_extrinsic)?;
+    Ok(CallComparison {
+        matches: contains_call(&signed, call_data),
+        preview: format!("{preview:#?}"),
+        signed: format!("{signed:#?}"),
+    })
+}
+
+/// decodes the call of an encoded (signed or unsigned) extrinsic.
+///
+/// The signed extra data is decoded following Kusama's signed extensions: only `CheckMortality`,
+/// `CheckNonce` and `ChargeTransactionPayment` add data to the extrinsic itself.
+pub fn decode_extrinsic_call(extrinsic: &[u8]) -> Result<RuntimeCall, anyhow::Error> {
+    let input = &mut &extrinsic[..];
+    let _length = Compact::<u32>::decode(input)?;
+    let version = u8::decode(input)?;
+    let is_signed = version & 0b1000_0000 != 0;
+    if is_signed {
+        MultiAddress::<AccountId32, ()>::decode(input)?;
+        MultiSignature::decode(input)?;
+        Era::decode(input)?;
+        // nonce
+        Compact::<u64>::decode(input)?;
+        // tip
+        Compact::<u128>::decode(input)?;
+    }
+    Ok(RuntimeCall::decode(input)?)
+}
+
+fn contains_call(call: &RuntimeCall, expected_call_data: &[u8]) -> bool {
+    if call.encode() == expected_call_data {
+        return true;
+    }
+    match call {
+        RuntimeCall::Multisig(MultisigCall::as_multi { call, .. }) => {
+            contains_call(call, expected_call_data)
+        }
+        RuntimeCall::Multisig(MultisigCall::approve_as_multi { call_hash, .. }) => {
+            *call_hash == multisig::call_hash(expected_call_data)
+        }
+        _ => false,
+    }
+}
+
+/// The vote the user chose, to check the encoded call against.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IntendedVote {
+    pub referendum: u32,
+    pub aye: bool,
+    pub balance: Balance,
+    /// 0 (no lock, 0.1x) to 6
+    pub conviction: u8,
+}
+
+/// decodes `call_data` with the generated runtime types and checks it's a standard
+/// `conviction_voting.vote` with exactly the intended parameters.
+pub fn verify_vote_call(call_data: &[u8], intended: &IntendedVote) -> Result<(), anyhow::Error> {
+    let call = RuntimeCall::decode(&mut &call_data[..])?;
+    let RuntimeCall::ConvictionVoting(ConvictionVotingCall::vote { poll_index, vote }) = call
+    else {
+        return Err(anyhow!("the call is not a conviction_voting.vote"));
+    };
+    if poll_index != intended.referendum {
+        return Err(anyhow!("the call votes on referendum #{poll_index}"));
+    }
+    let expected = CastVote::Standard {
+        aye: intended.aye,
+        conviction: intended.conviction,
+        balance: intended.balance,
+    };
+    let decoded = CastVote::from(vote);
+    if decoded != expected {
+        return Err(anyhow!("the call decodes to {decoded:?}"));
+    }
+    Ok(())
+}
+
+/// pallet and call index of `conviction_voting.vote` in `metadata`.
+fn vote_call_index(metadata: &Metadata) -> Result<(u8, u8), anyhow::Error> {
+    let pallet = metadata
+        .pallet_by_name("ConvictionVoting")
+        .ok_or(anyhow!("the ConvictionVoting pallet is missing"))?;
+    let call = pallet
+        .call_variant_by_name("vote")
+        .ok_or(anyhow!("the ConvictionVoting.vote call is missing"))?;
+    Ok((pallet.index(), call.index))
+}
+
+/// checks that `conviction_voting.vote` has the same pallet and call index in the live
+/// metadata as in the bundled metadata the runtime types are generated from. A runtime
+/// upgrade reindexing it would otherwise make the app encode a different call.
+pub fn check_vote_call_index(live: &Metadata) -> Result<(), anyhow::Error> {
+    let bundled = vote_call_index(&bundled_metadata()?)?;
+    let live = vote_call_index(live)?;
+    if bundled != live {
+        return Err(anyhow!(
+            "conviction_voting.vote is call {live:?} on chain but {bundled:?} in the bundled metadata"
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::offline::signed_extrinsic_bytes;
+    use crate::services::node_runtime::runtime_types::pallet_conviction_voting::vote::{
+        AccountVote, Vote,
+    };
+
+    fn vote_call(referendum: u32, aye: bool, conviction: u8, planck: u128) -> RuntimeCall {
+        let aye_flag = if aye { 0b1000_0000 } else { 0 };
+        RuntimeCall::ConvictionVoting(ConvictionVotingCall::vote {
+            poll_index: referendum,
+            vote: AccountVote::Standard {
+                vote: Vote(aye_flag | conviction),
+                balance: planck,
+            },
+        })
+    }
+
+    fn signed(call: &RuntimeCall) -> Vec<u8> {
+        signed_extrinsic_bytes(
+            &call.encode(),
+            AccountId32([1; 32]),
+            &MultiSignature::Sr25519([2; 64]),
+            0,
+        )
+    }
+
+    #[test]
+    fn compares_the_signed_call_with_the_preview() {
+        let preview = vote_call(275, true, 1, 1_000).encode();
+        let comparison = compare_calls(&preview, &signed(&vote_call(275, true, 1, 1_000))).unwrap();
+        assert!(comparison.matches);
+        assert_eq!(comparison.preview, comparison.signed);
+
+        let comparison = compare_calls(&preview, &signed(&vote_call(275, true, 1, 1_001))).unwrap();
+        assert!(!comparison.matches);
+        assert_ne!(comparison.preview, comparison.signed);
+
+        assert!(compare_calls(&preview, &[0x00]).is_err());
+    }
+
+    #[test]
+    fn finds_the_preview_in_multisig_calls() {
+        let vote = vote_call(275, false, 0, 1_000);
+        let preview = vote.encode();
+        let as_multi = |call: RuntimeCall| {
+            RuntimeCall::Multisig(MultisigCall::as_multi {
+                threshold: 2,
+                other_signatories: vec![AccountId32([3; 32])],
+                maybe_timepoint: None,
+                call: Box::new(call),
+                max_weight: multisig::vote_max_weight(),
+            })
+        };
+        let approve_as_multi = |call_data: &[u8]| {
+            RuntimeCall::Multisig(MultisigCall::approve_as_multi {
+                threshold: 2,
+                other_signatories: vec![AccountId32([3; 32])],
+                maybe_timepoint: None,
+                call_hash: multisig::call_hash(call_data),
+                max_weight: multisig::vote_max_weight(),
+            })
+        };
+
+        assert!(contains_call(
+            &as_multi(vote_call(275, false, 0, 1_000)),
+            &preview
+        ));
+        assert!(!contains_call(
+            &as_multi(vote_call(276, false, 0, 1_000)),
+            &preview
+        ));
+        assert!(contains_call(&approve_as_multi(&preview), &preview));
+        assert!(!contains_call(&approve_as_multi(&[0x00]), &preview));
+
+        let comparison = compare_calls(&preview, &signed(&as_multi(vote))).unwrap();
+        assert!(comparison.matches);
+    }
+
+    #[test]
+    fn decodes_unsigned_extrinsics() {
+        let call = vote_call(275, true, 6, 1);
+        let mut extrinsic = Compact(call.encoded_size() as u32 + 1).encode();
+        extrinsic.push(4);
+        extrinsic.extend(call.encode());
+        let decoded = decode_extrinsic_call(&extrinsic).unwrap();
+        assert_eq!(decoded.encode(), call.encode());
+    }
+
+    #[test]
+    fn verifies_the_intended_vote() {
+        let intended = IntendedVote {
+            referendum: 275,
+            aye: true,
+            balance: Balance::from_planck(1_000),
+            conviction: 1,
+        };
+        let verify = |call: RuntimeCall| verify_vote_call(&call.encode(), &intended);
+        assert!(verify(vote_call(275, true, 1, 1_000)).is_ok());
+
+        let err = verify(vote_call(276, true, 1, 1_000)).unwrap_err();
+        assert_eq!(err.to_string(), "the call votes on referendum #276");
+        // an index beyond u16 is a plain u32 in the call
+        let err = verify(vote_call(70_000, true, 1, 1_000)).unwrap_err();
+        assert_eq!(err.to_string(), "the call votes on referendum #70000");
+
+        for mismatch in [
+            vote_call(275, false, 1, 1_000),
+            vote_call(275, true, 2, 1_000),
+            vote_call(275, true, 1, 999),
+        ] {
+            let err = verify(mismatch).unwrap_err();
+            assert!(err.to_string().starts_with("the call decodes to"), "{err}");
+        }
+
+        let remark = RuntimeCall::System(
+            crate::services::node_runtime::runtime_types::frame_system::pallet::Call::remark {
+                remark: vec![],
+            },
+        );
+        let err = verify(remark).unwrap_err();
+        assert_eq!(err.to_string(), "the call is not a conviction_voting.vote");
+        assert!(verify_vote_call(&[0xff], &intended).is_err());
+    }
+}
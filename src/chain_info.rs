@@ -0,0 +1,140 @@
+use subxt::utils::AccountId32;
+use subxt::{OnlineClient, PolkadotConfig};
+
+use crate::accounts::{encode_ss58, parse_account_id};
+use crate::balance::{Balance, KSM_DECIMALS};
+use crate::explorer::Chain;
+
+/// Networks known without asking the node, from the ss58 registry.
+/// `(ss58 prefix, network, token symbol, token decimals, explorer)`
+const SS58_REGISTRY: &[(u16, &str, &str, u32, Chain)] = &[
+    (0, "Polkadot", "DOT", 10, Chain::Polkadot),
+    (2, "Kusama", "KSM", KSM_DECIMALS, Chain::Kusama),
+];
+
+/// highest token decimals accepted as an override, more than any known chain uses
+pub const MAX_DECIMALS: u32 = 18;
+
+/// Token symbol and decimals set by hand, e.g. with `?symbol=DOT&decimals=10` for a dev chain
+/// without (or with wrong) properties. Replace what's read from the node or the registry.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TokenOverride {
+    pub symbol: Option<String>,
+    pub decimals: Option<u32>,
+}
+
+impl TokenOverride {
+    /// parses the raw `symbol` and `decimals`, ignoring an empty symbol and decimals that
+    /// aren't a number from 0 to `MAX_DECIMALS`
+    pub fn parse(symbol: Option<String>, decimals: Option<String>) -> Self {
+        TokenOverride {
+            symbol: symbol
+                .map(|symbol| symbol.trim().to_string())
+                .filter(|symbol| !symbol.is_empty()),
+            decimals: decimals
+                .and_then(|decimals| decimals.trim().parse::<u32>().ok())
+                .filter(|decimals| *decimals <= MAX_DECIMALS),
+        }
+    }
+}
+
+/// How balances and addresses of the connected chain are displayed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChainInfo {
+    /// network name, e.g. "Kusama"
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u32,
+    pub ss58_prefix: u16,
+    /// explorer links are built for
+    pub explorer: Chain,
+}
+
+impl ChainInfo {
+    /// the registry entry of Kusama, used until the node's properties are known
+    pub fn kusama() -> Self {
+        Self::from_registry(2).expect("Kusama is in the registry")
+    }
+
+    pub fn from_registry(ss58_prefix: u16) -> Option<Self> {
+        SS58_REGISTRY
+            .iter()
+            .find(|(prefix, ..)| *prefix == ss58_prefix)
+            .map(|(prefix, name, symbol, decimals, explorer)| ChainInfo {
+                name: name.to_string(),
+                symbol: symbol.to_string(),
+                decimals: *decimals,
+                ss58_prefix: *prefix,
+                explorer: *explorer,
+            })
+    }
+
+    /// reads the chain name and token properties from the node (`system_chain` and
+    /// `system_properties`), falling back to the registry for anything missing.
+    pub async fn fetch(api: &OnlineClient<PolkadotConfig>) -> Result<Self, subxt::Error> {
+        let properties = api.rpc().system_properties().await?;
+        let name = api.rpc().system_chain().await?;
+
+        let ss58_prefix = properties
+            .get("ss58Format")
+            .and_then(|value| value.as_u64())
+            .map(|prefix| prefix as u16)
+            .unwrap_or(2);
+        let fallback = Self::from_registry(ss58_prefix).unwrap_or_else(Self::kusama);
+        // multi token chains list the native token first
+        let first = |value: &serde_json::Value| match value {
+            serde_json::Value::Array(values) => values.first().cloned(),
+            value => Some(value.clone()),
+        };
+        let symbol = properties
+            .get("tokenSymbol")
+            .and_then(first)
+            .and_then(|value| value.as_str().map(String::from))
+            .unwrap_or(fallback.symbol);
+        let decimals = properties
+            .get("tokenDecimals")
+            .and_then(first)
+            .and_then(|value| value.as_u64())
+            .map(|decimals| decimals as u32)
+            .unwrap_or(fallback.decimals);
+
+        Ok(ChainInfo {
+            name,
+            symbol,
+            decimals,
+            ss58_prefix,
+            explorer: fallback.explorer,
+        })
+    }
+
+    /// the chain info with the token symbol and decimals of `token_override`, where set
+    pub fn with_override(mut self, token_override: &TokenOverride) -> Self {
+        if let Some(symbol) = &token_override.symbol {
+            self.symbol = symbol.clone();
+        }
+        if let Some(decimals) = token_override.decimals {
+            self.decimals = decimals;
+        }
+        self
+    }
+
+    /// formats a balance with the token symbol, e.g. "1.5 KSM"
+    pub fn format_balance(&self, balance: Balance) -> String {
+        format!("{} {}", balance.format_units(self.decimals), self.symbol)
+    }
+
+    /// ss58 encodes an account with the network's prefix
+    pub fn format_address(&self, account_id: &AccountId32) -> String {
+        encode_ss58(account_id, self.ss58_prefix)
+    }
+
+    /// re-encodes an ss58 address of any network (e.g. generic 42) with the network's prefix,
+    /// or returns it as is if it can't be decoded. Display only, signing uses the address as
+    /// returned by the extension.
+    pub fn display_address(&self, address: &str) -> String {
+        parse_account_id(address).map_or_else(
+            |_| address.to_string(),
+            |account_id| self.format_address(&account_id),
+        )
+    }
+}
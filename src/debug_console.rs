@@ -0,0 +1,44 @@
+use std::cell::RefCell;
+
+use wasm_bindgen::prelude::*;
+use yew::html::Scope;
+
+use crate::vote::{Message, VoteComponent};
+
+// Commands to drive and inspect the running app from the browser console, only built with
+// the `debug` feature. They message the mounted `VoteComponent`, results are logged.
+
+thread_local! {
+    static VOTE_SCOPE: RefCell<Option<Scope<VoteComponent>>> = const { RefCell::new(None) };
+}
+
+/// registers the mounted component the commands are sent to, `None` once it's destroyed
+pub fn set_vote_scope(scope: Option<Scope<VoteComponent>>) {
+    VOTE_SCOPE.with(|cell| *cell.borrow_mut() = scope);
+}
+
+fn send(message: Message) {
+    VOTE_SCOPE.with(|cell| match cell.borrow().as_ref() {
+        Some(scope) => scope.send_message(message),
+        None => web_sys::console::warn_1(&"The vote component is not mounted".into()),
+    });
+}
+
+/// logs the stage and the encoded call that would be signed
+#[wasm_bindgen]
+pub fn debug_dump_call() {
+    send(Message::DebugDumpCall);
+}
+
+/// votes on referendum `id` instead, as if the page was opened on `/ref/<id>`
+#[wasm_bindgen]
+pub fn debug_set_referendum(id: u32) {
+    send(Message::DebugSetReferendum(id));
+}
+
+/// moves to a stage: "enter_message", "enter_balance", "select_account" (requests the
+/// accounts again) or "error"
+#[wasm_bindgen]
+pub fn debug_force_stage(name: String) {
+    send(Message::DebugForceStage(name));
+}
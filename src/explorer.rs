@@ -0,0 +1,184 @@
+use anyhow::anyhow;
+
+use crate::services::KUSAMA_RPC_ENDPOINT;
+
+const POLKADOT_RPC_ENDPOINT: &str = "wss://rpc.ibp.network/polkadot";
+
+/// Chains explorer links can be built for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Chain {
+    Kusama,
+    Polkadot,
+}
+
+impl Chain {
+    pub fn subscan_base_url(&self) -> &'static str {
+        match self {
+            Self::Kusama => "https://kusama.subscan.io",
+            Self::Polkadot => "https://polkadot.subscan.io",
+        }
+    }
+
+    pub fn rpc_endpoint(&self) -> &'static str {
+        match self {
+            Self::Kusama => KUSAMA_RPC_ENDPOINT,
+            Self::Polkadot => POLKADOT_RPC_ENDPOINT,
+        }
+    }
+}
+
+/// Explorer the links point to: the chain's Subscan, unless a custom base URL is set.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Explorer {
+    pub chain: Chain,
+    /// base URL of a self-hosted or alternative explorer with Subscan's paths
+    pub custom_base_url: Option<String>,
+}
+
+impl Explorer {
+    fn base_url(&self) -> &str {
+        self.custom_base_url
+            .as_deref()
+            .unwrap_or(self.chain.subscan_base_url())
+    }
+}
+
+/// validates a custom explorer base URL, returned without trailing slash.
+pub fn parse_explorer_base_url(input: &str) -> Result<String, anyhow::Error> {
+    let url = input.trim().trim_end_matches('/');
+    let host = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .ok_or(anyhow!("The URL must start with https:// or http://"))?;
+    if host.is_empty() || host.starts_with('/') {
+        return Err(anyhow!("The URL has no host"));
+    }
+    if url
+        .chars()
+        .any(|c| c.is_whitespace() || matches!(c, '?' | '#'))
+    {
+        return Err(anyhow!(
+            "The URL can't contain spaces, a query (?) or a fragment (#)"
+        ));
+    }
+    Ok(url.to_string())
+}
+
+/// explorer page of an extrinsic, `extrinsic` being its hash or `<block>-<index>`
+pub fn extrinsic_url(explorer: &Explorer, extrinsic: &str) -> String {
+    format!("{}/extrinsic/{}", explorer.base_url(), extrinsic)
+}
+
+/// explorer page of an ss58 encoded account
+pub fn account_url(explorer: &Explorer, address: &str) -> String {
+    format!("{}/account/{}", explorer.base_url(), address)
+}
+
+/// explorer page of an OpenGov referendum
+pub fn referendum_url(explorer: &Explorer, referendum: u32) -> String {
+    format!("{}/referenda_v2/{}", explorer.base_url(), referendum)
+}
+
+/// explorer page of a block, `block` being its number or hash
+pub fn block_url(explorer: &Explorer, block: &str) -> String {
+    format!("{}/block/{}", explorer.base_url(), block)
+}
+
+/// longest link that browsers, chat apps and QR codes reliably handle
+pub const MAX_LINK_LENGTH: usize = 2000;
+
+/// polkadot.js apps page decoding the hex encoded `call_data`
+pub fn decode_call_url(chain: Chain, call_data: &str) -> String {
+    format!(
+        "https://polkadot.js.org/apps/?rpc={}#/extrinsics/decode/{}",
+        chain.rpc_endpoint(),
+        call_data
+    )
+}
+
+/// polkadot.js apps page decoding the hex encoded signed `extrinsic`, to inspect it and submit
+/// it from apps. `None` if the link would be longer than `MAX_LINK_LENGTH`.
+pub fn decode_extrinsic_url(chain: Chain, extrinsic: &str) -> Option<String> {
+    // the decode page accepts both calls and full extrinsics
+    Some(decode_call_url(chain, extrinsic)).filter(|url| url.len() <= MAX_LINK_LENGTH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subscan(chain: Chain) -> Explorer {
+        Explorer {
+            chain,
+            custom_base_url: None,
+        }
+    }
+
+    #[test]
+    fn links_to_subscan_or_the_custom_explorer() {
+        let kusama = subscan(Chain::Kusama);
+        assert_eq!(
+            referendum_url(&kusama, 275),
+            "https://kusama.subscan.io/referenda_v2/275"
+        );
+        assert_eq!(
+            extrinsic_url(&subscan(Chain::Polkadot), "100-2"),
+            "https://polkadot.subscan.io/extrinsic/100-2"
+        );
+        let custom = Explorer {
+            custom_base_url: Some("https://explorer.example".to_string()),
+            ..kusama
+        };
+        assert_eq!(
+            account_url(&custom, "HNZata7iMYWmk5RvZRTiAsSDhV8366zq2YGb3tLH5Upf74F"),
+            "https://explorer.example/account/HNZata7iMYWmk5RvZRTiAsSDhV8366zq2YGb3tLH5Upf74F"
+        );
+        assert_eq!(
+            block_url(&custom, "0x01"),
+            "https://explorer.example/block/0x01"
+        );
+    }
+
+    #[test]
+    fn parses_custom_base_urls() {
+        assert_eq!(
+            parse_explorer_base_url(" https://explorer.example/kusama/ ").unwrap(),
+            "https://explorer.example/kusama"
+        );
+        assert_eq!(
+            parse_explorer_base_url("http://localhost:8080").unwrap(),
+            "http://localhost:8080"
+        );
+        for invalid in [
+            "explorer.example",
+            "ftp://explorer.example",
+            "https://",
+            "https:///path",
+            "https://explorer.example/?chain=kusama",
+            "https://explorer.example/#kusama",
+            "https://explorer .example",
+        ] {
+            assert!(parse_explorer_base_url(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn decode_links_up_to_the_max_length() {
+        assert_eq!(
+            decode_call_url(Chain::Kusama, "0x0001"),
+            format!(
+                "https://polkadot.js.org/apps/?rpc={KUSAMA_RPC_ENDPOINT}#/extrinsics/decode/0x0001"
+            )
+        );
+        let prefix_len = decode_call_url(Chain::Kusama, "").len();
+        let fits = "0".repeat(MAX_LINK_LENGTH - prefix_len);
+        assert_eq!(
+            decode_extrinsic_url(Chain::Kusama, &fits).map(|url| url.len()),
+            Some(MAX_LINK_LENGTH)
+        );
+        assert_eq!(
+            decode_extrinsic_url(Chain::Kusama, &format!("{fits}0")),
+            None
+        );
+    }
+}
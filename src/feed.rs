@@ -0,0 +1,104 @@
+use strum_macros::EnumIter;
+use wasm_bindgen::JsValue;
+
+use crate::services::FinalizedBlock;
+
+/// A block of the feed, timestamped when the component received it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeedEntry {
+    /// milliseconds since the epoch, from `Date.now()`
+    pub received_at: f64,
+    pub block: FinalizedBlock,
+}
+
+impl FeedEntry {
+    pub fn received(block: FinalizedBlock) -> Self {
+        Self {
+            received_at: js_sys::Date::now(),
+            block,
+        }
+    }
+
+    /// receive time in the browser's locale and timezone, e.g. "12:01:03"
+    pub fn received_time(&self) -> String {
+        js_sys::Date::new(&JsValue::from_f64(self.received_at))
+            .to_locale_time_string("default")
+            .into()
+    }
+
+    /// time since the block was received, e.g. "3s ago" or "2m ago", relative to `now` in
+    /// milliseconds since the epoch
+    pub fn age(&self, now: f64) -> String {
+        let seconds = ((now - self.received_at) / 1000.0).max(0.0) as u64;
+        match seconds {
+            0..=59 => format!("{seconds}s ago"),
+            60..=3599 => format!("{}m ago", seconds / 60),
+            _ => format!("{}h ago", seconds / 3600),
+        }
+    }
+}
+
+/// What the finalized blocks feed shows for each block.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, EnumIter)]
+pub enum FeedVerbosity {
+    Number,
+    #[default]
+    NumberHash,
+    /// number, extrinsics count, hash and author
+    Full,
+}
+
+impl FeedVerbosity {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Number => "Number",
+            Self::NumberHash => "Number and hash",
+            Self::Full => "Full",
+        }
+    }
+
+    /// value persisted in `localStorage`
+    pub fn key(&self) -> &'static str {
+        match self {
+            Self::Number => "number",
+            Self::NumberHash => "hash",
+            Self::Full => "full",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "number" => Some(Self::Number),
+            "hash" => Some(Self::NumberHash),
+            "full" => Some(Self::Full),
+            _ => None,
+        }
+    }
+
+    /// e.g. "12:01:03 (3s ago) • finalized • #12345678 • 42 extrinsics", the feed only follows
+    /// finalized blocks. The count is "—" if it could not be read.
+    pub fn format(&self, entry: &FeedEntry, now: f64) -> String {
+        let block = &entry.block;
+        let prefix = format!(
+            "{} ({}) • finalized • #{} • {} extrinsics",
+            entry.received_time(),
+            entry.age(now),
+            block.number,
+            block
+                .extrinsics_count
+                .map_or("—".to_string(), |count| count.to_string())
+        );
+        match self {
+            Self::Number => prefix,
+            Self::NumberHash => format!("{prefix} • {:?}", block.hash),
+            Self::Full => format!(
+                "{prefix} • {:?} • author {}",
+                block.hash,
+                block
+                    .author
+                    .as_ref()
+                    .map_or("unknown".to_string(), |author| author.to_string())
+            ),
+        }
+    }
+}
@@ -0,0 +1,627 @@
+use subxt::ext::codec::Encode;
+use subxt::utils::AccountId32;
+use subxt::{OnlineClient, PolkadotConfig};
+
+use crate::balance::Balance;
+use crate::chain_info::ChainInfo;
+use crate::services::{
+    node_runtime,
+    node_runtime::runtime_types::pallet_conviction_voting::vote::{AccountVote, Vote, Voting},
+    node_runtime::runtime_types::pallet_referenda::types::ReferendumInfo,
+};
+use crate::vote::Conviction;
+
+/// A referenda track (conviction voting class), read from the runtime.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Track {
+    pub id: u16,
+    /// name as defined in the runtime, e.g. "root"
+    pub name: String,
+    /// blocks a conviction of 1x locks the balance for after a referendum of the track ends.
+    /// `ConvictionVoting::VoteLockingPeriod`, currently the same for every track.
+    pub lock_period: u32,
+}
+
+/// reads the referenda tracks from the `Referenda::Tracks` constant of the runtime.
+pub fn read_tracks(api: &OnlineClient<PolkadotConfig>) -> Result<Vec<Track>, subxt::Error> {
+    let tracks = api
+        .constants()
+        .at(&node_runtime::constants().referenda().tracks())?;
+    let lock_period = api.constants().at(&node_runtime::constants()
+        .conviction_voting()
+        .vote_locking_period())?;
+    Ok(tracks
+        .into_iter()
+        .map(|(id, info)| Track {
+            id,
+            name: info.name,
+            lock_period,
+        })
+        .collect())
+}
+
+/// A vote recorded in `ConvictionVoting::VotingFor` for a given account and track.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrackVote {
+    /// track (class) id, e.g. 0 for root
+    pub track: u16,
+    /// track name as defined in the runtime, e.g. "root"
+    pub track_name: String,
+    pub referendum: u32,
+    pub vote: CastVote,
+}
+
+/// Decoded `AccountVote`, independent from the generated runtime types.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CastVote {
+    Standard {
+        aye: bool,
+        conviction: u8,
+        balance: Balance,
+    },
+    Split {
+        aye: Balance,
+        nay: Balance,
+    },
+    SplitAbstain {
+        aye: Balance,
+        nay: Balance,
+        abstain: Balance,
+    },
+}
+
+impl From<AccountVote<u128>> for CastVote {
+    fn from(vote: AccountVote<u128>) -> Self {
+        match vote {
+            AccountVote::Standard { vote, balance } => Self::Standard {
+                // the highest bit of the vote byte is the aye flag, the rest is the conviction
+                aye: vote.0 & 0b1000_0000 == 0b1000_0000,
+                conviction: vote.0 & 0b0111_1111,
+                balance: Balance::from_planck(balance),
+            },
+            AccountVote::Split { aye, nay } => Self::Split {
+                aye: Balance::from_planck(aye),
+                nay: Balance::from_planck(nay),
+            },
+            AccountVote::SplitAbstain { aye, nay, abstain } => Self::SplitAbstain {
+                aye: Balance::from_planck(aye),
+                nay: Balance::from_planck(nay),
+                abstain: Balance::from_planck(abstain),
+            },
+        }
+    }
+}
+
+impl From<&CastVote> for AccountVote<u128> {
+    fn from(vote: &CastVote) -> Self {
+        match vote {
+            CastVote::Standard {
+                aye,
+                conviction,
+                balance,
+            } => {
+                let aye_flag = if *aye { 0b1000_0000 } else { 0 };
+                AccountVote::Standard {
+                    vote: Vote(aye_flag | conviction),
+                    balance: balance.as_planck(),
+                }
+            }
+            CastVote::Split { aye, nay } => AccountVote::Split {
+                aye: aye.as_planck(),
+                nay: nay.as_planck(),
+            },
+            CastVote::SplitAbstain { aye, nay, abstain } => AccountVote::SplitAbstain {
+                aye: aye.as_planck(),
+                nay: nay.as_planck(),
+                abstain: abstain.as_planck(),
+            },
+        }
+    }
+}
+
+impl CastVote {
+    /// e.g. "AYE • 1 KSM • 1x", or "0.1x" for a vote without conviction
+    pub fn format(&self, chain_info: &ChainInfo) -> String {
+        let balance = |balance: &Balance| chain_info.format_balance(*balance);
+        match self {
+            Self::Standard {
+                aye,
+                conviction,
+                balance: amount,
+            } => {
+                let direction = if *aye { "AYE" } else { "NAY" };
+                // the conviction index is the position of the picker's slider
+                let conviction = Conviction::from_slider(*conviction);
+                format!("{direction} • {} • {conviction}", balance(amount))
+            }
+            Self::Split { aye, nay } => {
+                format!("SPLIT • {} aye / {} nay", balance(aye), balance(nay))
+            }
+            Self::SplitAbstain { aye, nay, abstain } => format!(
+                "ABSTAIN • {} aye / {} nay / {} abstain",
+                balance(aye),
+                balance(nay),
+                balance(abstain)
+            ),
+        }
+    }
+}
+
+/// An active delegation of the account's voting power on a track.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Delegation {
+    pub track: u16,
+    pub target: AccountId32,
+    pub balance: Balance,
+    /// conviction name as defined in the runtime, e.g. "Locked1x"
+    pub conviction: String,
+}
+
+/// The balance locked by conviction voting before and after casting a vote.
+///
+/// Locks are tracked per track (class), but they overlap: the locked balance is the largest
+/// lock across tracks, not their sum.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LockImpact {
+    /// track of the referendum voted on, `None` if it's not ongoing
+    pub track: Option<u16>,
+    pub current: Balance,
+    pub after: Balance,
+}
+
+impl LockImpact {
+    /// computes the impact of voting with `balance` on `track` given the account's `ClassLocksFor`
+    pub fn new(class_locks: &[(u16, Balance)], track: Option<u16>, balance: Balance) -> Self {
+        let current = class_locks
+            .iter()
+            .map(|(_, locked)| *locked)
+            .max()
+            .unwrap_or_default();
+        let after = match track {
+            Some(track) => {
+                let track_lock = class_locks
+                    .iter()
+                    .find(|(class, _)| *class == track)
+                    .map(|(_, locked)| *locked)
+                    .unwrap_or_default();
+                current.max(track_lock.max(balance))
+            }
+            None => current,
+        };
+        LockImpact {
+            track,
+            current,
+            after,
+        }
+    }
+}
+
+/// fetches the conviction voting locks of `account_id` and computes the impact of voting
+/// with `balance` on `referendum`
+pub async fn fetch_lock_impact(
+    api: &OnlineClient<PolkadotConfig>,
+    account_id: &AccountId32,
+    referendum: u32,
+    balance: Balance,
+) -> Result<LockImpact, subxt::Error> {
+    let class_locks_for = node_runtime::storage()
+        .conviction_voting()
+        .class_locks_for(account_id);
+    let class_locks = api
+        .storage()
+        .at_latest()
+        .await?
+        .fetch(&class_locks_for)
+        .await?
+        .map(|locks| {
+            locks
+                .0
+                .into_iter()
+                .map(|(class, locked)| (class, Balance::from_planck(locked)))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let track = fetch_referendum_track(api, referendum).await?;
+    Ok(LockImpact::new(&class_locks, track, balance))
+}
+
+/// A balance locked by conviction voting until a block.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LockEntry {
+    pub amount: Balance,
+    /// first block at which the balance can be unlocked
+    pub unlock_block: u32,
+    /// the referendum or delegation hasn't ended yet, so its lock period hasn't started and
+    /// `unlock_block` is the earliest possible
+    pub earliest: bool,
+}
+
+impl LockEntry {
+    /// lock of a vote for `lock_periods` vote locking periods that hasn't ended by `block`
+    pub fn pending(amount: Balance, lock_periods: u32, block: u32, lock_period: u32) -> Self {
+        LockEntry {
+            amount,
+            unlock_block: block.saturating_add(lock_periods.saturating_mul(lock_period)),
+            earliest: true,
+        }
+    }
+}
+
+/// vote locking periods of a conviction, from 0 for none to 6 for 6x
+fn conviction_lock_periods(conviction: u8) -> u32 {
+    match conviction {
+        0 => 0,
+        conviction => 1 << (conviction.min(6) - 1),
+    }
+}
+
+/// Conviction voting locks of an account, read at `current_block`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LockSchedule {
+    pub current_block: u32,
+    pub entries: Vec<LockEntry>,
+}
+
+/// the balance locked at `block`. Locks of different votes and tracks overlap, so it's the
+/// largest lock not unlockable yet.
+pub fn locked_at(entries: &[LockEntry], block: u32) -> Balance {
+    entries
+        .iter()
+        .filter(|entry| entry.unlock_block > block)
+        .map(|entry| entry.amount)
+        .max()
+        .unwrap_or_default()
+}
+
+/// the locked balance from `from_block` on, as `(block, locked)` steps: the balance stays
+/// locked from each step's block until the next one, and nothing is locked after the last.
+pub fn lock_steps(entries: &[LockEntry], from_block: u32) -> Vec<(u32, Balance)> {
+    let mut blocks: Vec<u32> = entries
+        .iter()
+        .map(|entry| entry.unlock_block)
+        .filter(|block| *block > from_block)
+        .collect();
+    blocks.sort_unstable();
+    blocks.dedup();
+    std::iter::once(from_block)
+        .chain(blocks)
+        .map(|block| (block, locked_at(entries, block)))
+        .collect()
+}
+
+/// fetches the conviction voting locks of `account_id` on the given tracks: prior locks of
+/// ended votes, and the earliest unlock of ongoing votes and delegations.
+pub async fn fetch_lock_schedule(
+    api: &OnlineClient<PolkadotConfig>,
+    account_id: &AccountId32,
+    tracks: &[Track],
+) -> Result<LockSchedule, subxt::Error> {
+    let block = api.blocks().at_latest().await?;
+    let current_block = block.number();
+    let storage = block.storage();
+
+    let prior_entry = |unlock_block: u32, amount: u128| LockEntry {
+        amount: Balance::from_planck(amount),
+        unlock_block,
+        earliest: false,
+    };
+    let mut entries = vec![];
+    for track in tracks {
+        let voting_for = node_runtime::storage()
+            .conviction_voting()
+            .voting_for(account_id, track.id);
+        match storage.fetch(&voting_for).await? {
+            Some(Voting::Casting(casting)) => {
+                entries.push(prior_entry(casting.prior.0, casting.prior.1));
+                for (_, vote) in casting.votes.0 {
+                    let (amount, conviction) = match CastVote::from(vote) {
+                        CastVote::Standard {
+                            conviction,
+                            balance,
+                            ..
+                        } => (balance, conviction),
+                        // split votes have no conviction, they're locked until the end only
+                        CastVote::Split { aye, nay } => (aye.max(nay), 0),
+                        CastVote::SplitAbstain { aye, nay, abstain } => {
+                            (aye.max(nay).max(abstain), 0)
+                        }
+                    };
+                    entries.push(LockEntry::pending(
+                        amount,
+                        conviction_lock_periods(conviction),
+                        current_block,
+                        track.lock_period,
+                    ));
+                }
+            }
+            Some(Voting::Delegating(delegating)) => {
+                entries.push(prior_entry(delegating.prior.0, delegating.prior.1));
+                entries.push(LockEntry::pending(
+                    Balance::from_planck(delegating.balance),
+                    conviction_lock_periods(delegating.conviction.encode()[0]),
+                    current_block,
+                    track.lock_period,
+                ));
+            }
+            Some(_) | None => {}
+        }
+    }
+    entries.retain(|entry| entry.amount > Balance::default());
+    Ok(LockSchedule {
+        current_block,
+        entries,
+    })
+}
+
+/// fetches the votes currently recorded for `account_id` across the given referenda tracks.
+///
+/// Tracks where the account is delegating are skipped, since no direct votes are recorded there.
+pub async fn fetch_account_votes(
+    api: &OnlineClient<PolkadotConfig>,
+    account_id: &AccountId32,
+    tracks: &[Track],
+) -> Result<Vec<TrackVote>, subxt::Error> {
+    let storage = api.storage().at_latest().await?;
+
+    let mut votes = vec![];
+    for track in tracks {
+        let voting_for = node_runtime::storage()
+            .conviction_voting()
+            .voting_for(account_id, track.id);
+        if let Some(Voting::Casting(casting)) = storage.fetch(&voting_for).await? {
+            for (referendum, vote) in casting.votes.0 {
+                votes.push(TrackVote {
+                    track: track.id,
+                    track_name: track.name.clone(),
+                    referendum,
+                    vote: vote.into(),
+                });
+            }
+        }
+    }
+    Ok(votes)
+}
+
+/// How an account voted on a referendum, see `lookup_vote`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum VoteLookup {
+    Voted(TrackVote),
+    /// no direct vote, the voting power is delegated on the referendum's track
+    Delegating(Delegation),
+    NotVoted,
+}
+
+/// looks up the vote of `account_id` on `referendum` in `ConvictionVoting::VotingFor`.
+///
+/// Votes stay recorded after the referendum ends, until they're removed to unlock, so every
+/// track is searched. Delegations are only found while the referendum is ongoing.
+pub async fn lookup_vote(
+    api: &OnlineClient<PolkadotConfig>,
+    account_id: &AccountId32,
+    referendum: u32,
+    tracks: &[Track],
+) -> Result<VoteLookup, subxt::Error> {
+    let votes = fetch_account_votes(api, account_id, tracks).await?;
+    if let Some(vote) = votes.into_iter().find(|vote| vote.referendum == referendum) {
+        return Ok(VoteLookup::Voted(vote));
+    }
+    Ok(
+        match fetch_delegation_for_referendum(api, account_id, referendum).await? {
+            Some(delegation) => VoteLookup::Delegating(delegation),
+            None => VoteLookup::NotVoted,
+        },
+    )
+}
+
+/// fetches the free balance of `account_id`, which conviction votes can use in full.
+pub async fn fetch_free_balance(
+    api: &OnlineClient<PolkadotConfig>,
+    account_id: &AccountId32,
+) -> Result<Balance, subxt::Error> {
+    let account = api
+        .storage()
+        .at_latest()
+        .await?
+        .fetch_or_default(&node_runtime::storage().system().account(account_id))
+        .await?;
+    Ok(Balance::from_planck(account.data.free))
+}
+
+/// Votes counted so far on an ongoing referendum.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tally {
+    /// conviction weighted AYE votes
+    pub ayes: Balance,
+    /// conviction weighted NAY votes
+    pub nays: Balance,
+    /// AYE balance, without conviction
+    pub support: Balance,
+}
+
+impl Tally {
+    /// share of the AYE votes in percent, `None` without votes
+    pub fn approval(&self) -> Option<f64> {
+        let total = self.ayes.as_planck() as f64 + self.nays.as_planck() as f64;
+        (total > 0.0).then(|| self.ayes.as_planck() as f64 / total * 100.0)
+    }
+
+    /// the tally once an AYE vote of `balance` weighing `votes` is added
+    pub fn with_aye(&self, balance: Balance, votes: Balance) -> Self {
+        let add = |a: Balance, b: Balance| {
+            Balance::from_planck(a.as_planck().saturating_add(b.as_planck()))
+        };
+        Self {
+            ayes: add(self.ayes, votes),
+            nays: self.nays,
+            support: add(self.support, balance),
+        }
+    }
+}
+
+/// fetches the tally of a referendum, `None` if the referendum is not ongoing.
+pub async fn fetch_tally(
+    api: &OnlineClient<PolkadotConfig>,
+    referendum: u32,
+) -> Result<Option<Tally>, subxt::Error> {
+    let referendum_info_for = node_runtime::storage()
+        .referenda()
+        .referendum_info_for(referendum);
+    let info = api
+        .storage()
+        .at_latest()
+        .await?
+        .fetch(&referendum_info_for)
+        .await?;
+    match info {
+        Some(ReferendumInfo::Ongoing(status)) => Ok(Some(Tally {
+            ayes: Balance::from_planck(status.tally.ayes),
+            nays: Balance::from_planck(status.tally.nays),
+            support: Balance::from_planck(status.tally.support),
+        })),
+        _ => Ok(None),
+    }
+}
+
+/// fetches the number of referenda created so far, referendum indexes range from 0 to count - 1.
+pub async fn fetch_referendum_count(
+    api: &OnlineClient<PolkadotConfig>,
+) -> Result<u32, subxt::Error> {
+    api.storage()
+        .at_latest()
+        .await?
+        .fetch_or_default(&node_runtime::storage().referenda().referendum_count())
+        .await
+}
+
+/// fetches the track of a referendum, `None` if the referendum is not ongoing.
+pub async fn fetch_referendum_track(
+    api: &OnlineClient<PolkadotConfig>,
+    referendum: u32,
+) -> Result<Option<u16>, subxt::Error> {
+    let referendum_info_for = node_runtime::storage()
+        .referenda()
+        .referendum_info_for(referendum);
+    let info = api
+        .storage()
+        .at_latest()
+        .await?
+        .fetch(&referendum_info_for)
+        .await?;
+    match info {
+        Some(ReferendumInfo::Ongoing(status)) => Ok(Some(status.track)),
+        _ => Ok(None),
+    }
+}
+
+/// fetches the delegation of `account_id` on the track of `referendum`, if any.
+///
+/// Direct votes are rejected with `ConvictionVoting.AlreadyDelegating` while delegating on the track.
+pub async fn fetch_delegation_for_referendum(
+    api: &OnlineClient<PolkadotConfig>,
+    account_id: &AccountId32,
+    referendum: u32,
+) -> Result<Option<Delegation>, subxt::Error> {
+    let Some(track) = fetch_referendum_track(api, referendum).await? else {
+        return Ok(None);
+    };
+    let voting_for = node_runtime::storage()
+        .conviction_voting()
+        .voting_for(account_id, track);
+    match api.storage().at_latest().await?.fetch(&voting_for).await? {
+        Some(Voting::Delegating(delegating)) => Ok(Some(Delegation {
+            track,
+            target: delegating.target,
+            balance: Balance::from_planck(delegating.balance),
+            conviction: format!("{:?}", delegating.conviction),
+        })),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use subxt::ext::codec::Decode;
+
+    /// `vote`'s SCALE encoding as `AccountVote`, checked against `expected`, decoded back
+    fn assert_round_trip(vote: CastVote, expected: &[u8]) {
+        let encoded = AccountVote::from(&vote).encode();
+        assert_eq!(encoded, expected);
+        let decoded = AccountVote::<u128>::decode(&mut &encoded[..]).unwrap();
+        assert_eq!(CastVote::from(decoded), vote);
+    }
+
+    fn le_u128(planck: u128) -> [u8; 16] {
+        planck.to_le_bytes()
+    }
+
+    #[test]
+    fn standard_vote_encoding() {
+        let balance = Balance::from_planck(1_000_000_000_000);
+        // variant 0, the aye flag and the conviction in one byte, the balance as a plain u128
+        let mut expected = vec![0, 0b1000_0011];
+        expected.extend(le_u128(1_000_000_000_000));
+        assert_round_trip(
+            CastVote::Standard {
+                aye: true,
+                conviction: 3,
+                balance,
+            },
+            &expected,
+        );
+
+        let mut expected = vec![0, 0b0000_0000];
+        expected.extend(le_u128(1_000_000_000_000));
+        assert_round_trip(
+            CastVote::Standard {
+                aye: false,
+                conviction: 0,
+                balance,
+            },
+            &expected,
+        );
+    }
+
+    #[test]
+    fn split_vote_encoding() {
+        let mut expected = vec![1];
+        expected.extend(le_u128(2));
+        expected.extend(le_u128(u128::MAX));
+        assert_round_trip(
+            CastVote::Split {
+                aye: Balance::from_planck(2),
+                nay: Balance::from_planck(u128::MAX),
+            },
+            &expected,
+        );
+    }
+
+    #[test]
+    fn split_abstain_vote_encoding() {
+        let mut expected = vec![2];
+        expected.extend(le_u128(1));
+        expected.extend(le_u128(0));
+        expected.extend(le_u128(3));
+        assert_round_trip(
+            CastVote::SplitAbstain {
+                aye: Balance::from_planck(1),
+                nay: Balance::from_planck(0),
+                abstain: Balance::from_planck(3),
+            },
+            &expected,
+        );
+    }
+
+    #[test]
+    fn formats_the_conviction_like_the_picker() {
+        let chain_info = ChainInfo::kusama();
+        let vote = |conviction| CastVote::Standard {
+            aye: true,
+            conviction,
+            balance: Balance::from_planck(1_000_000_000_000),
+        };
+        assert_eq!(vote(0).format(&chain_info), "AYE • 1 KSM • 0.1x");
+        assert_eq!(vote(1).format(&chain_info), "AYE • 1 KSM • 1x");
+        assert_eq!(vote(6).format(&chain_info), "AYE • 1 KSM • 6x");
+    }
+}
@@ -0,0 +1,152 @@
+use std::time::Duration;
+
+use wasm_bindgen_futures::JsFuture;
+use yew::platform::time::sleep;
+use yew::prelude::*;
+
+use crate::services::js_copy_to_clipboard;
+
+/// how long the copy button shows that the value was copied
+const COPIED_FEEDBACK: Duration = Duration::from_secs(2);
+
+/// characters kept at the start (including `0x`) and end of values shown truncated
+const TRUNCATED_HEAD: usize = 10;
+const TRUNCATED_TAIL: usize = 8;
+
+/// shortens `value` to its first `head` and last `tail` characters, e.g. "0x12345678…9abcdef0".
+/// Values that wouldn't get shorter are returned as they are.
+pub fn truncate_hex(value: &str, head: usize, tail: usize) -> String {
+    // hex is ascii, so characters and bytes line up
+    if !value.is_ascii() || value.len() <= head + tail + 1 {
+        return value.to_string();
+    }
+    format!("{}…{}", &value[..head], &value[value.len() - tail..])
+}
+
+/// `0x` prefixed hex representation of `bytes`, as expected by `HexDisplay`
+pub fn hex_value(bytes: impl AsRef<[u8]>) -> AttrValue {
+    format!("0x{}", hex::encode(bytes.as_ref())).into()
+}
+
+#[derive(Properties, PartialEq)]
+pub struct HexDisplayProps {
+    /// `0x` prefixed hex string, see `hex_value` to display bytes
+    pub value: AttrValue,
+    #[prop_or_default]
+    pub label: Option<AttrValue>,
+    /// short explanation of what the value is, shown under the label and as a tooltip
+    #[prop_or_default]
+    pub hint: Option<AttrValue>,
+    /// renders the value as a link, e.g. to decode it on polkadot.js apps
+    #[prop_or_default]
+    pub href: Option<AttrValue>,
+    #[prop_or(true)]
+    pub copyable: bool,
+    /// shows long values truncated, with a toggle to expand them. Copying always copies the
+    /// full value.
+    #[prop_or(true)]
+    pub truncate: bool,
+}
+
+pub enum HexDisplayMessage {
+    Copy,
+    Copied(bool),
+    ResetCopied,
+    ToggleExpanded,
+}
+
+/// Displays encoded data (calls, signatures, extrinsics) with consistent wrapping and a copy button.
+pub struct HexDisplay {
+    /// `Some(true)` once copied, `Some(false)` if copying failed
+    copied: Option<bool>,
+    /// the full value is shown, even if `truncate` is set
+    expanded: bool,
+}
+
+impl Component for HexDisplay {
+    type Message = HexDisplayMessage;
+
+    type Properties = HexDisplayProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        HexDisplay {
+            copied: None,
+            expanded: false,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            HexDisplayMessage::Copy => {
+                let value = ctx.props().value.to_string();
+                ctx.link().send_future(async move {
+                    let copied = JsFuture::from(js_copy_to_clipboard(value)).await.is_ok();
+                    HexDisplayMessage::Copied(copied)
+                });
+                false
+            }
+            HexDisplayMessage::Copied(copied) => {
+                self.copied = Some(copied);
+                ctx.link().send_future(async {
+                    sleep(COPIED_FEEDBACK).await;
+                    HexDisplayMessage::ResetCopied
+                });
+                true
+            }
+            HexDisplayMessage::ResetCopied => {
+                self.copied = None;
+                true
+            }
+            HexDisplayMessage::ToggleExpanded => {
+                self.expanded = !self.expanded;
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let copy_click = ctx.link().callback(|_| HexDisplayMessage::Copy);
+        let shown = match props.truncate && !self.expanded {
+            true => truncate_hex(&props.value, TRUNCATED_HEAD, TRUNCATED_TAIL),
+            false => props.value.to_string(),
+        };
+        let expandable = props.truncate
+            && truncate_hex(&props.value, TRUNCATED_HEAD, TRUNCATED_TAIL) != props.value.as_str();
+        html! {
+            <div class="hex-display mb">
+                if let Some(label) = &props.label {
+                    <b>{label}</b>
+                    if let Some(hint) = &props.hint {
+                        {" "}<span class="hint-icon" title={hint.clone()} aria-hidden="true">{"ⓘ"}</span>
+                    }
+                    <br/>
+                }
+                if let Some(hint) = &props.hint {
+                    <small class="hint">{hint}</small> <br/>
+                }
+                <span class="hex">
+                    if let Some(href) = &props.href {
+                        <a class="encoded" href={href} target="_blank" title={props.value.clone()}>{shown}</a>
+                    } else {
+                        <span title={props.value.clone()}>{shown}</span>
+                    }
+                </span>
+                if expandable {
+                    <button class="copy" onclick={ctx.link().callback(|_| HexDisplayMessage::ToggleExpanded)}>
+                        {if self.expanded { "Collapse" } else { "Expand" }}
+                    </button>
+                }
+                if props.copyable {
+                    <button class="copy" onclick={copy_click}>
+                        {match self.copied {
+                            None => "Copy",
+                            Some(true) => "Copied ✓",
+                            Some(false) => "Copy failed",
+                        }}
+                    </button>
+                }
+            </div>
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct LoadingButtonProps {
+    pub label: AttrValue,
+    pub onclick: Callback<MouseEvent>,
+    /// shown instead of `label` while loading, e.g. "Refreshing accounts..."
+    #[prop_or_default]
+    pub loading_label: Option<AttrValue>,
+    #[prop_or_default]
+    pub disabled: bool,
+    /// shows a spinner and ignores clicks
+    #[prop_or_default]
+    pub loading: bool,
+    #[prop_or_default]
+    pub class: Classes,
+}
+
+/// A button with consistent disabled and loading states, so actions can't be triggered twice.
+pub struct LoadingButton;
+
+impl Component for LoadingButton {
+    type Message = ();
+
+    type Properties = LoadingButtonProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        LoadingButton
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let blocked = props.disabled || props.loading;
+        let onclick = props.onclick.clone();
+        let click = Callback::from(move |event: MouseEvent| {
+            if !blocked {
+                onclick.emit(event);
+            }
+        });
+        let label = match (&props.loading_label, props.loading) {
+            (Some(loading_label), true) => loading_label.clone(),
+            _ => props.label.clone(),
+        };
+        html! {
+            <button
+                class={classes!(props.class.clone(), props.loading.then_some("is-loading"))}
+                disabled={blocked}
+                aria-busy={props.loading.to_string()}
+                onclick={click}
+            >
+                if props.loading {
+                    <span class="spinner" aria-hidden="true"></span>
+                }
+                {label}
+            </button>
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+use yew::prelude::*;
+
+use crate::balance::Balance;
+use crate::chain_info::ChainInfo;
+use crate::governance::{lock_steps, LockEntry, LockSchedule};
+
+const GRAPH_WIDTH: u32 = 480;
+const GRAPH_HEIGHT: u32 = 200;
+const GRAPH_PADDING: f64 = 32.0;
+const AXIS_COLOR: &str = "#9E9E9E";
+const CURRENT_COLOR: &str = "#757575";
+const AFTER_COLOR: &str = "#E6007A";
+const FONT: &str = "12px 'Helvetica Neue', 'Arial', sans-serif";
+
+#[derive(Properties, PartialEq)]
+pub struct LockGraphProps {
+    pub schedule: LockSchedule,
+    /// lock of the vote being cast, `None` to only draw the current locks
+    pub pending: Option<LockEntry>,
+    /// milliseconds per block, to label the time axis in days
+    pub expected_block_time: u64,
+    pub chain_info: ChainInfo,
+}
+
+/// Draws the conviction voting locks of the account over time, before and after the vote.
+///
+/// The locked balance is the largest lock not unlockable yet, so it steps down as locks expire.
+pub struct LockGraph {
+    canvas: NodeRef,
+    /// the graph was drawn for the current props
+    drawn: bool,
+}
+
+impl Component for LockGraph {
+    type Message = ();
+
+    type Properties = LockGraphProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        LockGraph {
+            canvas: NodeRef::default(),
+            drawn: false,
+        }
+    }
+
+    fn changed(&mut self, _ctx: &Context<Self>, _old_props: &Self::Properties) -> bool {
+        self.drawn = false;
+        true
+    }
+
+    fn rendered(&mut self, ctx: &Context<Self>, _first_render: bool) {
+        if self.drawn {
+            return;
+        }
+        if let Some(canvas) = self.canvas.cast::<HtmlCanvasElement>() {
+            self.drawn = true;
+            draw_graph(&canvas, ctx.props());
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        html! {
+            <div class="lock-graph">
+                <canvas ref={self.canvas.clone()}></canvas>
+                <small>
+                    <span style={format!("color: {CURRENT_COLOR}")}>{"━ current locks"}</span>
+                    if props.pending.is_some() {
+                        {" "}
+                        <span style={format!("color: {AFTER_COLOR}")}>{"━ after this vote"}</span>
+                    }
+                    {" — ongoing votes and delegations are drawn at their earliest unlock."}
+                </small>
+            </div>
+        }
+    }
+}
+
+/// draws the current and the after vote locks on `canvas`, `None` if it has no 2d context.
+fn draw_graph(canvas: &HtmlCanvasElement, props: &LockGraphProps) -> Option<()> {
+    canvas.set_width(GRAPH_WIDTH);
+    canvas.set_height(GRAPH_HEIGHT);
+    let context = canvas
+        .get_context("2d")
+        .ok()??
+        .dyn_into::<CanvasRenderingContext2d>()
+        .ok()?;
+    let (width, height) = (GRAPH_WIDTH as f64, GRAPH_HEIGHT as f64);
+    context.clear_rect(0.0, 0.0, width, height);
+
+    let from_block = props.schedule.current_block;
+    let mut entries_after = props.schedule.entries.clone();
+    entries_after.extend(props.pending);
+    let current = lock_steps(&props.schedule.entries, from_block);
+    let after = lock_steps(&entries_after, from_block);
+
+    let last_block = after.last().map_or(from_block, |(block, _)| *block);
+    let max_locked = after.iter().map(|(_, locked)| *locked).max()?;
+    if last_block == from_block || max_locked.as_planck() == 0 {
+        context.set_font(FONT);
+        context.set_fill_style_str(AXIS_COLOR);
+        context
+            .fill_text("Nothing locked", GRAPH_PADDING, height / 2.0)
+            .ok()?;
+        return Some(());
+    }
+    // leave room after the last unlock so the final drop to 0 is visible
+    let span = (last_block - from_block) as f64 * 1.1;
+    let x = |block: u32| {
+        GRAPH_PADDING + (block - from_block) as f64 / span * (width - 2.0 * GRAPH_PADDING)
+    };
+    let y = |planck: u128| {
+        height
+            - GRAPH_PADDING
+            - planck as f64 / max_locked.as_planck() as f64 * (height - 2.0 * GRAPH_PADDING)
+    };
+
+    context.set_stroke_style_str(AXIS_COLOR);
+    context.set_line_width(1.0);
+    context.begin_path();
+    context.move_to(GRAPH_PADDING, GRAPH_PADDING);
+    context.line_to(GRAPH_PADDING, height - GRAPH_PADDING);
+    context.line_to(width - GRAPH_PADDING, height - GRAPH_PADDING);
+    context.stroke();
+
+    context.set_font(FONT);
+    context.set_fill_style_str(AXIS_COLOR);
+    context
+        .fill_text(
+            &props.chain_info.format_balance(max_locked),
+            GRAPH_PADDING + 4.0,
+            GRAPH_PADDING - 8.0,
+        )
+        .ok()?;
+    context
+        .fill_text("now", GRAPH_PADDING, height - 12.0)
+        .ok()?;
+    let days = (last_block - from_block) as u64 * props.expected_block_time / 86_400_000;
+    context
+        .fill_text(&format!("{days} days"), x(last_block) - 20.0, height - 12.0)
+        .ok()?;
+
+    // the after line first, so the current one stays visible where they overlap
+    let end_x = width - GRAPH_PADDING;
+    if props.pending.is_some() {
+        draw_steps(&context, &after, end_x, AFTER_COLOR, 3.0, &x, &y);
+    }
+    draw_steps(&context, &current, end_x, CURRENT_COLOR, 2.0, &x, &y);
+    Some(())
+}
+
+/// strokes `steps` as a step line up to `end_x`
+fn draw_steps(
+    context: &CanvasRenderingContext2d,
+    steps: &[(u32, Balance)],
+    end_x: f64,
+    color: &str,
+    line_width: f64,
+    x: &dyn Fn(u32) -> f64,
+    y: &dyn Fn(u128) -> f64,
+) {
+    context.set_stroke_style_str(color);
+    context.set_line_width(line_width);
+    context.begin_path();
+    let mut locked = 0;
+    for (i, (block, step_locked)) in steps.iter().enumerate() {
+        if i == 0 {
+            context.move_to(x(*block), y(step_locked.as_planck()));
+        } else {
+            context.line_to(x(*block), y(locked));
+            context.line_to(x(*block), y(step_locked.as_planck()));
+        }
+        locked = step_locked.as_planck();
+    }
+    context.line_to(end_x, y(locked));
+    context.stroke();
+}
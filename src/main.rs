@@ -1,15 +1,47 @@
+// `subxt::Error` is large, and it is returned all over the chain helpers
+#![allow(clippy::result_large_err)]
+
 use yew::prelude::*;
 use yew_router::prelude::*;
 
+use crate::not_found::NotFound;
 use crate::vote::VoteComponent;
+use crate::vote_lookup::VoteLookupComponent;
+mod accounts;
+mod balance;
+mod call_check;
+mod chain_info;
+#[cfg(feature = "debug")]
+mod debug_console;
+mod explorer;
+mod feed;
+mod governance;
+mod hex_display;
+mod loading_button;
+mod lock_graph;
+mod multisig;
+mod not_found;
+mod offline;
+mod outcome;
+mod relay;
 mod services;
+mod share_card;
+mod signer;
+mod signing_flow;
 mod vote;
+mod vote_lookup;
 
 #[derive(Routable, PartialEq, Eq, Clone, Debug)]
 pub enum Route {
-    // #[not_found]
     #[at("/")]
     Home,
+    #[at("/ref/:id")]
+    Referendum { id: u32 },
+    #[at("/ref/:id/lookup")]
+    Lookup { id: u32 },
+    #[not_found]
+    #[at("/404")]
+    NotFound,
 }
 
 fn main() {
@@ -39,5 +71,8 @@ impl Component for App {
 fn switch(routes: Route) -> Html {
     match routes {
         Route::Home => html! { <VoteComponent/> },
+        Route::Referendum { id } => html! { <VoteComponent referendum_index={id}/> },
+        Route::Lookup { id } => html! { <VoteLookupComponent referendum_index={id}/> },
+        Route::NotFound => html! { <NotFound/> },
     }
 }
@@ -0,0 +1,184 @@
+use anyhow::anyhow;
+use subxt::config::substrate::BlakeTwo256;
+use subxt::config::Hasher;
+use subxt::ext::codec::Encode;
+use subxt::utils::AccountId32;
+use subxt::{OnlineClient, PolkadotConfig};
+
+use crate::services::{
+    node_runtime, node_runtime::runtime_types::pallet_multisig::Timepoint,
+    node_runtime::runtime_types::sp_weights::weight_v2::Weight,
+};
+
+/// Upper bound of the weight of the wrapped `conviction_voting.vote` call, required by the
+/// final `as_multi`. The unused part is refunded, but a value below the real weight fails
+/// with `Multisig.MaxWeightTooLow`, so it's intentionally generous.
+pub const VOTE_MAX_WEIGHT: (u64, u64) = (5_000_000_000, 500_000);
+
+/// A multisig account the vote is cast through, from the point of view of the signer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultisigParams {
+    /// all signatories except the signer, sorted as required by the multisig pallet
+    pub other_signatories: Vec<AccountId32>,
+    pub threshold: u16,
+    /// the multisig account derived from all signatories and the threshold
+    pub account_id: AccountId32,
+}
+
+impl MultisigParams {
+    /// validates the user input and derives the multisig account.
+    ///
+    /// `other_signatories` are ss58 addresses separated by whitespace or commas.
+    pub fn new(
+        signer: &AccountId32,
+        other_signatories: &str,
+        threshold: &str,
+    ) -> Result<Self, anyhow::Error> {
+        let mut others = other_signatories
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|address| !address.is_empty())
+            .map(|address| {
+                address
+                    .parse::<AccountId32>()
+                    .map_err(|_| anyhow!("\"{address}\" is not a valid address"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        others.retain(|account_id| account_id != signer);
+        others.sort_by_key(|a| a.0);
+        others.dedup();
+        if others.is_empty() {
+            return Err(anyhow!("Enter the addresses of the other signatories"));
+        }
+
+        let threshold = threshold
+            .trim()
+            .parse::<u16>()
+            .map_err(|_| anyhow!("The multisig threshold must be a number"))?;
+        if threshold < 2 || threshold as usize > others.len() + 1 {
+            return Err(anyhow!(
+                "The multisig threshold must be between 2 and {}",
+                others.len() + 1
+            ));
+        }
+
+        let mut signatories = others.clone();
+        signatories.push(signer.clone());
+        Ok(MultisigParams {
+            account_id: multisig_account_id(signatories, threshold),
+            other_signatories: others,
+            threshold,
+        })
+    }
+}
+
+/// derives the multisig account id the same way `pallet_multisig::Pallet::multi_account_id` does
+pub fn multisig_account_id(mut signatories: Vec<AccountId32>, threshold: u16) -> AccountId32 {
+    signatories.sort_by_key(|a| a.0);
+    let entropy = (b"modlpy/utilisuba", signatories, threshold).encode();
+    AccountId32(BlakeTwo256::hash(&entropy).0)
+}
+
+/// blake2-256 hash of the encoded call, identifying the multisig operation
+pub fn call_hash(call_data: &[u8]) -> [u8; 32] {
+    BlakeTwo256::hash(call_data).0
+}
+
+/// Which multisig call the signer has to submit, depending on the approvals so far.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MultisigStep {
+    /// no pending operation yet, the signer opens it with the first approval
+    Initiate,
+    /// the operation opened at the `(block height, extrinsic index)` timepoint needs more approvals
+    Approve {
+        timepoint: (u32, u32),
+        approvals: usize,
+    },
+    /// the signer's approval reaches the threshold and executes the vote
+    Execute {
+        timepoint: (u32, u32),
+        approvals: usize,
+    },
+}
+
+impl MultisigStep {
+    pub fn timepoint(&self) -> Option<Timepoint<u32>> {
+        match self {
+            Self::Initiate => None,
+            Self::Approve { timepoint, .. } | Self::Execute { timepoint, .. } => Some(Timepoint {
+                height: timepoint.0,
+                index: timepoint.1,
+            }),
+        }
+    }
+}
+
+impl std::fmt::Display for MultisigStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Initiate => write!(
+                f,
+                "first approval. The other signatories approve with the timepoint (block height and extrinsic index) it gets included at"
+            ),
+            Self::Approve {
+                timepoint,
+                approvals,
+            } => write!(
+                f,
+                "approval #{} of the operation opened at timepoint {}-{}",
+                approvals + 1,
+                timepoint.0,
+                timepoint.1
+            ),
+            Self::Execute {
+                timepoint,
+                approvals,
+            } => write!(
+                f,
+                "final approval #{} of the operation opened at timepoint {}-{}, executing the vote",
+                approvals + 1,
+                timepoint.0,
+                timepoint.1
+            ),
+        }
+    }
+}
+
+/// looks up the pending multisig operation for `call_hash` and returns the step `signer` is at
+pub async fn fetch_multisig_step(
+    api: &OnlineClient<PolkadotConfig>,
+    params: &MultisigParams,
+    call_hash: [u8; 32],
+    signer: &AccountId32,
+) -> Result<MultisigStep, anyhow::Error> {
+    let multisigs = node_runtime::storage()
+        .multisig()
+        .multisigs(&params.account_id, call_hash);
+    let Some(multisig) = api.storage().at_latest().await?.fetch(&multisigs).await? else {
+        return Ok(MultisigStep::Initiate);
+    };
+    let approvals = multisig.approvals.0;
+    if approvals.contains(signer) {
+        return Err(anyhow!(
+            "This account already approved the pending multisig vote"
+        ));
+    }
+    let timepoint = (multisig.when.height, multisig.when.index);
+    if approvals.len() + 1 >= params.threshold as usize {
+        Ok(MultisigStep::Execute {
+            timepoint,
+            approvals: approvals.len(),
+        })
+    } else {
+        Ok(MultisigStep::Approve {
+            timepoint,
+            approvals: approvals.len(),
+        })
+    }
+}
+
+pub fn vote_max_weight() -> Weight {
+    Weight {
+        ref_time: VOTE_MAX_WEIGHT.0,
+        proof_size: VOTE_MAX_WEIGHT.1,
+    }
+}
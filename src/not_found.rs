@@ -0,0 +1,34 @@
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+use crate::Route;
+
+/// Page for unknown urls, including `/ref/:id` paths whose id is not a number.
+pub struct NotFound;
+
+impl Component for NotFound {
+    type Message = ();
+
+    type Properties = ();
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        NotFound
+    }
+
+    fn view(&self, _ctx: &Context<Self>) -> Html {
+        html! {
+            <div class="container">
+                <div class="top">
+                    <div class="header">
+                        <span class="kusama-logo">
+                            <img src="https://raw.githubusercontent.com/turboflakes/ref-275/main/assets/kusama_icon_shadow.svg" alt="kusama logo" />
+                        </span>
+                        <h1>{"404"}</h1>
+                    </div>
+                    <div class="mb">{"There is nothing to vote on at this address."}</div>
+                    <Link<Route> to={Route::Home}>{"=> Go to the voting page"}</Link<Route>>
+                </div>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use anyhow::anyhow;
+use subxt::config::substrate::Era;
+use subxt::ext::codec::{Compact, Decode, Encode};
+use subxt::utils::{AccountId32, MultiAddress, MultiSignature, H256};
+use subxt::Metadata;
+
+use crate::services::{check_kusama_genesis, ChainParams, EXTRINSIC_VERSION, KUSAMA_GENESIS_HASH};
+
+/// the metadata the runtime types are generated from, used when there's no live connection
+const BUNDLED_METADATA: &[u8] = include_bytes!("../artifacts/kusama_metadata.scale");
+
+/// Fields of `OfflineInput`, to update them one at a time from the form.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OfflineField {
+    GenesisHash,
+    SpecVersion,
+    TransactionVersion,
+    Nonce,
+}
+
+/// Raw input of the chain values that are otherwise read from the online client.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OfflineInput {
+    pub genesis_hash: String,
+    pub spec_version: String,
+    pub transaction_version: String,
+    pub nonce: String,
+}
+
+impl Default for OfflineInput {
+    fn default() -> Self {
+        OfflineInput {
+            genesis_hash: KUSAMA_GENESIS_HASH.to_string(),
+            spec_version: "".to_string(),
+            transaction_version: "".to_string(),
+            nonce: "".to_string(),
+        }
+    }
+}
+
+impl OfflineInput {
+    pub fn set(&mut self, field: OfflineField, value: String) {
+        match field {
+            OfflineField::GenesisHash => self.genesis_hash = value,
+            OfflineField::SpecVersion => self.spec_version = value,
+            OfflineField::TransactionVersion => self.transaction_version = value,
+            OfflineField::Nonce => self.nonce = value,
+        }
+    }
+
+    pub fn get(&self, field: OfflineField) -> &str {
+        match field {
+            OfflineField::GenesisHash => &self.genesis_hash,
+            OfflineField::SpecVersion => &self.spec_version,
+            OfflineField::TransactionVersion => &self.transaction_version,
+            OfflineField::Nonce => &self.nonce,
+        }
+    }
+
+    /// parses the input into the chain params and the account nonce to sign with.
+    ///
+    /// Signed extensions and constants come from the bundled metadata, so the spec version
+    /// entered should match the runtime it was generated from.
+    pub fn parse(&self) -> Result<(ChainParams, u64), anyhow::Error> {
+        let genesis_hash =
+            self.genesis_hash.trim().parse::<H256>().map_err(|_| {
+                anyhow!("The genesis hash must be a 0x prefixed 32 byte hex string")
+            })?;
+        check_kusama_genesis(genesis_hash)?;
+        let spec_version = parse_number(&self.spec_version, "spec version")?;
+        let transaction_version = parse_number(&self.transaction_version, "transaction version")?;
+        let nonce = parse_number(&self.nonce, "nonce")?;
+
+        let metadata = bundled_metadata()?;
+        let signed_extensions = metadata
+            .extrinsic()
+            .signed_extensions()
+            .iter()
+            .map(|e| e.identifier().to_string())
+            .collect();
+        let chain_params = ChainParams {
+            genesis_hash,
+            spec_version,
+            transaction_version,
+            signed_extensions,
+            vote_locking_period: constant(&metadata, "ConvictionVoting", "VoteLockingPeriod")?,
+            expected_block_time: constant(&metadata, "Babe", "ExpectedBlockTime")?,
+            existential_deposit: constant(&metadata, "Balances", "ExistentialDeposit")?,
+        };
+        Ok((chain_params, nonce))
+    }
+}
+
+/// decodes the metadata bundled with the app.
+pub fn bundled_metadata() -> Result<Metadata, anyhow::Error> {
+    Ok(Metadata::decode(&mut &BUNDLED_METADATA[..])?)
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str, name: &str) -> Result<T, anyhow::Error> {
+    value
+        .trim()
+        .parse::<T>()
+        .map_err(|_| anyhow!("The {name} must be a number"))
+}
+
+fn constant<T: Decode>(metadata: &Metadata, pallet: &str, name: &str) -> Result<T, anyhow::Error> {
+    let value = metadata
+        .pallet_by_name(pallet)
+        .and_then(|pallet| pallet.constant_by_name(name))
+        .ok_or(anyhow!("{pallet}.{name} not found in the bundled metadata"))?
+        .value();
+    Ok(T::decode(&mut &value[..])?)
+}
+
+/// encodes a signed (`EXTRINSIC_VERSION`) extrinsic from its parts, the same way the online client does for
+/// an immortal extrinsic without tip, so it can be submitted later by any node.
+pub fn signed_extrinsic_bytes(
+    call_data: &[u8],
+    account_id: AccountId32,
+    signature: &MultiSignature,
+    nonce: u64,
+) -> Vec<u8> {
+    let mut extrinsic = vec![0b1000_0000 | EXTRINSIC_VERSION];
+    MultiAddress::<AccountId32, ()>::Id(account_id).encode_to(&mut extrinsic);
+    signature.encode_to(&mut extrinsic);
+    Era::Immortal.encode_to(&mut extrinsic);
+    Compact(nonce).encode_to(&mut extrinsic);
+    // tip
+    Compact(0u128).encode_to(&mut extrinsic);
+    extrinsic.extend_from_slice(call_data);
+
+    let mut encoded = Compact(extrinsic.len() as u32).encode();
+    encoded.extend(extrinsic);
+    encoded
+}
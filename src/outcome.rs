@@ -0,0 +1,490 @@
+use jsonrpsee::types::error::CallError;
+use subxt::blocks::ExtrinsicEvents;
+use subxt::error::RpcError;
+use subxt::events::EventDetails;
+use subxt::ext::codec::Decode;
+use subxt::utils::H256;
+use subxt::{Metadata, PolkadotConfig};
+
+use crate::balance::Balance;
+use crate::services::node_runtime::runtime_types::sp_runtime::{DispatchError, ModuleError};
+use crate::services::node_runtime::{multisig, system, transaction_payment};
+
+/// An event emitted by an extrinsic, with its names resolved from the metadata.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EventSummary {
+    /// e.g. "ConvictionVoting"
+    pub pallet: String,
+    /// e.g. "Voted"
+    pub variant: String,
+    /// decoded fields, empty if they could not be decoded
+    pub fields: String,
+}
+
+impl EventSummary {
+    fn from_details(event: &EventDetails<PolkadotConfig>) -> Self {
+        EventSummary {
+            pallet: event.pallet_name().to_string(),
+            variant: event.variant_name().to_string(),
+            fields: event
+                .field_values()
+                .map(|fields| fields.to_string())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// What happened to a finalized extrinsic, read from its events.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubmissionOutcome {
+    pub block_hash: H256,
+    pub extrinsic_hash: H256,
+    /// index of the extrinsic in the block
+    pub extrinsic_index: u32,
+    /// actual fee charged, `None` if no `TransactionFeePaid` event was found
+    pub fee_paid: Option<Balance>,
+    /// the vote itself was dispatched: directly, or by the final multisig approval.
+    ///
+    /// `ConvictionVoting` emits no event for a vote, so a successful direct vote is only
+    /// observable through `System.ExtrinsicSuccess`.
+    pub vote_executed: bool,
+    /// why the extrinsic (or the call executed by the multisig) failed, if it did
+    pub dispatch_error: Option<String>,
+    /// all events emitted by the extrinsic, in order
+    pub events: Vec<EventSummary>,
+    /// the block is finalized. `false` if the outcome was read as soon as the extrinsic was
+    /// included in a block, which may still be retracted.
+    pub finalized: bool,
+}
+
+impl SubmissionOutcome {
+    /// reads the outcome from the extrinsic's events, resolving dispatch errors with `metadata`.
+    /// The events are assumed to be from a finalized block, see `finalized`.
+    pub fn from_events(
+        events: &ExtrinsicEvents<PolkadotConfig>,
+        metadata: &Metadata,
+    ) -> Result<Self, subxt::Error> {
+        let fee_paid = events
+            .find_first::<transaction_payment::events::TransactionFeePaid>()?
+            .map(|fee| Balance::from_planck(fee.actual_fee));
+        let mut dispatch_error = events
+            .find_first::<system::events::ExtrinsicFailed>()?
+            .map(|failed| describe_dispatch_error(&failed.dispatch_error, metadata));
+
+        let vote_executed = match events.find_first::<multisig::events::MultisigExecuted>()? {
+            Some(executed) => match executed.result {
+                Ok(()) => dispatch_error.is_none(),
+                Err(err) => {
+                    dispatch_error = Some(describe_dispatch_error(&err, metadata));
+                    false
+                }
+            },
+            None => {
+                // an approval that didn't reach the threshold yet
+                let pending_multisig = events.has::<multisig::events::NewMultisig>()?
+                    || events.has::<multisig::events::MultisigApproval>()?;
+                dispatch_error.is_none() && !pending_multisig
+            }
+        };
+
+        let events_emitted = events
+            .iter()
+            .map(|event| event.map(|event| EventSummary::from_details(&event)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(SubmissionOutcome {
+            block_hash: events.block_hash(),
+            extrinsic_hash: events.extrinsic_hash(),
+            extrinsic_index: events.extrinsic_index(),
+            fee_paid,
+            vote_executed,
+            dispatch_error,
+            events: events_emitted,
+            finalized: true,
+        })
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.dispatch_error.is_none()
+    }
+}
+
+/// human-readable reason of a dispatch error. Module errors are resolved to their pallet and
+/// name with `metadata`, e.g. "ConvictionVoting.InsufficientFunds: The account's ...".
+pub fn describe_dispatch_error(error: &DispatchError, metadata: &Metadata) -> String {
+    let DispatchError::Module(ModuleError { index, error }) = error else {
+        return format!("{error:?}");
+    };
+    let Some(pallet) = metadata.pallet_by_index(*index) else {
+        return format!("Unknown error {} of pallet #{index}", error[0]);
+    };
+    match pallet.error_variant_by_index(error[0]) {
+        Some(variant) if variant.docs.is_empty() => format!("{}.{}", pallet.name(), variant.name),
+        Some(variant) => format!(
+            "{}.{}: {}",
+            pallet.name(),
+            variant.name,
+            variant.docs.join(" ").trim()
+        ),
+        None => format!("Unknown error {} of {}", error[0], pallet.name()),
+    }
+}
+
+/// Transaction pool rejections caused by the state of the pool rather than by the extrinsic
+/// itself.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PoolRejection {
+    /// the pool is full of transactions with a higher priority (RPC error 1014)
+    PriorityTooLow,
+    /// the pool is at its limit and dropped the transaction right away (RPC error 1016)
+    PoolFull,
+    /// the pool recently rejected the same transaction and ignores it for a while (RPC error 1012)
+    TemporarilyBanned,
+}
+
+impl PoolRejection {
+    /// recognizes a pool rejection from the JSON-RPC error code the node answered a submission
+    /// with
+    pub fn from_error(err: &subxt::Error) -> Option<Self> {
+        let subxt::Error::Rpc(RpcError::ClientError(err)) = err else {
+            return None;
+        };
+        match err.downcast_ref::<jsonrpsee::core::Error>()? {
+            jsonrpsee::core::Error::Call(CallError::Custom(error)) => Self::from_code(error.code()),
+            _ => None,
+        }
+    }
+
+    /// the rejection of an `author_submitExtrinsic` error `code`, see the transaction pool
+    /// errors of `sc_rpc_api::author::error`
+    pub fn from_code(code: i32) -> Option<Self> {
+        match code {
+            1012 => Some(PoolRejection::TemporarilyBanned),
+            1014 => Some(PoolRejection::PriorityTooLow),
+            1016 => Some(PoolRejection::PoolFull),
+            _ => None,
+        }
+    }
+
+    /// whether the pool is congested, so submitting again once it drains can succeed. A
+    /// temporarily banned extrinsic was already submitted instead.
+    pub fn is_congestion(&self) -> bool {
+        !matches!(self, PoolRejection::TemporarilyBanned)
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            PoolRejection::PriorityTooLow => {
+                "the transaction pool is full of transactions with a higher priority"
+            }
+            PoolRejection::PoolFull => "the transaction pool is full",
+            PoolRejection::TemporarilyBanned => {
+                "the node recently rejected this extrinsic and ignores it for a while"
+            }
+        }
+    }
+}
+
+/// Why the node didn't accept an extrinsic for submission.
+#[derive(Debug)]
+pub enum SubmissionError {
+    /// rejected by the transaction pool because of its state rather than the extrinsic
+    Rejected(PoolRejection, subxt::Error),
+    Other(subxt::Error),
+}
+
+impl SubmissionError {
+    pub fn pool_rejection(&self) -> Option<PoolRejection> {
+        match self {
+            SubmissionError::Rejected(rejection, _) => Some(*rejection),
+            SubmissionError::Other(_) => None,
+        }
+    }
+
+    /// the pool rejection `err` carries, if it's a `SubmissionError`
+    pub fn pool_rejection_of(err: &anyhow::Error) -> Option<PoolRejection> {
+        err.downcast_ref::<SubmissionError>()
+            .and_then(SubmissionError::pool_rejection)
+    }
+}
+
+impl From<subxt::Error> for SubmissionError {
+    fn from(err: subxt::Error) -> Self {
+        match PoolRejection::from_error(&err) {
+            Some(rejection) => SubmissionError::Rejected(rejection, err),
+            None => SubmissionError::Other(err),
+        }
+    }
+}
+
+impl std::fmt::Display for SubmissionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubmissionError::Rejected(_, err) | SubmissionError::Other(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for SubmissionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SubmissionError::Rejected(_, err) | SubmissionError::Other(err) => Some(err),
+        }
+    }
+}
+
+/// The runtime's verdict on a signed extrinsic, decoded from
+/// `TaggedTransactionQueue_validate_transaction`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationResult {
+    Valid(TransactionValid),
+    Invalid(TransactionInvalid),
+    Unknown(TransactionUnknown),
+}
+
+impl ValidationResult {
+    /// decodes the runtime API's `Result<ValidTransaction, TransactionValidityError>`
+    pub fn decode_from(bytes: &[u8]) -> Result<Self, subxt::ext::codec::Error> {
+        let result = <Result<TransactionValid, TransactionValidityError>>::decode(&mut &*bytes)?;
+        Ok(match result {
+            Ok(valid) => ValidationResult::Valid(valid),
+            Err(TransactionValidityError::Invalid(invalid)) => ValidationResult::Invalid(invalid),
+            Err(TransactionValidityError::Unknown(unknown)) => ValidationResult::Unknown(unknown),
+        })
+    }
+}
+
+/// `sp_runtime::transaction_validity::ValidTransaction`
+#[derive(Clone, Debug, PartialEq, Decode)]
+#[codec(crate = subxt::ext::codec)]
+pub struct TransactionValid {
+    pub priority: u64,
+    pub requires: Vec<Vec<u8>>,
+    pub provides: Vec<Vec<u8>>,
+    pub longevity: u64,
+    pub propagate: bool,
+}
+
+/// `sp_runtime::transaction_validity::InvalidTransaction`
+#[derive(Clone, Debug, PartialEq, Decode)]
+#[codec(crate = subxt::ext::codec)]
+pub enum TransactionInvalid {
+    Call,
+    Payment,
+    Future,
+    Stale,
+    BadProof,
+    AncientBirthBlock,
+    ExhaustsResources,
+    Custom(u8),
+    BadMandatory,
+    MandatoryValidation,
+    BadSigner,
+}
+
+/// `sp_runtime::transaction_validity::UnknownTransaction`
+#[derive(Clone, Debug, PartialEq, Decode)]
+#[codec(crate = subxt::ext::codec)]
+pub enum TransactionUnknown {
+    CannotLookup,
+    NoUnsignedValidator,
+    Custom(u8),
+}
+
+#[derive(Decode)]
+#[codec(crate = subxt::ext::codec)]
+enum TransactionValidityError {
+    Invalid(TransactionInvalid),
+    Unknown(TransactionUnknown),
+}
+
+/// human-readable result of validating a signed extrinsic against the transaction pool rules,
+/// without submitting it
+pub fn describe_validation(result: &ValidationResult) -> Result<String, String> {
+    match result {
+        ValidationResult::Valid(valid) => Ok(format!(
+            "priority {}, valid for {} blocks, {}",
+            valid.priority,
+            valid.longevity,
+            if valid.propagate {
+                "propagated to other nodes"
+            } else {
+                "not propagated to other nodes"
+            }
+        )),
+        ValidationResult::Invalid(invalid) => Err(match invalid {
+            TransactionInvalid::Call => "the call is not allowed".to_string(),
+            TransactionInvalid::Payment => "the account can't pay the fee".to_string(),
+            TransactionInvalid::Future => {
+                "the nonce is ahead of the account's nonce, an earlier extrinsic is missing"
+                    .to_string()
+            }
+            TransactionInvalid::Stale => {
+                "the nonce was already used, sign again with the current nonce".to_string()
+            }
+            TransactionInvalid::BadProof => "the signature is invalid".to_string(),
+            TransactionInvalid::AncientBirthBlock => {
+                "the extrinsic's era started too long ago".to_string()
+            }
+            TransactionInvalid::ExhaustsResources => {
+                "the extrinsic would exhaust the block's resources".to_string()
+            }
+            TransactionInvalid::Custom(code) => format!("rejected by the runtime (code {code})"),
+            TransactionInvalid::BadMandatory | TransactionInvalid::MandatoryValidation => {
+                "a mandatory extrinsic can't be submitted".to_string()
+            }
+            TransactionInvalid::BadSigner => "the signer is not allowed".to_string(),
+        }),
+        ValidationResult::Unknown(unknown) => Err(match unknown {
+            TransactionUnknown::CannotLookup => {
+                "the validity could not be determined, an account lookup failed".to_string()
+            }
+            TransactionUnknown::NoUnsignedValidator => {
+                "no validator for unsigned extrinsics".to_string()
+            }
+            TransactionUnknown::Custom(code) => {
+                format!("the validity could not be determined (code {code})")
+            }
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::offline::bundled_metadata;
+
+    /// the module error `name` of `pallet`, with the indexes of the bundled metadata
+    fn module_error(metadata: &Metadata, pallet: &str, name: &str) -> DispatchError {
+        let pallet = metadata.pallet_by_name(pallet).unwrap();
+        let variant = pallet
+            .error_variants()
+            .unwrap()
+            .iter()
+            .find(|variant| variant.name == name)
+            .unwrap();
+        DispatchError::Module(ModuleError {
+            index: pallet.index(),
+            error: [variant.index, 0, 0, 0],
+        })
+    }
+
+    #[test]
+    fn describes_module_errors_by_name() {
+        let metadata = bundled_metadata().unwrap();
+        let error = module_error(&metadata, "ConvictionVoting", "InsufficientFunds");
+        let description = describe_dispatch_error(&error, &metadata);
+        assert!(
+            description.starts_with("ConvictionVoting.InsufficientFunds: "),
+            "{description}"
+        );
+
+        let DispatchError::Module(ModuleError { index, .. }) = error else {
+            unreachable!()
+        };
+        let unknown_variant = DispatchError::Module(ModuleError {
+            index,
+            error: [250, 0, 0, 0],
+        });
+        assert_eq!(
+            describe_dispatch_error(&unknown_variant, &metadata),
+            "Unknown error 250 of ConvictionVoting"
+        );
+        let unknown_pallet = DispatchError::Module(ModuleError {
+            index: 250,
+            error: [1, 0, 0, 0],
+        });
+        assert_eq!(
+            describe_dispatch_error(&unknown_pallet, &metadata),
+            "Unknown error 1 of pallet #250"
+        );
+        assert_eq!(
+            describe_dispatch_error(&DispatchError::BadOrigin, &metadata),
+            "BadOrigin"
+        );
+    }
+
+    /// the error subxt returns when the node answers a submission with a JSON-RPC error
+    fn rpc_error(code: i32, message: &str) -> subxt::Error {
+        let error = jsonrpsee::core::Error::Call(CallError::Custom(
+            jsonrpsee::types::ErrorObject::owned(code, message, None::<()>),
+        ));
+        subxt::Error::Rpc(RpcError::ClientError(Box::new(error)))
+    }
+
+    #[test]
+    fn recognizes_pool_rejections_by_code() {
+        let rejection = |code, message| PoolRejection::from_error(&rpc_error(code, message));
+        assert_eq!(
+            rejection(1014, "Priority is too low: (1 vs 2)"),
+            Some(PoolRejection::PriorityTooLow)
+        );
+        assert_eq!(
+            rejection(1016, "Immediately Dropped"),
+            Some(PoolRejection::PoolFull)
+        );
+        assert_eq!(
+            rejection(1012, "Transaction is temporarily banned"),
+            Some(PoolRejection::TemporarilyBanned)
+        );
+        assert_eq!(rejection(1010, "Invalid Transaction"), None);
+        // the message alone doesn't make a rejection
+        assert_eq!(
+            PoolRejection::from_error(&subxt::Error::Rpc(RpcError::ClientError(
+                "Immediately Dropped".to_string().into(),
+            ))),
+            None
+        );
+
+        assert!(PoolRejection::PriorityTooLow.is_congestion());
+        assert!(PoolRejection::PoolFull.is_congestion());
+        assert!(!PoolRejection::TemporarilyBanned.is_congestion());
+
+        // carried through an `anyhow::Error`, as submissions are reported
+        let err = anyhow::Error::from(SubmissionError::from(rpc_error(
+            1016,
+            "Immediately Dropped",
+        )));
+        assert_eq!(
+            SubmissionError::pool_rejection_of(&err),
+            Some(PoolRejection::PoolFull)
+        );
+        assert_eq!(
+            SubmissionError::pool_rejection_of(&anyhow::anyhow!("Immediately Dropped")),
+            None
+        );
+    }
+
+    #[test]
+    fn decodes_validation_results() {
+        // Ok(ValidTransaction { priority: 7, requires: [], provides: [], longevity: 64, propagate })
+        let mut valid = vec![0x00];
+        valid.extend(7u64.to_le_bytes());
+        valid.extend([0x00, 0x00]);
+        valid.extend(64u64.to_le_bytes());
+        valid.push(0x01);
+        let ValidationResult::Valid(valid) = ValidationResult::decode_from(&valid).unwrap() else {
+            panic!("expected a valid transaction");
+        };
+        assert_eq!(
+            (valid.priority, valid.longevity, valid.propagate),
+            (7, 64, true)
+        );
+
+        // Err(Invalid(Stale)) and Err(Unknown(Custom(9)))
+        assert_eq!(
+            ValidationResult::decode_from(&[0x01, 0x00, 0x03]).unwrap(),
+            ValidationResult::Invalid(TransactionInvalid::Stale)
+        );
+        assert_eq!(
+            ValidationResult::decode_from(&[0x01, 0x01, 0x02, 0x09]).unwrap(),
+            ValidationResult::Unknown(TransactionUnknown::Custom(9))
+        );
+        assert!(
+            describe_validation(&ValidationResult::Invalid(TransactionInvalid::Stale))
+                .unwrap_err()
+                .contains("nonce was already used")
+        );
+        assert!(ValidationResult::decode_from(&[0x01]).is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use anyhow::anyhow;
+use serde::Deserialize;
+use subxt::utils::H256;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Headers, Request, RequestInit, Response};
+
+/// Where signed extrinsics are submitted.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum SubmissionBackend {
+    /// `submit_and_watch` through the connected node
+    #[default]
+    Direct,
+    /// POSTed to a sponsor relay at this URL, which pays for and submits the extrinsic, set
+    /// with e.g. `?relay=https://sponsor.example/submit`
+    Relay(String),
+}
+
+impl SubmissionBackend {
+    /// the backend for the `?relay=` query param, `Direct` without one
+    pub fn from_param(relay: Option<String>) -> Result<Self, anyhow::Error> {
+        match relay {
+            None => Ok(Self::Direct),
+            Some(url) => Ok(Self::Relay(parse_relay_url(&url)?)),
+        }
+    }
+}
+
+/// validates a relay URL: https, or http for a relay running locally
+pub fn parse_relay_url(input: &str) -> Result<String, anyhow::Error> {
+    let url = input.trim();
+    let host = match url.strip_prefix("https://") {
+        Some(host) => host,
+        None => url
+            .strip_prefix("http://")
+            .filter(|host| host.starts_with("localhost") || host.starts_with("127.0.0.1"))
+            .ok_or(anyhow!("The relay URL must start with https://"))?,
+    };
+    if host.is_empty() || host.starts_with('/') || url.chars().any(char::is_whitespace) {
+        return Err(anyhow!("\"{url}\" is not a valid relay URL"));
+    }
+    Ok(url.to_string())
+}
+
+/// Body of the relay's response: `{"block_hash": "0x…", "finalized": true}` once the
+/// extrinsic is in a block, or `{"error": "…"}` if it could not be submitted.
+#[derive(Debug, Deserialize)]
+struct RelayResponse {
+    block_hash: Option<String>,
+    #[serde(default)]
+    finalized: bool,
+    error: Option<String>,
+}
+
+/// Where the relay reports the extrinsic was included. Not trusted as such: the extrinsic is
+/// looked up in that block through the connected node.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RelayReceipt {
+    pub block_hash: H256,
+    pub finalized: bool,
+}
+
+/// POSTs `{"extrinsic": "0x…"}` to the relay at `url` and waits for it to report the block
+/// the extrinsic was included in.
+pub async fn relay_extrinsic(url: &str, extrinsic: &[u8]) -> Result<RelayReceipt, anyhow::Error> {
+    let window = web_sys::window().ok_or(anyhow!("No window to send the request from"))?;
+    let body = serde_json::json!({ "extrinsic": format!("0x{}", hex::encode(extrinsic)) });
+    let headers = Headers::new().map_err(|js_err| anyhow!("{js_err:?}"))?;
+    headers
+        .set("Content-Type", "application/json")
+        .map_err(|js_err| anyhow!("{js_err:?}"))?;
+    let init = RequestInit::new();
+    init.set_method("POST");
+    init.set_headers(&headers);
+    init.set_body(&JsValue::from_str(&body.to_string()));
+    let request =
+        Request::new_with_str_and_init(url, &init).map_err(|js_err| anyhow!("{js_err:?}"))?;
+
+    let response: Response = JsFuture::from(window.fetch_with_request(&request))
+        .await
+        .map_err(|js_err| anyhow!("The relay could not be reached: {js_err:?}"))?
+        .dyn_into()
+        .map_err(|_| anyhow!("The relay returned no response"))?;
+    let text = JsFuture::from(response.text().map_err(|js_err| anyhow!("{js_err:?}"))?)
+        .await
+        .map_err(|js_err| anyhow!("{js_err:?}"))?
+        .as_string()
+        .ok_or(anyhow!("Error converting JsValue into String"))?;
+    let parsed: RelayResponse = serde_json::from_str(&text).map_err(|err| {
+        anyhow!(
+            "The relay returned an invalid response (HTTP {}): {err}",
+            response.status()
+        )
+    })?;
+
+    match (parsed.error, parsed.block_hash) {
+        (Some(error), _) => Err(anyhow!("The relay rejected the extrinsic: {error}")),
+        (None, Some(block_hash)) if response.ok() => Ok(RelayReceipt {
+            block_hash: block_hash
+                .parse()
+                .map_err(|_| anyhow!("The relay returned an invalid block hash {block_hash}"))?,
+            finalized: parsed.finalized,
+        }),
+        _ => Err(anyhow!(
+            "The relay didn't report a block (HTTP {})",
+            response.status()
+        )),
+    }
+}
@@ -3,45 +3,301 @@ use futures::StreamExt;
 use js_sys::Promise;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::fmt::Write;
-use subxt::config::substrate::Era;
-use subxt::ext::codec::{Compact, Encode};
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+use subxt::blocks::{Block, ExtrinsicEvents};
+use subxt::config::substrate::{BlakeTwo256, DigestItem, Era};
+use subxt::config::Hasher;
+use subxt::ext::codec::{Compact, Decode, Encode};
+use subxt::tx::SubmittableExtrinsic;
+use subxt::utils::{AccountId32, MultiSignature, H256};
 use subxt::{self, OnlineClient, PolkadotConfig};
 use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::JsFuture;
-use yew::{AttrValue, Callback};
+use yew::platform::time::sleep;
+use yew::Callback;
+
+use crate::accounts::parse_accounts;
+use crate::chain_info::ChainInfo;
+use crate::offline::signed_extrinsic_bytes;
+use crate::outcome::ValidationResult;
 
 #[subxt::subxt(runtime_metadata_path = "artifacts/kusama_metadata.scale")]
 pub mod node_runtime {}
 
-/// subscribes to finalized blocks. When a block is received, it is formatted as a string and sent via the callback.
+pub const KUSAMA_RPC_ENDPOINT: &str = "wss://rpc.ibp.network/kusama";
+/// endpoints tried in turn when connecting, the preferred `KUSAMA_RPC_ENDPOINT` first
+pub const KUSAMA_RPC_ENDPOINTS: &[&str] = &[
+    KUSAMA_RPC_ENDPOINT,
+    "wss://rpc.dotters.network/kusama",
+    "wss://kusama-rpc.dwellir.com",
+];
+pub const KUSAMA_GENESIS_HASH: &str =
+    "0xb0a8d493285c2df73290dfb7e61f870f17b41801197a149ca93654499ea3dafe";
+
+/// connection attempts per endpoint before giving up
+pub const CONNECT_ATTEMPTS: u32 = 4;
+/// delay before the second round of connection attempts, doubled for every following round
+const CONNECT_BACKOFF: Duration = Duration::from_millis(500);
+
+/// connects to the first endpoint that responds, trying them in turn for up to `max_attempts`
+/// rounds with a jittered exponential backoff between rounds. Returns the client and the
+/// endpoint it's connected to, or an error listing every failure.
+pub async fn connect_with_retry(
+    endpoints: &[&str],
+    max_attempts: u32,
+) -> Result<(OnlineClient<PolkadotConfig>, String), anyhow::Error> {
+    let (api, endpoint) = retry_connect(
+        endpoints,
+        max_attempts,
+        |endpoint| OnlineClient::<PolkadotConfig>::from_url(endpoint.to_string()),
+        |backoff| {
+            // 50% to 150% of the backoff, so clients dropped together don't retry together
+            let jitter = 0.5 + js_sys::Math::random();
+            sleep(backoff.mul_f64(jitter))
+        },
+    )
+    .await?;
+    Ok((api, endpoint.to_string()))
+}
+
+/// the rounds of `connect_with_retry`, with `connect` opening a connection to an endpoint and
+/// `wait` sleeping for the backoff between rounds
+async fn retry_connect<'a, T, E: Display, C, W>(
+    endpoints: &[&'a str],
+    max_attempts: u32,
+    connect: impl Fn(&str) -> C,
+    wait: impl Fn(Duration) -> W,
+) -> Result<(T, &'a str), anyhow::Error>
+where
+    C: Future<Output = Result<T, E>>,
+    W: Future<Output = ()>,
+{
+    let mut failures = vec![];
+    for attempt in 0..max_attempts {
+        if attempt > 0 {
+            wait(CONNECT_BACKOFF * 2u32.pow(attempt - 1)).await;
+        }
+        for endpoint in endpoints {
+            match connect(endpoint).await {
+                Ok(api) => return Ok((api, endpoint)),
+                Err(err) => failures.push(format!("{endpoint} (attempt {}): {err}", attempt + 1)),
+            }
+        }
+    }
+    Err(anyhow!(
+        "Could not connect to any endpoint:\n{}",
+        failures.join("\n")
+    ))
+}
+
+/// checks that `genesis_hash` is Kusama's. Accounts, metadata and the referendum are all
+/// Kusama specific, so a vote crafted for another chain would be invalid or a different call.
+pub fn check_kusama_genesis(genesis_hash: H256) -> Result<(), anyhow::Error> {
+    let expected: H256 = KUSAMA_GENESIS_HASH.parse()?;
+    if genesis_hash != expected {
+        return Err(anyhow!(
+            "The endpoint is not connected to Kusama (genesis hash {genesis_hash:?}), voting is disabled"
+        ));
+    }
+    Ok(())
+}
+
+/// A finalized block received from the subscription, formatted by the component.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FinalizedBlock {
+    pub number: u32,
+    pub hash: H256,
+    /// validator that authored the block, `None` if it could not be resolved
+    pub author: Option<AccountId32>,
+    /// `None` if the block's extrinsics could not be read
+    pub extrinsics_count: Option<usize>,
+}
+
+/// subscribes to finalized blocks of `api`. When a block is received, its details are sent via the callback.
 pub(crate) async fn subscribe_to_finalized_blocks(
-    cb: Callback<AttrValue>,
+    api: OnlineClient<PolkadotConfig>,
+    cb: Callback<FinalizedBlock>,
 ) -> Result<(), subxt::Error> {
-    let api = OnlineClient::<PolkadotConfig>::from_url("wss://rpc.ibp.network/kusama").await?;
-
     // Subscribe to all finalized blocks:
     let mut blocks_sub = api.blocks().subscribe_finalized().await?;
     while let Some(block) = blocks_sub.next().await {
         let block = block?;
-        let mut output = String::new();
-        writeln!(output, "Block #{}:", block.header().number).ok();
-        writeln!(output, "  Hash: {}", block.hash()).ok();
-        cb.emit(output.into())
+        // an enrichment only, not worth stopping the feed for
+        let extrinsics_count = block.body().await.ok().map(|body| body.extrinsics().len());
+        cb.emit(FinalizedBlock {
+            number: block.header().number,
+            hash: block.hash(),
+            author: block_author(&api, &block).await,
+            extrinsics_count,
+        })
     }
     Ok(())
 }
 
+const BABE_ENGINE_ID: [u8; 4] = *b"BABE";
+
+/// resolves the author of `block` from the authority index of its BABE pre-runtime digest
+/// and the session validators at that block.
+async fn block_author(
+    api: &OnlineClient<PolkadotConfig>,
+    block: &Block<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+) -> Option<AccountId32> {
+    let authority_index = block
+        .header()
+        .digest
+        .logs
+        .iter()
+        .find_map(|log| match log {
+            // every BABE pre-digest variant starts with the u32 authority index
+            DigestItem::PreRuntime(BABE_ENGINE_ID, data) => u32::decode(&mut data.get(1..)?).ok(),
+            _ => None,
+        })?;
+    let validators = api
+        .storage()
+        .at(block.hash())
+        .fetch(&node_runtime::storage().session().validators())
+        .await
+        .ok()??;
+    validators.get(authority_index as usize).cloned()
+}
+
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_name = getAccounts)]
     pub fn js_get_accounts() -> Promise;
+    #[wasm_bindgen(js_name = getExtensionStatus)]
+    pub fn js_get_extension_status() -> Promise;
+    #[wasm_bindgen(js_name = getExtensionMetadata)]
+    pub fn js_get_extension_metadata(source: String, genesis_hash: String) -> Promise;
+    #[wasm_bindgen(js_name = provideMetadata)]
+    pub fn js_provide_metadata(source: String, definition: String) -> Promise;
     #[wasm_bindgen(js_name = signPayload)]
     pub fn js_sign_payload(payload: String, source: String, address: String) -> Promise;
+    #[wasm_bindgen(js_name = postStatus)]
+    pub fn js_post_status(status: String);
+    #[wasm_bindgen(js_name = copyToClipboard)]
+    pub fn js_copy_to_clipboard(text: String) -> Promise;
+}
+
+/// calls `cb` with the page visibility whenever it changes, e.g. when the tab is backgrounded.
+///
+/// The returned closure must be kept alive for as long as the listener should fire
+/// and passed to `remove_visibility_listener` when done.
+pub fn add_visibility_listener(cb: Callback<bool>) -> Option<Closure<dyn Fn()>> {
+    let document = web_sys::window()?.document()?;
+    let listener = Closure::<dyn Fn()>::new(move || cb.emit(is_page_visible()));
+    document
+        .add_event_listener_with_callback("visibilitychange", listener.as_ref().unchecked_ref())
+        .ok()?;
+    Some(listener)
+}
+
+pub fn remove_visibility_listener(listener: &Closure<dyn Fn()>) {
+    if let Some(document) = web_sys::window().and_then(|window| window.document()) {
+        document
+            .remove_event_listener_with_callback(
+                "visibilitychange",
+                listener.as_ref().unchecked_ref(),
+            )
+            .ok();
+    }
+}
+
+pub fn is_page_visible() -> bool {
+    web_sys::window()
+        .and_then(|window| window.document())
+        .map(|document| document.visibility_state() == web_sys::VisibilityState::Visible)
+        .unwrap_or(true)
+}
+
+/// reads a query parameter from the page url, e.g. `postmessage` in `?postmessage=1`
+pub fn query_param(name: &str) -> Option<String> {
+    let search = web_sys::window()?.location().search().ok()?;
+    web_sys::UrlSearchParams::new_with_str(&search)
+        .ok()?
+        .get(name)
+}
+
+/// reads a value persisted in the browser's `localStorage`
+pub fn local_storage_get(key: &str) -> Option<String> {
+    web_sys::window()?
+        .local_storage()
+        .ok()??
+        .get_item(key)
+        .ok()?
+}
+
+/// persists a value in the browser's `localStorage`, ignoring failures (e.g. private mode)
+pub fn local_storage_set(key: &str, value: &str) {
+    if let Some(storage) = web_sys::window().and_then(|window| window.local_storage().ok()?) {
+        storage.set_item(key, value).ok();
+    }
+}
+
+/// Browser features the app relies on, detected once at startup.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BrowserSupport {
+    /// `localStorage` can be written, it throws in some private modes
+    pub local_storage: bool,
+    /// `navigator.clipboard` exists, it requires a secure context
+    pub clipboard: bool,
+}
+
+impl BrowserSupport {
+    pub fn detect() -> Self {
+        const PROBE_KEY: &str = "ref275.probe";
+        let local_storage = web_sys::window()
+            .and_then(|window| window.local_storage().ok()?)
+            .is_some_and(|storage| {
+                let writable = storage.set_item(PROBE_KEY, "1").is_ok();
+                storage.remove_item(PROBE_KEY).ok();
+                writable
+            });
+        let clipboard = js_sys::Reflect::get(&js_sys::global(), &"navigator".into())
+            .ok()
+            .filter(|navigator| navigator.is_object())
+            .and_then(|navigator| js_sys::Reflect::get(&navigator, &"clipboard".into()).ok())
+            .is_some_and(|clipboard| !clipboard.is_undefined());
+        Self {
+            local_storage,
+            clipboard,
+        }
+    }
+}
+
+/// Status posted to the embedding page on key transitions. Serialized as
+/// `{ "source": "ref-275", "type": "<variant>", ...fields }`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum StatusEvent {
+    AccountsLoaded {
+        count: usize,
+    },
+    Signed {
+        address: String,
+    },
+    Submitted,
+    Finalized {
+        #[serde(rename = "blockHash")]
+        block_hash: String,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+/// posts `event` to the parent window via `postMessage`
+pub fn post_status(event: &StatusEvent) {
+    let mut status = serde_json::to_value(event).expect("StatusEvent is serializable");
+    status["source"] = json!("ref-275");
+    js_post_status(status.to_string());
 }
 
 /// DTO to communicate with JavaScript
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Account {
     /// account name
     pub name: String,
@@ -51,6 +307,10 @@ pub struct Account {
     pub ty: String,
     /// ss58 formatted address as string. Can be converted into AccountId32 via it's FromStr implementation.
     pub address: String,
+    /// the extension marks this account as its default or currently selected one.
+    /// Missing in older shims, hence the default.
+    #[serde(default, rename = "isDefault")]
+    pub is_default: bool,
 }
 
 pub async fn get_accounts() -> Result<Vec<Account>, anyhow::Error> {
@@ -60,10 +320,256 @@ pub async fn get_accounts() -> Result<Vec<Account>, anyhow::Error> {
     let accounts_str = result
         .as_string()
         .ok_or(anyhow!("Error converting JsValue into String"))?;
-    let accounts: Vec<Account> = serde_json::from_str(&accounts_str)?;
+    let (accounts, skipped) = parse_accounts(&accounts_str)?;
+    if skipped > 0 {
+        web_sys::console::warn_1(
+            &format!("Skipped {skipped} malformed or non-ss58 accounts").into(),
+        );
+    }
     Ok(accounts)
 }
 
+/// Browser extensions seen by the JavaScript shim, to tell why no accounts were returned.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct ExtensionStatus {
+    /// extensions injected in the page, e.g. "talisman"
+    pub installed: Vec<String>,
+    /// extensions that authorized this site
+    pub authorized: Vec<String>,
+}
+
+impl ExtensionStatus {
+    /// what to do when no accounts were returned
+    pub fn empty_accounts_hint(&self) -> &'static str {
+        if self.installed.is_empty() {
+            "No Web3 extension found. Install Talisman or the Polkadot.js extension and add an account."
+        } else if self.authorized.is_empty() {
+            "Your wallet extension did not authorize this site. Allow it in the extension and click Refresh."
+        } else {
+            "Unlock your wallet extension and click Refresh. If it's unlocked, add an account to it."
+        }
+    }
+}
+
+pub async fn get_extension_status() -> Result<ExtensionStatus, anyhow::Error> {
+    let result = JsFuture::from(js_get_extension_status())
+        .await
+        .map_err(|js_err| anyhow!("{js_err:?}"))?;
+    let status_str = result
+        .as_string()
+        .ok_or(anyhow!("Error converting JsValue into String"))?;
+    Ok(serde_json::from_str(&status_str)?)
+}
+
+/// Whether an extension can be given chain metadata, so its popup decodes the signed call.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtensionMetadata {
+    pub supported: bool,
+    /// spec version of the metadata the extension has for Kusama, `None` if it has none
+    pub spec_version: Option<u32>,
+}
+
+impl ExtensionMetadata {
+    /// the extension can be given metadata it doesn't have yet
+    pub fn needs_update(&self, chain_params: &ChainParams) -> bool {
+        self.supported && self.spec_version != Some(chain_params.spec_version)
+    }
+}
+
+pub async fn get_extension_metadata(
+    source: String,
+    genesis_hash: H256,
+) -> Result<ExtensionMetadata, anyhow::Error> {
+    let result = JsFuture::from(js_get_extension_metadata(
+        source,
+        format!("{genesis_hash:?}"),
+    ))
+    .await
+    .map_err(|js_err| anyhow!("{js_err:?}"))?;
+    let metadata_str = result
+        .as_string()
+        .ok_or(anyhow!("Error converting JsValue into String"))?;
+    Ok(serde_json::from_str(&metadata_str)?)
+}
+
+/// reads the chain metadata and asks the extension of `source` to store it. Returns whether
+/// the user accepted the update in the extension.
+pub async fn provide_metadata(
+    api: &OnlineClient<PolkadotConfig>,
+    chain_info: &ChainInfo,
+    chain_params: &ChainParams,
+    source: String,
+) -> Result<bool, anyhow::Error> {
+    // SCALE encoded `RuntimeMetadataPrefixed`, as the extensions expect it
+    let raw_metadata: String = api
+        .rpc()
+        .request("state_getMetadata", subxt::rpc_params![])
+        .await?;
+    // `MetadataDef` of @polkadot/extension-inject
+    let definition = json!({
+        "chain": chain_info.name,
+        "chainType": "substrate",
+        "genesisHash": format!("{:?}", chain_params.genesis_hash),
+        "icon": "polkadot",
+        "ss58Format": chain_info.ss58_prefix,
+        "specVersion": chain_params.spec_version,
+        "tokenDecimals": chain_info.decimals,
+        "tokenSymbol": chain_info.symbol,
+        "types": {},
+        "rawMetadata": raw_metadata,
+    });
+    let result = JsFuture::from(js_provide_metadata(source, definition.to_string()))
+        .await
+        .map_err(|js_err| anyhow!("{js_err:?}"))?;
+    Ok(result.as_bool().unwrap_or(false))
+}
+
+/// hash of an encoded extrinsic, as used by explorers and the transaction pool
+pub fn extrinsic_hash(encoded_extrinsic: &[u8]) -> H256 {
+    BlakeTwo256::hash(encoded_extrinsic)
+}
+
+/// looks for the extrinsic with `extrinsic_hash` in the latest `depth` finalized blocks
+/// and returns its events if found.
+pub async fn find_finalized_extrinsic(
+    api: &OnlineClient<PolkadotConfig>,
+    extrinsic_hash: H256,
+    depth: u32,
+) -> Result<Option<ExtrinsicEvents<PolkadotConfig>>, subxt::Error> {
+    let mut block = api.blocks().at_latest().await?;
+    for _ in 0..depth {
+        if let Some(events) = extrinsic_events_in(&block, extrinsic_hash).await? {
+            return Ok(Some(events));
+        }
+        let parent_hash = block.header().parent_hash;
+        block = api.blocks().at(parent_hash).await?;
+    }
+    Ok(None)
+}
+
+/// looks for the extrinsic with `extrinsic_hash` in the block with `block_hash` and returns
+/// its events if found.
+pub async fn find_extrinsic_in_block(
+    api: &OnlineClient<PolkadotConfig>,
+    block_hash: H256,
+    extrinsic_hash: H256,
+) -> Result<Option<ExtrinsicEvents<PolkadotConfig>>, subxt::Error> {
+    let block = api.blocks().at(block_hash).await?;
+    extrinsic_events_in(&block, extrinsic_hash).await
+}
+
+async fn extrinsic_events_in(
+    block: &Block<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+    extrinsic_hash: H256,
+) -> Result<Option<ExtrinsicEvents<PolkadotConfig>>, subxt::Error> {
+    for extrinsic in block.body().await?.extrinsics().iter() {
+        let extrinsic = extrinsic?;
+        if self::extrinsic_hash(extrinsic.bytes()) == extrinsic_hash {
+            return Ok(Some(extrinsic.events().await?));
+        }
+    }
+    Ok(None)
+}
+
+/// validates a signed extrinsic against the transaction pool rules at the best block with
+/// `TaggedTransactionQueue_validate_transaction`, without submitting it.
+pub async fn validate_extrinsic(
+    api: &OnlineClient<PolkadotConfig>,
+    signed_extrinsic: &[u8],
+) -> Result<ValidationResult, subxt::Error> {
+    let best = api
+        .rpc()
+        .block_hash(None)
+        .await?
+        .ok_or_else(|| subxt::Error::Other("no best block".to_string()))?;
+    // (TransactionSource::External, extrinsic, block hash)
+    let mut params = vec![2u8];
+    params.extend_from_slice(signed_extrinsic);
+    best.encode_to(&mut params);
+    let result = api
+        .rpc()
+        .state_call_raw(
+            "TaggedTransactionQueue_validate_transaction",
+            Some(&params),
+            Some(best),
+        )
+        .await?;
+    Ok(ValidationResult::decode_from(&result)?)
+}
+
+/// estimates the fee of `call_data` signed by `account_id`, before signing it.
+///
+/// The extrinsic is built with a placeholder signature: the fee only depends on the
+/// extrinsic's length and weight, not on the signature being valid.
+pub async fn estimate_fee(
+    api: &OnlineClient<PolkadotConfig>,
+    call_data: &[u8],
+    account_id: AccountId32,
+) -> Result<u128, subxt::Error> {
+    let placeholder = MultiSignature::Sr25519([0; 64]);
+    let extrinsic = signed_extrinsic_bytes(call_data, account_id, &placeholder, 0);
+    SubmittableExtrinsic::from_bytes(api.clone(), extrinsic)
+        .partial_fee_estimate()
+        .await
+}
+
+/// share of the max extrinsic weight from which a batch is flagged as too heavy, unless set
+/// with e.g. `?weightwarning=50`
+pub const DEFAULT_WEIGHT_WARNING_PERCENT: u8 = 75;
+
+/// Weight of an extrinsic compared to the most the runtime allows for a single extrinsic.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WeightEstimate {
+    pub ref_time: u64,
+    pub proof_size: u64,
+    pub max_ref_time: u64,
+    pub max_proof_size: u64,
+}
+
+impl WeightEstimate {
+    /// share of the limit used, in percent, by the more constrained of the two dimensions
+    pub fn percent_of_limit(&self) -> u64 {
+        let percent = |used: u64, max: u64| (used as u128 * 100 / max.max(1) as u128) as u64;
+        percent(self.ref_time, self.max_ref_time).max(percent(self.proof_size, self.max_proof_size))
+    }
+}
+
+/// estimates the weight of `call_data` signed by `account_id` with
+/// `TransactionPaymentApi_query_info`, and reads the max weight of a normal extrinsic from
+/// `System.BlockWeights`.
+pub async fn estimate_weight(
+    api: &OnlineClient<PolkadotConfig>,
+    call_data: &[u8],
+    account_id: AccountId32,
+) -> Result<WeightEstimate, subxt::Error> {
+    let placeholder = MultiSignature::Sr25519([0; 64]);
+    let mut params = signed_extrinsic_bytes(call_data, account_id, &placeholder, 0);
+    (params.len() as u32).encode_to(&mut params);
+    let info = api
+        .rpc()
+        .state_call_raw("TransactionPaymentApi_query_info", Some(&params), None)
+        .await?;
+    // RuntimeDispatchInfo { weight: { ref_time, proof_size }, class, partial_fee }
+    let (ref_time, proof_size, _, _) =
+        <(Compact<u64>, Compact<u64>, u8, u128)>::decode(&mut &*info)?;
+
+    let block_weights = api
+        .constants()
+        .at(&node_runtime::constants().system().block_weights())?;
+    let max_extrinsic = block_weights
+        .per_class
+        .normal
+        .max_extrinsic
+        .unwrap_or(block_weights.max_block);
+    Ok(WeightEstimate {
+        ref_time: ref_time.0,
+        proof_size: proof_size.0,
+        max_ref_time: max_extrinsic.ref_time,
+        max_proof_size: max_extrinsic.proof_size,
+    })
+}
+
 fn to_hex(bytes: impl AsRef<[u8]>) -> String {
     format!("0x{}", hex::encode(bytes.as_ref()))
 }
@@ -72,36 +578,122 @@ fn encode_then_hex<E: Encode>(input: &E) -> String {
     format!("0x{}", hex::encode(input.encode()))
 }
 
-/// communicates with JavaScript to obtain a signature for the `partial_extrinsic` via a browser extension (e.g. polkadot-js or Talisman)
+/// Chain values that don't change during a session, read once when the online client is created.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChainParams {
+    pub genesis_hash: H256,
+    pub spec_version: u32,
+    pub transaction_version: u32,
+    /// identifiers of the signed extensions expected in the signing payload
+    pub signed_extensions: Vec<String>,
+    /// blocks a conviction of 1x locks the balance for after the referendum ends
+    pub vote_locking_period: u32,
+    /// expected block time in milliseconds
+    pub expected_block_time: u64,
+    /// minimum free balance (in Planck) an account needs to exist
+    pub existential_deposit: u128,
+}
+
+/// version of the extrinsics built here and of the `version` field of the signing payload.
 ///
-/// Some parameters are hard-coded here and not taken from the partial_extrinsic itself (mortality_checkpoint, era, tip).
-pub async fn extension_signature_for_extrinsic(
+/// Both the payload and the offline encoder follow the v4 format, so supporting a runtime on
+/// another version means updating them along with this constant.
+pub const EXTRINSIC_VERSION: u8 = 4;
+
+impl ChainParams {
+    pub fn from_client(api: &OnlineClient<PolkadotConfig>) -> Result<Self, subxt::Error> {
+        let extrinsic_version = api.metadata().extrinsic().version();
+        if extrinsic_version != EXTRINSIC_VERSION {
+            return Err(subxt::Error::Other(format!(
+                "The chain uses extrinsic version {extrinsic_version}, only version {EXTRINSIC_VERSION} is supported"
+            )));
+        }
+        let runtime_version = api.runtime_version();
+        let signed_extensions = api
+            .metadata()
+            .extrinsic()
+            .signed_extensions()
+            .iter()
+            .map(|e| e.identifier().to_string())
+            .collect();
+        let vote_locking_period = api.constants().at(&node_runtime::constants()
+            .conviction_voting()
+            .vote_locking_period())?;
+        let expected_block_time = api
+            .constants()
+            .at(&node_runtime::constants().babe().expected_block_time())?;
+        let existential_deposit = api
+            .constants()
+            .at(&node_runtime::constants().balances().existential_deposit())?;
+        Ok(ChainParams {
+            genesis_hash: api.genesis_hash(),
+            spec_version: runtime_version.spec_version,
+            transaction_version: runtime_version.transaction_version,
+            signed_extensions,
+            vote_locking_period,
+            expected_block_time,
+            existential_deposit,
+        })
+    }
+}
+
+/// Signed extensions the polkadot-js extension (and wallets built on `@polkadot/types`) know how to
+/// encode. If the chain requires any other, signing fails inside the extension without a clear reason.
+pub const SUPPORTED_SIGNED_EXTENSIONS: &[&str] = &[
+    "ChargeAssetTxPayment",
+    "ChargeTransactionPayment",
+    "CheckBlockGasLimit",
+    "CheckEra",
+    "CheckGenesis",
+    "CheckMortality",
+    "CheckNonZeroSender",
+    "CheckNonce",
+    "CheckSpecVersion",
+    "CheckTxVersion",
+    "CheckWeight",
+    "LimitParathreadCommits",
+    "LockStakingStatus",
+    "OnlyStakingAndClaims",
+    "PrevalidateAttests",
+    "RestrictFunctionality",
+    "TransactionCallFilter",
+    "ValidateDoubleVoteReports",
+    "ValidateEquivocationReport",
+];
+
+/// returns the signed extensions in `required` that are not in `SUPPORTED_SIGNED_EXTENSIONS`
+pub fn unsupported_signed_extensions(required: &[String]) -> Vec<String> {
+    required
+        .iter()
+        .filter(|e| !SUPPORTED_SIGNED_EXTENSIONS.contains(&e.as_str()))
+        .cloned()
+        .collect()
+}
+
+/// builds the JSON signing payload expected by the browser extensions' `signPayload`.
+///
+/// Only uses `chain_params`, so it can be built without a live connection (see the offline mode).
+/// Some parameters are hard-coded here (mortality_checkpoint, era, tip).
+pub fn signing_payload(
     call_data: &[u8],
-    api: &OnlineClient<PolkadotConfig>,
+    chain_params: &ChainParams,
     account_nonce: u64,
-    account_source: String,
-    account_address: String,
-) -> Result<Vec<u8>, anyhow::Error> {
-    let genesis_hash = encode_then_hex(&api.genesis_hash());
+    account_address: &str,
+) -> serde_json::Value {
+    let genesis_hash = encode_then_hex(&chain_params.genesis_hash);
     // These numbers aren't SCALE encoded; their bytes are just converted to hex:
-    let spec_version = to_hex(&api.runtime_version().spec_version.to_be_bytes());
-    let transaction_version = to_hex(&api.runtime_version().transaction_version.to_be_bytes());
-    let nonce = to_hex(&account_nonce.to_be_bytes());
+    let spec_version = to_hex(chain_params.spec_version.to_be_bytes());
+    let transaction_version = to_hex(chain_params.transaction_version.to_be_bytes());
+    let nonce = to_hex(account_nonce.to_be_bytes());
     // If you construct a mortal transaction, then this block hash needs to correspond
     // to the block number passed to `Era::mortal()`.
-    let mortality_checkpoint = encode_then_hex(&api.genesis_hash());
+    let mortality_checkpoint = encode_then_hex(&chain_params.genesis_hash);
     let era = encode_then_hex(&Era::Immortal);
     let method = to_hex(call_data);
-    let signed_extensions: Vec<String> = api
-        .metadata()
-        .extrinsic()
-        .signed_extensions()
-        .iter()
-        .map(|e| e.identifier().to_string())
-        .collect();
+    let signed_extensions = &chain_params.signed_extensions;
     let tip = encode_then_hex(&Compact(0u128));
 
-    let payload = json!({
+    json!({
         "specVersion": spec_version,
         "transactionVersion": transaction_version,
         "address": account_address,
@@ -113,8 +705,46 @@ pub async fn extension_signature_for_extrinsic(
         "nonce": nonce,
         "signedExtensions": signed_extensions,
         "tip": tip,
-        "version": 4,
-    });
+        "version": EXTRINSIC_VERSION,
+    })
+}
+
+/// checks the fields binding a signing payload to the chain: `genesisHash` must be the chain's
+/// `genesis_hash`, and so must the `blockHash` (mortality checkpoint) of an immortal payload.
+/// A mismatch would make the signature invalid on-chain, so it's caught before signing.
+pub fn check_signing_payload(
+    payload: &serde_json::Value,
+    genesis_hash: H256,
+) -> Result<(), anyhow::Error> {
+    let expected = encode_then_hex(&genesis_hash);
+    let field = |name: &str| payload.get(name).and_then(|value| value.as_str());
+    match field("genesisHash") {
+        Some(hash) if hash == expected => {}
+        hash => {
+            return Err(anyhow!(
+                "The signing payload is for genesis {}, but the chain's genesis is {expected}",
+                hash.unwrap_or("(missing)")
+            ))
+        }
+    }
+    let immortal = field("era") == Some(encode_then_hex(&Era::Immortal).as_str());
+    match field("blockHash") {
+        Some(hash) if immortal && hash != expected => Err(anyhow!(
+            "The signing payload is immortal, so its block hash must be the genesis hash {expected}, not {hash}"
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// communicates with JavaScript to obtain a signature for the `partial_extrinsic` via a browser extension (e.g. polkadot-js or Talisman)
+pub async fn extension_signature_for_extrinsic(
+    call_data: &[u8],
+    chain_params: &ChainParams,
+    account_nonce: u64,
+    account_source: String,
+    account_address: String,
+) -> Result<MultiSignature, anyhow::Error> {
+    let payload = signing_payload(call_data, chain_params, account_nonce, &account_address);
 
     let payload = payload.to_string();
     let result = JsFuture::from(js_sign_payload(payload, account_source, account_address))
@@ -123,6 +753,202 @@ pub async fn extension_signature_for_extrinsic(
     let signature = result
         .as_string()
         .ok_or(anyhow!("Error converting JsValue into String"))?;
-    let signature = hex::decode(&signature[2..])?;
-    Ok(signature)
+    decode_signature(&signature)
+}
+
+/// decodes the hex encoded `MultiSignature` returned by an extension, i.e. a variant tag
+/// (0 Ed25519, 1 Sr25519, 2 Ecdsa) followed by a 64 (or 65 for Ecdsa) bytes signature.
+pub fn decode_signature(signature: &str) -> Result<MultiSignature, anyhow::Error> {
+    let Some(signature) = signature.strip_prefix("0x") else {
+        return Err(anyhow!(
+            "The extension returned a signature without 0x prefix: \"{signature}\""
+        ));
+    };
+    let bytes = hex::decode(signature)
+        .map_err(|err| anyhow!("The extension returned a signature that is not hex: {err}"))?;
+    let expected_len = match bytes.first() {
+        Some(0) | Some(1) => 65,
+        Some(2) => 66,
+        Some(tag) => {
+            return Err(anyhow!(
+                "The extension returned a signature of unknown type {tag}"
+            ))
+        }
+        None => return Err(anyhow!("The extension returned an empty signature")),
+    };
+    if bytes.len() != expected_len {
+        return Err(anyhow!(
+            "The extension returned a {} bytes signature, expected {expected_len}",
+            bytes.len()
+        ));
+    }
+    Ok(MultiSignature::decode(&mut &bytes[..])?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[test]
+    fn retry_connect_rotates_endpoints_and_backs_off() {
+        let attempts = RefCell::new(vec![]);
+        let waits = RefCell::new(vec![]);
+        // only the second endpoint comes up, in the third round
+        let result = futures::executor::block_on(retry_connect(
+            &["wss://a", "wss://b"],
+            4,
+            |endpoint| {
+                attempts.borrow_mut().push(endpoint.to_string());
+                let up = endpoint == "wss://b" && attempts.borrow().len() == 6;
+                async move {
+                    if up {
+                        Ok(42)
+                    } else {
+                        Err("connection refused")
+                    }
+                }
+            },
+            |backoff| {
+                waits.borrow_mut().push(backoff);
+                async {}
+            },
+        ));
+        assert_eq!(result.unwrap(), (42, "wss://b"));
+        assert_eq!(
+            attempts.into_inner(),
+            ["wss://a", "wss://b", "wss://a", "wss://b", "wss://a", "wss://b"]
+        );
+        assert_eq!(
+            waits.into_inner(),
+            [Duration::from_millis(500), Duration::from_millis(1000)]
+        );
+    }
+
+    #[test]
+    fn retry_connect_lists_every_failure() {
+        let waits = RefCell::new(0);
+        let result: Result<((), &str), _> = futures::executor::block_on(retry_connect(
+            &["wss://a", "wss://b"],
+            2,
+            |_| async { Err("timeout") },
+            |_| {
+                *waits.borrow_mut() += 1;
+                async {}
+            },
+        ));
+        let err = result.unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "Could not connect to any endpoint:\n\
+             wss://a (attempt 1): timeout\n\
+             wss://b (attempt 1): timeout\n\
+             wss://a (attempt 2): timeout\n\
+             wss://b (attempt 2): timeout"
+        );
+        assert_eq!(waits.into_inner(), 1);
+    }
+
+    fn signature_hex(tag: u8, len: usize) -> String {
+        let mut bytes = vec![7; len + 1];
+        bytes[0] = tag;
+        format!("0x{}", hex::encode(bytes))
+    }
+
+    #[test]
+    fn decodes_signatures_of_every_type() {
+        assert_eq!(
+            decode_signature(&signature_hex(0, 64)).unwrap(),
+            MultiSignature::Ed25519([7; 64])
+        );
+        assert_eq!(
+            decode_signature(&signature_hex(1, 64)).unwrap(),
+            MultiSignature::Sr25519([7; 64])
+        );
+        assert_eq!(
+            decode_signature(&signature_hex(2, 65)).unwrap(),
+            MultiSignature::Ecdsa([7; 65])
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_signatures() {
+        let err = |signature: &str| decode_signature(signature).unwrap_err().to_string();
+        assert!(err(&signature_hex(1, 64)[2..]).contains("without 0x prefix"));
+        assert!(err("0xzz").contains("not hex"));
+        assert!(err("0x").contains("empty signature"));
+        assert!(err(&signature_hex(3, 64)).contains("unknown type 3"));
+        assert!(err(&signature_hex(1, 63)).contains("64 bytes signature, expected 65"));
+        // an Ecdsa tag with an Sr25519 length
+        assert!(err(&signature_hex(2, 64)).contains("65 bytes signature, expected 66"));
+    }
+
+    fn kusama_params() -> ChainParams {
+        ChainParams {
+            genesis_hash: KUSAMA_GENESIS_HASH.parse().unwrap(),
+            spec_version: 1_000_000,
+            transaction_version: 25,
+            signed_extensions: vec!["CheckNonce".to_string()],
+            vote_locking_period: 100_800,
+            expected_block_time: 6_000,
+            existential_deposit: 333_333_333,
+        }
+    }
+
+    #[test]
+    fn signing_payload_is_an_immortal_v4_payload() {
+        let payload = signing_payload(
+            &[0x00, 0x01],
+            &kusama_params(),
+            5,
+            "HNZata7iMYWmk5RvZRTiAsSDhV8366zq2YGb3tLH5Upf74F",
+        );
+        assert_eq!(payload["version"], json!(4));
+        assert_eq!(payload["specVersion"], json!("0x000f4240"));
+        assert_eq!(payload["transactionVersion"], json!("0x00000019"));
+        assert_eq!(payload["nonce"], json!("0x0000000000000005"));
+        assert_eq!(payload["method"], json!("0x0001"));
+        assert_eq!(payload["era"], json!("0x00"));
+        assert_eq!(payload["tip"], json!("0x00"));
+        assert_eq!(payload["genesisHash"], json!(KUSAMA_GENESIS_HASH));
+        assert_eq!(payload["blockHash"], json!(KUSAMA_GENESIS_HASH));
+        assert_eq!(payload["signedExtensions"], json!(["CheckNonce"]));
+    }
+
+    #[test]
+    fn signing_payload_is_bound_to_the_genesis() {
+        let genesis_hash: H256 = KUSAMA_GENESIS_HASH.parse().unwrap();
+        let payload = signing_payload(&[0x00], &kusama_params(), 0, "");
+        assert!(check_signing_payload(&payload, genesis_hash).is_ok());
+
+        let err = check_signing_payload(&payload, H256::repeat_byte(1)).unwrap_err();
+        assert!(err.to_string().contains("payload is for genesis"), "{err}");
+
+        let mut missing = payload.clone();
+        missing.as_object_mut().unwrap().remove("genesisHash");
+        let err = check_signing_payload(&missing, genesis_hash).unwrap_err();
+        assert!(err.to_string().contains("(missing)"), "{err}");
+
+        let other_block = format!("{:?}", H256::repeat_byte(2));
+        let mut immortal = payload.clone();
+        immortal["blockHash"] = json!(other_block);
+        let err = check_signing_payload(&immortal, genesis_hash).unwrap_err();
+        assert!(err.to_string().contains("is immortal"), "{err}");
+
+        // the checkpoint of a mortal payload is a recent block
+        let mut mortal = immortal;
+        mortal["era"] = json!("0xe500");
+        assert!(check_signing_payload(&mortal, genesis_hash).is_ok());
+    }
+
+    #[test]
+    fn only_kusama_is_accepted() {
+        assert!(check_kusama_genesis(KUSAMA_GENESIS_HASH.parse().unwrap()).is_ok());
+        // Polkadot
+        let polkadot: H256 = "0x91b171bb158e2d3848fa23a9f1c25182fb8e20313b2c1eb49219da7a70ce90c3"
+            .parse()
+            .unwrap();
+        let err = check_kusama_genesis(polkadot).unwrap_err();
+        assert!(err.to_string().contains("not connected to Kusama"), "{err}");
+    }
 }
@@ -0,0 +1,177 @@
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+use yew::prelude::*;
+
+use crate::services::js_copy_to_clipboard;
+
+const CARD_WIDTH: u32 = 600;
+const CARD_HEIGHT: u32 = 315;
+const CARD_PADDING: f64 = 40.0;
+const BACKGROUND_COLOR: &str = "#0B1317";
+const TEXT_COLOR: &str = "#FFFFFF";
+const FONT_FAMILY: &str = "'Gilroy', 'Helvetica Neue', 'Arial', sans-serif";
+
+#[derive(Properties, PartialEq)]
+pub struct ShareCardProps {
+    /// e.g. "I voted AYE with 10 KSM, 1x conviction on Kusama ref #275"
+    pub summary: AttrValue,
+    /// link printed at the bottom of the card and appended to the copied text
+    pub link: AttrValue,
+    /// name of the downloaded image, e.g. "ref-275-vote.png"
+    pub file_name: AttrValue,
+}
+
+pub enum ShareCardMessage {
+    /// the card was drawn, `None` if the canvas is not available
+    Drawn(Option<String>),
+    CopyText,
+    TextCopied(bool),
+}
+
+/// Draws a "share your vote" card on a canvas, to download as an image or copy as text.
+///
+/// Everything happens client-side, nothing is uploaded.
+pub struct ShareCard {
+    canvas: NodeRef,
+    /// the card was drawn for the current props
+    drawn: bool,
+    /// `data:` URL of the drawn card
+    image_url: Option<String>,
+    /// `Some(true)` once the text was copied, `Some(false)` if copying failed
+    text_copied: Option<bool>,
+}
+
+impl Component for ShareCard {
+    type Message = ShareCardMessage;
+
+    type Properties = ShareCardProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        ShareCard {
+            canvas: NodeRef::default(),
+            drawn: false,
+            image_url: None,
+            text_copied: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            ShareCardMessage::Drawn(image_url) => self.image_url = image_url,
+            ShareCardMessage::CopyText => {
+                let text = format!("{} {}", ctx.props().summary, ctx.props().link);
+                ctx.link().send_future(async move {
+                    let copied = JsFuture::from(js_copy_to_clipboard(text)).await.is_ok();
+                    ShareCardMessage::TextCopied(copied)
+                });
+                return false;
+            }
+            ShareCardMessage::TextCopied(copied) => self.text_copied = Some(copied),
+        }
+        true
+    }
+
+    fn changed(&mut self, _ctx: &Context<Self>, _old_props: &Self::Properties) -> bool {
+        self.drawn = false;
+        self.image_url = None;
+        self.text_copied = None;
+        true
+    }
+
+    fn rendered(&mut self, ctx: &Context<Self>, _first_render: bool) {
+        if self.drawn {
+            return;
+        }
+        if let Some(canvas) = self.canvas.cast::<HtmlCanvasElement>() {
+            self.drawn = true;
+            let props = ctx.props();
+            let image_url = draw_card(&canvas, &props.summary, &props.link);
+            ctx.link().send_message(ShareCardMessage::Drawn(image_url));
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let copy_click = ctx.link().callback(|_| ShareCardMessage::CopyText);
+        html! {
+            <div class="share-card mb">
+                <b>{"Share your vote"}</b> <br/>
+                <canvas ref={self.canvas.clone()}></canvas>
+                <div>
+                    if let Some(image_url) = &self.image_url {
+                        <a class="button" href={image_url.clone()} download={props.file_name.clone()}>{"Download image"}</a>
+                    }
+                    <button onclick={copy_click}>
+                        {match self.text_copied {
+                            None => "Copy text",
+                            Some(true) => "Copied ✓",
+                            Some(false) => "Copy failed",
+                        }}
+                    </button>
+                </div>
+            </div>
+        }
+    }
+}
+
+/// draws the card on `canvas` and returns it as a PNG `data:` URL.
+fn draw_card(canvas: &HtmlCanvasElement, summary: &str, link: &str) -> Option<String> {
+    canvas.set_width(CARD_WIDTH);
+    canvas.set_height(CARD_HEIGHT);
+    let context = canvas
+        .get_context("2d")
+        .ok()??
+        .dyn_into::<CanvasRenderingContext2d>()
+        .ok()?;
+    let (width, height) = (CARD_WIDTH as f64, CARD_HEIGHT as f64);
+
+    context.set_fill_style_str(BACKGROUND_COLOR);
+    context.fill_rect(0.0, 0.0, width, height);
+    context.set_fill_style_str(TEXT_COLOR);
+
+    context.set_font(&format!("bold 20px {FONT_FAMILY}"));
+    context.fill_text("KUSAMA", CARD_PADDING, 60.0).ok()?;
+
+    context.set_font(&format!("bold 32px {FONT_FAMILY}"));
+    let max_width = width - 2.0 * CARD_PADDING;
+    let mut y = 120.0;
+    for line in wrap_text(&context, summary, max_width) {
+        context.fill_text(&line, CARD_PADDING, y).ok()?;
+        y += 42.0;
+    }
+
+    context.set_font(&format!("16px {FONT_FAMILY}"));
+    context
+        .fill_text(link, CARD_PADDING, height - CARD_PADDING)
+        .ok()?;
+
+    canvas.to_data_url().ok()
+}
+
+/// splits `text` into lines fitting `max_width` with the current font of `context`
+fn wrap_text(context: &CanvasRenderingContext2d, text: &str, max_width: f64) -> Vec<String> {
+    let fits = |line: &str| {
+        context
+            .measure_text(line)
+            .map_or(true, |metrics| metrics.width() <= max_width)
+    };
+    let mut lines: Vec<String> = vec![];
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if line.is_empty() {
+            word.to_string()
+        } else {
+            format!("{line} {word}")
+        };
+        if fits(&candidate) || line.is_empty() {
+            line = candidate;
+        } else {
+            lines.push(std::mem::replace(&mut line, word.to_string()));
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
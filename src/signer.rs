@@ -0,0 +1,54 @@
+use futures::future::LocalBoxFuture;
+use futures::FutureExt;
+use subxt::utils::MultiSignature;
+
+use crate::services::{extension_signature_for_extrinsic, ChainParams};
+
+/// What a signer gets to sign an extrinsic: the call and the values of its signed extensions.
+#[derive(Clone, Debug)]
+pub struct SigningRequest {
+    pub call_data: Vec<u8>,
+    pub chain_params: ChainParams,
+    pub nonce: u64,
+    /// extension the account comes from, e.g. "talisman"
+    pub account_source: String,
+    pub account_address: String,
+}
+
+/// A way to sign extrinsics: a browser extension, or e.g. a hardware wallet or an offline
+/// device. The component signs everything through the signer it holds.
+pub trait Signer {
+    /// short description shown to the user, e.g. "browser extension"
+    fn name(&self) -> &'static str;
+
+    fn sign(
+        &self,
+        request: SigningRequest,
+    ) -> LocalBoxFuture<'static, Result<MultiSignature, anyhow::Error>>;
+}
+
+/// Signs with the browser extension the account comes from, the default signer.
+pub struct ExtensionSigner;
+
+impl Signer for ExtensionSigner {
+    fn name(&self) -> &'static str {
+        "browser extension"
+    }
+
+    fn sign(
+        &self,
+        request: SigningRequest,
+    ) -> LocalBoxFuture<'static, Result<MultiSignature, anyhow::Error>> {
+        async move {
+            extension_signature_for_extrinsic(
+                &request.call_data,
+                &request.chain_params,
+                request.nonce,
+                request.account_source,
+                request.account_address,
+            )
+            .await
+        }
+        .boxed_local()
+    }
+}
@@ -0,0 +1,745 @@
+use std::collections::HashMap;
+
+use subxt::utils::{MultiSignature, H256};
+use yew::AttrValue;
+
+use crate::call_check::{compare_calls, CallComparison};
+use crate::governance::{CastVote, Delegation};
+use crate::hex_display::hex_value;
+use crate::outcome::SubmissionOutcome;
+use crate::services::{extrinsic_hash, Account, StatusEvent};
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Default)]
+pub enum SigningStage {
+    Error(String),
+    #[default]
+    CreatingOnlineClient,
+    EnterMessage,
+    EnterBalance,
+    RequestingAccounts,
+    SelectAccount(Vec<Account>),
+    /// final review of the vote with the selected account, before the extension is asked to sign
+    ReviewVote(Account),
+    Signing(Account),
+    /// the account delegates its votes on the referendum's track, so a direct vote would fail.
+    DelegationBlocked {
+        account: Account,
+        delegation: Delegation,
+        undelegate_call_hex: AttrValue,
+    },
+    /// the extension didn't sign, e.g. its popup was closed. The vote can be signed again with
+    /// the same nonce, as long as it's still current.
+    SigningDeclined {
+        account: Account,
+        nonce: u64,
+        error: String,
+    },
+    /// signed in offline mode, to be submitted later by the user
+    OfflineSigned {
+        signer_account: Account,
+        signed_extrinsic_hex: AttrValue,
+    },
+    SigningSuccess {
+        signer_account: Account,
+        signature: MultiSignature,
+        signed_extrinsic_hex: AttrValue,
+        submitting_stage: SubmittingStage,
+        /// the account's vote on the referendum before this one, which the new vote replaces
+        replaced_vote: Option<CastVote>,
+    },
+}
+
+impl SigningStage {
+    /// whether the stage waits on the user, who may take their time before the connection is
+    /// needed to sign or submit
+    pub fn is_interactive(&self) -> bool {
+        match self {
+            SigningStage::EnterMessage
+            | SigningStage::EnterBalance
+            | SigningStage::SelectAccount(_)
+            | SigningStage::ReviewVote(_)
+            | SigningStage::Signing(_)
+            | SigningStage::DelegationBlocked { .. }
+            | SigningStage::SigningDeclined { .. } => true,
+            SigningStage::SigningSuccess {
+                submitting_stage, ..
+            } => matches!(submitting_stage, SubmittingStage::Initial { .. }),
+            SigningStage::Error(_)
+            | SigningStage::CreatingOnlineClient
+            | SigningStage::RequestingAccounts
+            | SigningStage::OfflineSigned { .. } => false,
+        }
+    }
+
+    /// short name of the stage, including the submitting stage once signed
+    pub fn label(&self) -> &'static str {
+        match self {
+            SigningStage::Error(_) => "error",
+            SigningStage::CreatingOnlineClient => "creating_online_client",
+            SigningStage::EnterMessage => "enter_message",
+            SigningStage::EnterBalance => "enter_balance",
+            SigningStage::RequestingAccounts => "requesting_accounts",
+            SigningStage::SelectAccount(_) => "select_account",
+            SigningStage::ReviewVote(_) => "review_vote",
+            SigningStage::Signing(_) => "signing",
+            SigningStage::SigningDeclined { .. } => "signing_declined",
+            SigningStage::DelegationBlocked { .. } => "delegation_blocked",
+            SigningStage::OfflineSigned { .. } => "offline_signed",
+            SigningStage::SigningSuccess {
+                submitting_stage, ..
+            } => match submitting_stage {
+                SubmittingStage::Initial { .. } => "signed",
+                SubmittingStage::Submitting { .. } => "submitting",
+                SubmittingStage::Submitted { .. } => "submitted",
+                SubmittingStage::Finalized(_) => "finalized",
+                SubmittingStage::Cancelled => "cancelled",
+                SubmittingStage::Error(_) => "submission_error",
+            },
+        }
+    }
+}
+
+pub enum SubmittingStage {
+    /// the encoded signed extrinsic, not bound to a client so it survives reconnecting
+    Initial {
+        signed_extrinsic: Vec<u8>,
+    },
+    Submitting {
+        extrinsic_hash: H256,
+    },
+    /// accepted by the node, submitted without watching for the result
+    Submitted {
+        extrinsic_hash: H256,
+    },
+    /// finalized, successfully dispatched or not
+    Finalized(SubmissionOutcome),
+    /// the user stopped watching; the extrinsic may still be included on-chain.
+    Cancelled,
+    Error(anyhow::Error),
+}
+
+/// What came of a request to the extension to sign, see `sign_call` and `sign_offline`.
+pub enum SigningResult {
+    /// the encoded signed extrinsic, u64 is the nonce it was signed with
+    Signed(MultiSignature, Vec<u8>, u64),
+    /// the encoded signed extrinsic, built without an online client
+    SignedOffline(Vec<u8>),
+    /// the extension didn't sign, u64 is the nonce the signing request was made with
+    Declined(String, u64),
+    /// the request could not be made, e.g. the nonce could not be fetched
+    Failed(String),
+}
+
+/// The part of the vote page's state that `reduce` transitions, from the review of the vote to
+/// its submission.
+#[derive(Default)]
+pub struct FlowState {
+    pub stage: SigningStage,
+    /// incremented on every signing request and `Reset`, so the result of an earlier request
+    /// is never taken for the current one
+    pub signing_epoch: u32,
+    /// nonce the current extrinsic was signed with, to track the next nonce once submitted
+    pub signed_nonce: Option<u64>,
+    /// next nonce per address, ahead of the chain while earlier extrinsics are pending
+    pub next_nonces: HashMap<String, u64>,
+    /// the submitted extrinsic, kept to submit it again after a failure
+    pub submitted_extrinsic: Option<Vec<u8>>,
+    /// why the vote has to be signed again, shown in the review
+    pub resign_reason: Option<String>,
+    /// the previewed call side by side with the signed one
+    pub call_comparison: Option<Result<CallComparison, String>>,
+    /// dry-run of the signed extrinsic, `None` while running
+    pub validation: Option<Result<String, String>>,
+    /// the Polkadot.js apps link was copied, or copying failed
+    pub apps_link_copied: Option<bool>,
+}
+
+/// What `reduce` reads from the rest of the page.
+#[derive(Default)]
+pub struct FlowInputs {
+    /// the encoded call previewed to the user
+    pub call_data: Vec<u8>,
+    /// the balance is resolved and the account checked for delegations, so it can be signed
+    pub review_ready: bool,
+    /// connected to a node, or in offline mode
+    pub connected: bool,
+    /// submit as soon as signed
+    pub auto_submit: bool,
+    /// only sign, the extrinsic is submitted by the embedding page
+    pub signer_only: bool,
+    /// a multi-account vote is running, which moves on to the next account on failures
+    pub multi_vote: bool,
+    /// the signer's vote on the referendum, which voting replaces
+    pub existing_vote: Option<CastVote>,
+}
+
+pub enum FlowMessage {
+    Reset,
+    SignWithAccount,
+    /// u32 is the signing epoch the request was made in
+    SigningFinished(u32, SigningResult),
+    /// `watch: false` only waits for the node to accept the extrinsic
+    SubmitSigned {
+        watch: bool,
+    },
+    /// a new online client replaced a dropped connection. A runtime upgrade meanwhile
+    /// invalidates a signed extrinsic that wasn't submitted yet.
+    Reconnected {
+        runtime_changed: bool,
+    },
+}
+
+/// A side effect of a transition, run by `VoteComponent`.
+#[derive(Debug, PartialEq)]
+pub enum Effect {
+    /// aborts every running future, e.g. a signing request or a submission watch
+    AbortTasks,
+    /// resets the message, balance and options entered for the vote
+    ResetForm,
+    Connect,
+    /// restarts the chain queries, polling and the feed after `AbortTasks`
+    RestartBackgroundTasks,
+    /// remembers the account to offer it first next time
+    RememberAccount(Account),
+    /// asks the extension to sign the vote with `account`, with the tracked next nonce if any
+    Sign {
+        account: Account,
+        epoch: u32,
+        nonce: Option<u64>,
+    },
+    Notify(StatusEvent),
+    /// dry-runs the encoded signed extrinsic
+    Validate(Vec<u8>),
+    Submit {
+        extrinsic: Vec<u8>,
+        watch: bool,
+    },
+    /// loads the review of the vote with `account` again
+    Review(Account),
+    /// records the result of the current account of a multi-account vote
+    FinishQueued(Result<String, String>),
+    Error(String),
+}
+
+/// the next state of the signing flow after `msg`, and the side effects to run. Messages that
+/// don't apply to the current stage leave the state unchanged.
+pub fn reduce(
+    mut state: FlowState,
+    msg: FlowMessage,
+    inputs: &FlowInputs,
+) -> (FlowState, Vec<Effect>) {
+    let mut effects = vec![];
+    match msg {
+        FlowMessage::Reset => {
+            state.signing_epoch = state.signing_epoch.wrapping_add(1);
+            state.signed_nonce = None;
+            state.submitted_extrinsic = None;
+            state.resign_reason = None;
+            state.call_comparison = None;
+            state.validation = None;
+            state.apps_link_copied = None;
+            effects.extend([Effect::AbortTasks, Effect::ResetForm]);
+            if inputs.connected {
+                state.stage = SigningStage::EnterBalance;
+            } else {
+                state.stage = SigningStage::CreatingOnlineClient;
+                effects.push(Effect::Connect);
+            }
+            effects.push(Effect::RestartBackgroundTasks);
+        }
+        FlowMessage::SignWithAccount => {
+            // a percentage is only signed once it's resolved to an amount
+            let SigningStage::ReviewVote(account) = &state.stage else {
+                return (state, effects);
+            };
+            if !inputs.review_ready {
+                return (state, effects);
+            }
+            let account = account.clone();
+            state.signing_epoch = state.signing_epoch.wrapping_add(1);
+            state.resign_reason = None;
+            effects.extend([
+                Effect::RememberAccount(account.clone()),
+                Effect::Sign {
+                    account: account.clone(),
+                    epoch: state.signing_epoch,
+                    nonce: state.next_nonces.get(&account.address).copied(),
+                },
+            ]);
+            state.stage = SigningStage::Signing(account);
+        }
+        // a result of a request made before a `Reset` or for another account
+        FlowMessage::SigningFinished(epoch, _) if epoch != state.signing_epoch => {}
+        FlowMessage::SigningFinished(_, result) => {
+            let SigningStage::Signing(account) = &state.stage else {
+                return (state, effects);
+            };
+            let account = account.clone();
+            match result {
+                SigningResult::Failed(err) => effects.push(Effect::Error(err)),
+                // a multi-account vote moves on to the next account instead
+                SigningResult::Declined(error, _) if inputs.multi_vote => effects.push(
+                    Effect::FinishQueued(Err(format!("Signing via extension failed: {error}"))),
+                ),
+                SigningResult::Declined(error, nonce) => {
+                    state.stage = SigningStage::SigningDeclined {
+                        account,
+                        nonce,
+                        error,
+                    };
+                }
+                SigningResult::SignedOffline(signed_extrinsic) => {
+                    effects.push(Effect::Notify(StatusEvent::Signed {
+                        address: account.address.clone(),
+                    }));
+                    state.stage = SigningStage::OfflineSigned {
+                        signer_account: account,
+                        signed_extrinsic_hex: hex_value(signed_extrinsic),
+                    };
+                }
+                SigningResult::Signed(signature, signed_extrinsic, nonce) => {
+                    state.signed_nonce = Some(nonce);
+                    effects.push(Effect::Notify(StatusEvent::Signed {
+                        address: account.address.clone(),
+                    }));
+                    let call_comparison = compare_calls(&inputs.call_data, &signed_extrinsic)
+                        .map_err(|err| err.to_string());
+                    let mismatch =
+                        matches!(&call_comparison, Ok(comparison) if !comparison.matches);
+                    state.call_comparison = Some(call_comparison);
+                    state.validation = None;
+                    state.apps_link_copied = None;
+                    // never submittable, whatever the build profile
+                    if mismatch {
+                        state.stage = SigningStage::Error("The signed call differs from the previewed vote, submission was blocked. Please reload and try again.".to_string());
+                        if inputs.multi_vote {
+                            effects.push(Effect::FinishQueued(Err(
+                                "the signed call differs from the preview".to_string(),
+                            )));
+                        }
+                        return (state, effects);
+                    }
+                    effects.push(Effect::Validate(signed_extrinsic.clone()));
+                    state.stage = SigningStage::SigningSuccess {
+                        signer_account: account,
+                        signature,
+                        signed_extrinsic_hex: hex_value(&signed_extrinsic),
+                        submitting_stage: SubmittingStage::Initial { signed_extrinsic },
+                        replaced_vote: inputs.existing_vote.clone(),
+                    };
+                    // accounts of a multi-account vote are submitted as soon as they're signed
+                    if (inputs.auto_submit || inputs.multi_vote) && !inputs.signer_only {
+                        let (next, submit_effects) =
+                            reduce(state, FlowMessage::SubmitSigned { watch: true }, inputs);
+                        effects.extend(submit_effects);
+                        return (next, effects);
+                    }
+                }
+            }
+        }
+        FlowMessage::SubmitSigned { .. } if inputs.signer_only => {}
+        FlowMessage::SubmitSigned { watch } => {
+            let SigningStage::SigningSuccess {
+                signer_account,
+                submitting_stage,
+                ..
+            } = &mut state.stage
+            else {
+                return (state, effects);
+            };
+            let SubmittingStage::Initial { signed_extrinsic } = submitting_stage else {
+                return (state, effects);
+            };
+            let extrinsic = std::mem::take(signed_extrinsic);
+            if let Some(nonce) = state.signed_nonce {
+                state
+                    .next_nonces
+                    .insert(signer_account.address.clone(), nonce + 1);
+            }
+            *submitting_stage = SubmittingStage::Submitting {
+                extrinsic_hash: extrinsic_hash(&extrinsic),
+            };
+            state.submitted_extrinsic = Some(extrinsic.clone());
+            effects.extend([
+                Effect::Notify(StatusEvent::Submitted),
+                Effect::Submit { extrinsic, watch },
+            ]);
+        }
+        FlowMessage::Reconnected { runtime_changed } => {
+            // its signature is for the runtime version it was signed for
+            if let (
+                true,
+                SigningStage::SigningSuccess {
+                    signer_account,
+                    submitting_stage: SubmittingStage::Initial { .. },
+                    ..
+                },
+            ) = (runtime_changed, &state.stage)
+            {
+                let account = signer_account.clone();
+                effects.push(Effect::Review(account.clone()));
+                state.resign_reason = Some("The runtime was upgraded while reconnecting, so the signed extrinsic is no longer valid. Please sign again.".to_string());
+                state.stage = SigningStage::ReviewVote(account);
+            }
+        }
+    }
+    (state, effects)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::offline::signed_extrinsic_bytes;
+    use crate::services::node_runtime::runtime_types::frame_system::pallet::Call as SystemCall;
+    use crate::services::node_runtime::runtime_types::kusama_runtime::RuntimeCall;
+    use subxt::ext::codec::Encode;
+    use subxt::utils::AccountId32;
+
+    const ADDRESS: &str = "HNZata7iMYWmk5RvZRTiAsSDhV8366zq2YGb3tLH5Upf74F";
+
+    fn account() -> Account {
+        Account {
+            name: "Alice".to_string(),
+            source: "polkadot-js".to_string(),
+            ty: "sr25519".to_string(),
+            address: ADDRESS.to_string(),
+            is_default: false,
+        }
+    }
+
+    fn remark_call(remark: &[u8]) -> Vec<u8> {
+        RuntimeCall::System(SystemCall::remark {
+            remark: remark.to_vec(),
+        })
+        .encode()
+    }
+
+    fn signed(call_data: &[u8], nonce: u64) -> SigningResult {
+        let signature = MultiSignature::Sr25519([1; 64]);
+        let extrinsic = signed_extrinsic_bytes(call_data, AccountId32([2; 32]), &signature, nonce);
+        SigningResult::Signed(signature, extrinsic, nonce)
+    }
+
+    fn inputs() -> FlowInputs {
+        FlowInputs {
+            call_data: remark_call(b"aye"),
+            review_ready: true,
+            connected: true,
+            ..Default::default()
+        }
+    }
+
+    fn with_stage(stage: SigningStage) -> FlowState {
+        FlowState {
+            stage,
+            ..Default::default()
+        }
+    }
+
+    /// the state after signing with `account()` in epoch 1
+    fn signing() -> FlowState {
+        FlowState {
+            stage: SigningStage::Signing(account()),
+            signing_epoch: 1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reset_returns_to_the_balance_and_aborts_tasks() {
+        let state = FlowState {
+            signing_epoch: 3,
+            signed_nonce: Some(7),
+            submitted_extrinsic: Some(vec![1]),
+            ..signing()
+        };
+        let (state, effects) = reduce(state, FlowMessage::Reset, &inputs());
+        assert_eq!(state.stage.label(), "enter_balance");
+        assert_eq!(state.signing_epoch, 4);
+        assert_eq!(state.signed_nonce, None);
+        assert_eq!(state.submitted_extrinsic, None);
+        assert_eq!(
+            effects,
+            [
+                Effect::AbortTasks,
+                Effect::ResetForm,
+                Effect::RestartBackgroundTasks
+            ]
+        );
+    }
+
+    #[test]
+    fn reset_without_a_connection_connects() {
+        let inputs = FlowInputs {
+            connected: false,
+            ..inputs()
+        };
+        let (state, effects) = reduce(signing(), FlowMessage::Reset, &inputs);
+        assert_eq!(state.stage.label(), "creating_online_client");
+        assert_eq!(
+            effects,
+            [
+                Effect::AbortTasks,
+                Effect::ResetForm,
+                Effect::Connect,
+                Effect::RestartBackgroundTasks
+            ]
+        );
+    }
+
+    #[test]
+    fn sign_with_account_starts_a_new_epoch() {
+        let mut state = with_stage(SigningStage::ReviewVote(account()));
+        state.next_nonces.insert(ADDRESS.to_string(), 5);
+        state.resign_reason = Some("runtime upgraded".to_string());
+        let (state, effects) = reduce(state, FlowMessage::SignWithAccount, &inputs());
+        assert_eq!(state.stage.label(), "signing");
+        assert_eq!(state.signing_epoch, 1);
+        assert_eq!(state.resign_reason, None);
+        assert_eq!(
+            effects,
+            [
+                Effect::RememberAccount(account()),
+                Effect::Sign {
+                    account: account(),
+                    epoch: 1,
+                    nonce: Some(5),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn sign_with_account_waits_for_the_review() {
+        let inputs = FlowInputs {
+            review_ready: false,
+            ..inputs()
+        };
+        let state = with_stage(SigningStage::ReviewVote(account()));
+        let (state, effects) = reduce(state, FlowMessage::SignWithAccount, &inputs);
+        assert_eq!(state.stage.label(), "review_vote");
+        assert!(effects.is_empty());
+
+        // only from the review
+        let (state, effects) = reduce(signing(), FlowMessage::SignWithAccount, &inputs);
+        assert_eq!(state.signing_epoch, 1);
+        assert!(effects.is_empty());
+    }
+
+    #[test]
+    fn signed_extrinsic_is_validated() {
+        let inputs = inputs();
+        let result = signed(&inputs.call_data, 4);
+        let (state, effects) = reduce(signing(), FlowMessage::SigningFinished(1, result), &inputs);
+        assert_eq!(state.stage.label(), "signed");
+        assert_eq!(state.signed_nonce, Some(4));
+        assert!(matches!(&state.call_comparison, Some(Ok(comparison)) if comparison.matches));
+        let SigningStage::SigningSuccess {
+            submitting_stage: SubmittingStage::Initial { signed_extrinsic },
+            ..
+        } = &state.stage
+        else {
+            panic!("not signed");
+        };
+        assert_eq!(
+            effects,
+            [
+                Effect::Notify(StatusEvent::Signed {
+                    address: ADDRESS.to_string()
+                }),
+                Effect::Validate(signed_extrinsic.clone()),
+            ]
+        );
+    }
+
+    #[test]
+    fn signed_result_of_another_epoch_is_ignored() {
+        let inputs = inputs();
+        let result = signed(&inputs.call_data, 4);
+        let (state, effects) = reduce(signing(), FlowMessage::SigningFinished(0, result), &inputs);
+        assert_eq!(state.stage.label(), "signing");
+        assert!(effects.is_empty());
+
+        // e.g. after a `Reset`
+        let result = signed(&inputs.call_data, 4);
+        let state = with_stage(SigningStage::EnterBalance);
+        let (state, effects) = reduce(state, FlowMessage::SigningFinished(0, result), &inputs);
+        assert_eq!(state.stage.label(), "enter_balance");
+        assert!(effects.is_empty());
+    }
+
+    #[test]
+    fn mismatching_signed_call_is_blocked() {
+        let inputs = FlowInputs {
+            auto_submit: true,
+            multi_vote: true,
+            ..inputs()
+        };
+        let result = signed(&remark_call(b"nay"), 4);
+        let (state, effects) = reduce(signing(), FlowMessage::SigningFinished(1, result), &inputs);
+        assert_eq!(state.stage.label(), "error");
+        assert!(matches!(&state.call_comparison, Some(Ok(comparison)) if !comparison.matches));
+        assert_eq!(state.submitted_extrinsic, None);
+        assert_eq!(
+            effects,
+            [
+                Effect::Notify(StatusEvent::Signed {
+                    address: ADDRESS.to_string()
+                }),
+                Effect::FinishQueued(Err("the signed call differs from the preview".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn auto_submit_submits_once_signed() {
+        let inputs = FlowInputs {
+            auto_submit: true,
+            ..inputs()
+        };
+        let result = signed(&inputs.call_data, 4);
+        let (state, effects) = reduce(signing(), FlowMessage::SigningFinished(1, result), &inputs);
+        assert_eq!(state.stage.label(), "submitting");
+        assert_eq!(state.next_nonces.get(ADDRESS), Some(&5));
+        let extrinsic = state.submitted_extrinsic.clone().unwrap();
+        assert_eq!(
+            effects[1..],
+            [
+                Effect::Validate(extrinsic.clone()),
+                Effect::Notify(StatusEvent::Submitted),
+                Effect::Submit {
+                    extrinsic,
+                    watch: true
+                },
+            ]
+        );
+
+        // unless the embedding page submits
+        let inputs = FlowInputs {
+            signer_only: true,
+            ..inputs
+        };
+        let result = signed(&inputs.call_data, 4);
+        let (state, _) = reduce(signing(), FlowMessage::SigningFinished(1, result), &inputs);
+        assert_eq!(state.stage.label(), "signed");
+    }
+
+    #[test]
+    fn declined_signing_can_be_retried() {
+        let result = SigningResult::Declined("Cancelled".to_string(), 4);
+        let (state, effects) = reduce(
+            signing(),
+            FlowMessage::SigningFinished(1, result),
+            &inputs(),
+        );
+        assert!(matches!(
+            state.stage,
+            SigningStage::SigningDeclined { nonce: 4, .. }
+        ));
+        assert!(effects.is_empty());
+
+        // a multi-account vote moves on instead
+        let inputs = FlowInputs {
+            multi_vote: true,
+            ..inputs()
+        };
+        let result = SigningResult::Declined("Cancelled".to_string(), 4);
+        let (state, effects) = reduce(signing(), FlowMessage::SigningFinished(1, result), &inputs);
+        assert_eq!(state.stage.label(), "signing");
+        assert_eq!(
+            effects,
+            [Effect::FinishQueued(Err(
+                "Signing via extension failed: Cancelled".to_string()
+            ))]
+        );
+    }
+
+    #[test]
+    fn failed_and_offline_signing() {
+        let result = SigningResult::Failed("Fetching account nonce failed".to_string());
+        let (_, effects) = reduce(
+            signing(),
+            FlowMessage::SigningFinished(1, result),
+            &inputs(),
+        );
+        assert_eq!(
+            effects,
+            [Effect::Error("Fetching account nonce failed".to_string())]
+        );
+
+        let result = SigningResult::SignedOffline(vec![0xab]);
+        let (state, _) = reduce(
+            signing(),
+            FlowMessage::SigningFinished(1, result),
+            &inputs(),
+        );
+        assert!(matches!(
+            &state.stage,
+            SigningStage::OfflineSigned { signed_extrinsic_hex, .. } if signed_extrinsic_hex.as_str() == "0xab"
+        ));
+    }
+
+    #[test]
+    fn submit_signed_only_from_initial() {
+        let inputs = inputs();
+        let result = signed(&inputs.call_data, 4);
+        let (state, _) = reduce(signing(), FlowMessage::SigningFinished(1, result), &inputs);
+        let (state, effects) = reduce(state, FlowMessage::SubmitSigned { watch: false }, &inputs);
+        assert_eq!(state.stage.label(), "submitting");
+        assert!(matches!(
+            effects.last(),
+            Some(Effect::Submit { watch: false, .. })
+        ));
+
+        // already submitting
+        let (state, effects) = reduce(state, FlowMessage::SubmitSigned { watch: true }, &inputs);
+        assert_eq!(state.stage.label(), "submitting");
+        assert!(effects.is_empty());
+
+        let (state, effects) = reduce(
+            with_stage(SigningStage::EnterBalance),
+            FlowMessage::SubmitSigned { watch: true },
+            &inputs,
+        );
+        assert_eq!(state.stage.label(), "enter_balance");
+        assert!(effects.is_empty());
+    }
+
+    #[test]
+    fn runtime_upgrade_while_reconnecting_asks_to_sign_again() {
+        let inputs = inputs();
+        let result = signed(&inputs.call_data, 4);
+        let (signed_state, _) = reduce(signing(), FlowMessage::SigningFinished(1, result), &inputs);
+
+        let (state, effects) = reduce(
+            signed_state,
+            FlowMessage::Reconnected {
+                runtime_changed: false,
+            },
+            &inputs,
+        );
+        assert_eq!(state.stage.label(), "signed");
+        assert!(effects.is_empty());
+
+        let (state, effects) = reduce(
+            state,
+            FlowMessage::Reconnected {
+                runtime_changed: true,
+            },
+            &inputs,
+        );
+        assert_eq!(state.stage.label(), "review_vote");
+        assert!(state.resign_reason.is_some());
+        assert_eq!(effects, [Effect::Review(account())]);
+    }
+
+    #[test]
+    fn only_stages_waiting_on_the_user_are_interactive() {
+        assert!(SigningStage::EnterBalance.is_interactive());
+        assert!(SigningStage::ReviewVote(account()).is_interactive());
+        assert!(!SigningStage::CreatingOnlineClient.is_interactive());
+        assert!(!SigningStage::RequestingAccounts.is_interactive());
+        assert!(!SigningStage::Error("failed".into()).is_interactive());
+    }
+}
@@ -1,64 +1,1250 @@
 use anyhow::anyhow;
+use futures::future::{AbortHandle, Abortable};
 use futures::FutureExt;
+use std::future::Future;
+use std::rc::{Rc, Weak};
+use std::time::Duration;
 
-use subxt::{OnlineClient, PolkadotConfig};
+use subxt::{Metadata, OnlineClient, PolkadotConfig};
 
-use subxt::ext::codec::{Decode, Encode};
+use subxt::ext::codec::Encode;
 use subxt::tx::SubmittableExtrinsic;
 use subxt::tx::TxPayload;
-use subxt::utils::{AccountId32, MultiSignature};
+use subxt::utils::{AccountId32, H256};
+use wasm_bindgen_futures::JsFuture;
 
+use crate::accounts::{
+    available_recent_accounts, duplicate_sources, group_accounts_by_source, merge_accounts,
+    parse_account_id, remember_account, RecentAccount,
+};
+use crate::balance::{
+    below_existential_deposit, fee_shortfall, parse_amount, parse_percentage, planck_per_unit,
+    Balance,
+};
+use crate::call_check::{check_vote_call_index, verify_vote_call, CallComparison, IntendedVote};
+use crate::chain_info::{ChainInfo, TokenOverride};
+use crate::explorer::{
+    account_url, block_url, decode_call_url, decode_extrinsic_url, extrinsic_url,
+    parse_explorer_base_url, referendum_url, Explorer, MAX_LINK_LENGTH,
+};
+use crate::feed::{FeedEntry, FeedVerbosity};
+use crate::governance::{
+    fetch_account_votes, fetch_delegation_for_referendum, fetch_free_balance, fetch_lock_impact,
+    fetch_lock_schedule, fetch_referendum_count, fetch_tally, read_tracks, CastVote, Delegation,
+    LockEntry, LockImpact, LockSchedule, Tally, Track, TrackVote,
+};
+use crate::hex_display::{hex_value, HexDisplay};
+use crate::loading_button::LoadingButton;
+use crate::lock_graph::LockGraph;
+use crate::multisig::{
+    call_hash, fetch_multisig_step, vote_max_weight, MultisigParams, MultisigStep,
+};
+use crate::offline::{signed_extrinsic_bytes, OfflineField, OfflineInput};
+use crate::outcome::{describe_validation, PoolRejection, SubmissionError, SubmissionOutcome};
+use crate::relay::{relay_extrinsic, SubmissionBackend};
 use crate::services::{
-    extension_signature_for_extrinsic, get_accounts, node_runtime,
+    add_visibility_listener, check_kusama_genesis, check_signing_payload, connect_with_retry,
+    estimate_fee, estimate_weight, extrinsic_hash, find_extrinsic_in_block,
+    find_finalized_extrinsic, get_accounts, get_extension_metadata, get_extension_status,
+    is_page_visible, js_copy_to_clipboard, local_storage_get, local_storage_set, node_runtime,
+    node_runtime::runtime_types::frame_system::pallet::Call as SystemCall,
+    node_runtime::runtime_types::kusama_runtime::RuntimeCall,
+    node_runtime::runtime_types::pallet_conviction_voting::pallet::Call as ConvictionVotingCall,
     node_runtime::runtime_types::pallet_conviction_voting::vote::{AccountVote, Vote},
-    subscribe_to_finalized_blocks, Account,
+    node_runtime::runtime_types::pallet_utility::pallet::Call as UtilityCall,
+    post_status, provide_metadata, query_param, remove_visibility_listener, signing_payload,
+    subscribe_to_finalized_blocks, unsupported_signed_extensions, validate_extrinsic, Account,
+    BrowserSupport, ChainParams, ExtensionMetadata, ExtensionStatus, FinalizedBlock, StatusEvent,
+    WeightEstimate, CONNECT_ATTEMPTS, DEFAULT_WEIGHT_WARNING_PERCENT, KUSAMA_RPC_ENDPOINT,
+    KUSAMA_RPC_ENDPOINTS, SUPPORTED_SIGNED_EXTENSIONS,
 };
+use crate::share_card::ShareCard;
+use crate::signer::{ExtensionSigner, Signer, SigningRequest};
+use crate::signing_flow::{
+    reduce, Effect, FlowInputs, FlowMessage, FlowState, SigningResult, SigningStage,
+    SubmittingStage,
+};
+use crate::Route;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
-use web_sys::HtmlInputElement;
+use wasm_bindgen::prelude::Closure;
+use web_sys::{HtmlInputElement, HtmlTextAreaElement};
+use yew::platform::time::sleep;
 use yew::prelude::*;
+use yew_router::prelude::*;
+
+/// time an async stage may take before the user is asked whether it's stuck
+const STAGE_TIMEOUT: Duration = Duration::from_secs(45);
+/// finalized blocks searched for a submitted extrinsic when the page becomes visible again
+const RECOVERY_SEARCH_DEPTH: u32 = 50;
+/// time without a finalized block after which the endpoint is considered stalled and replaced
+const FEED_STALL_TIMEOUT: Duration = Duration::from_secs(30);
+/// how often the age of the feed's blocks is refreshed
+const FEED_CLOCK_INTERVAL: Duration = Duration::from_secs(1);
+/// number of finalized blocks the feed shows
+const FEED_HISTORY_LEN: usize = 1;
+/// time between two RPC latency measurements
+const PING_INTERVAL: Duration = Duration::from_secs(10);
+/// time between two keep-alive requests while the user takes their time in an interactive
+/// stage, so the connection doesn't idle and drop before signing
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(20);
+/// RPC latency above which the connection is shown as slow
+const SLOW_RPC_LATENCY: Duration = Duration::from_millis(1000);
+
+/// `localStorage` key of the extension the last signing account came from
+const LAST_SOURCE_KEY: &str = "ref275.lastAccountSource";
+const RECENT_ACCOUNTS_KEY: &str = "ref275.recentAccounts";
+const EXPLORER_URL_KEY: &str = "ref275.explorerBaseUrl";
+const FEED_VERBOSITY_KEY: &str = "ref275.feedVerbosity";
+const AUTO_SUBMIT_KEY: &str = "ref275.autoSubmit";
+const WAIT_FOR_IN_BLOCK_KEY: &str = "ref275.waitForInBlock";
+const PUBLIC_RPC_NOTICE_KEY: &str = "ref275.publicRpcNoticeDismissed";
+
+/// shown as the endpoint of a client passed in by the host page without its endpoint
+const INJECTED_CLIENT_LABEL: &str = "injected client";
+
+/// An already connected client, e.g. of a host page that embeds the component. Props compare
+/// it by identity, since clients can't be compared by value.
+#[derive(Clone)]
+pub struct SharedClient(Rc<OnlineClient<PolkadotConfig>>);
+
+impl From<OnlineClient<PolkadotConfig>> for SharedClient {
+    fn from(client: OnlineClient<PolkadotConfig>) -> Self {
+        SharedClient(Rc::new(client))
+    }
+}
+
+impl PartialEq for SharedClient {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct VoteProps {
+    /// index of the referendum to vote on
+    #[prop_or(275)]
+    pub referendum_index: u32,
+    /// client to use instead of connecting to `KUSAMA_RPC_ENDPOINT`. Only read when the
+    /// component is created. It's never replaced, a dropped connection is shown as an error.
+    #[prop_or_default]
+    pub client: Option<SharedClient>,
+    /// endpoint `client` is connected to, shown to the user. `INJECTED_CLIENT_LABEL` if unset.
+    #[prop_or_default]
+    pub client_endpoint: Option<AttrValue>,
+}
 
 pub struct VoteComponent {
+    /// `referendum_index` prop, kept here for the helpers encoding the vote
+    referendum: u32,
     message: String,
+    /// attach the message with `system.remark_with_event`, so indexers can find it in the events
+    remark_with_event: bool,
+    /// the message starts as the `remark_tag` of the referendum, set with `?remarktag=1` by
+    /// campaigns indexing the votes off-chain
+    preset_remark: bool,
     conviction: Conviction,
-    balance: u128,
+    balance: Balance,
+    /// why the entered balance could not be parsed, the vote keeps the last valid balance
+    balance_error: Option<String>,
+    /// the balance was entered as this percentage of the signer's free balance. It's resolved
+    /// to an amount once the account is chosen, `balance` is only valid after that.
+    balance_percentage: Option<u8>,
+    /// free balance of the reviewed account, to resolve `balance_percentage` and check that
+    /// the fee can still be paid
+    free_balance: Option<Result<Balance, String>>,
     remark_call_bytes: Vec<u8>,
     vote_call_bytes: Vec<u8>,
     online_client: Option<OnlineClient<PolkadotConfig>>,
-    stage: SigningStage,
-    finalized_blocks: Vec<AttrValue>,
+    /// the endpoint of `KUSAMA_RPC_ENDPOINTS` the online client is connected to
+    rpc_endpoint: String,
+    /// read once from `online_client` when it's created
+    chain_params: Option<ChainParams>,
+    /// why the live metadata is incompatible with the bundled one. Signing is blocked if set.
+    metadata_mismatch: Option<String>,
+    /// referenda tracks, read from the runtime when the online client is created
+    tracks: Vec<Track>,
+    /// token and explorer of the connected chain, Kusama's registry entry until fetched
+    chain_info: ChainInfo,
+    /// token symbol and decimals set with `?symbol=` and `?decimals=`, applied over `chain_info`
+    token_override: TokenOverride,
+    /// the notice about submitting through a public RPC was dismissed, persisted in `localStorage`
+    public_rpc_notice_dismissed: bool,
+    /// where the signed extrinsic is submitted, see `SubmissionBackend`
+    submission_backend: SubmissionBackend,
+    /// this device only signs, set with `?signeronly=1`: the signed extrinsic is handed off to
+    /// be submitted from another device, and there's no submit button
+    signer_only: bool,
+    /// the `?relay=` URL is invalid, so extrinsics are submitted directly
+    relay_error: Option<String>,
+    /// explorer base URL set in the advanced panel, used instead of Subscan
+    custom_explorer_url: Option<String>,
+    /// raw input of the custom explorer base URL
+    explorer_url_input: String,
+    explorer_url_error: Option<String>,
+    /// stage of the vote and the state of signing and submitting it, see `reduce`
+    flow: FlowState,
+    finalized_blocks: Vec<FeedEntry>,
+    /// `Date.now()` the ages of the feed's blocks are shown relative to, refreshed every
+    /// `FEED_CLOCK_INTERVAL` while the feed has blocks and the page is visible
+    feed_clock: f64,
+    feed_clock_running: bool,
+    /// stops the finalized blocks subscription, e.g. to restart it on a new client
+    feed_handle: Option<AbortHandle>,
+    /// shown in the feed after the subscription was restarted on a new connection
+    feed_marker: Option<String>,
+    /// incremented on every received block and restart, so stale feed watchdogs are ignored
+    feed_epoch: u32,
+    /// what the finalized blocks feed shows per block, persisted in `localStorage`
+    feed_verbosity: FeedVerbosity,
+    /// aborts watching the submitted extrinsic. Cleared once the watch completes.
+    submission_handle: Option<AbortHandle>,
+    /// in-flight futures started with `spawn`, aborted on destroy. The `Weak` is dead once the
+    /// future completed, so its handle can be dropped.
+    tasks: Vec<(AbortHandle, Weak<()>)>,
+    /// conviction votes already recorded for the signer account, `None` while loading.
+    account_votes: Option<Result<Vec<TrackVote>, String>>,
+    /// fee of the vote with the reviewed account, `None` while loading or not estimated
+    fee_estimate: Option<Result<Balance, String>>,
+    /// locked balance of the signer before and after the vote, `None` while loading.
+    lock_impact: Option<Result<LockImpact, String>>,
+    /// the reviewed account was checked for a delegation on the referendum's track, which
+    /// would make its vote fail. Signing waits for the check.
+    delegation_checked: bool,
+    /// conviction voting locks of the signer over time, `None` while loading or if they
+    /// couldn't be read, since the lock impact above already covers the essentials.
+    lock_schedule: Option<LockSchedule>,
+    /// current tally of the referendum, to project the effect of the vote. `None` while loading,
+    /// or if the referendum isn't ongoing or the tally could not be read.
+    tally: Option<Tally>,
+    /// number of referenda created so far, to tell if the referendum exists. `None` until read.
+    referendum_count: Option<u32>,
+    /// incremented on every stage transition, so stale watchdog timers can be ignored
+    stage_epoch: u32,
+    /// the current async stage has been running for longer than `STAGE_TIMEOUT`
+    stage_stuck: bool,
+    /// post status changes to the embedding page, enabled with `?postmessage=1`
+    post_status: bool,
+    /// highest selectable conviction, set with e.g. `?maxconviction=3` (the slider position) so
+    /// shared deployments can rule out very long locks
+    max_conviction: Conviction,
+    /// compact widget for embedding in other pages, enabled with `?embed=1`: only the vote form
+    /// and the submit flow, without header, footer and panels
+    embed: bool,
+    /// shows the advanced panel with chain and signing details
+    show_advanced: bool,
+    /// shows the connection diagnostics panel, built from values already on the component
+    show_diagnostics: bool,
+    /// lists all events of the finalized extrinsic in the outcome
+    show_events: bool,
+    browser_support: BrowserSupport,
+    page_visible: bool,
+    /// round trip time of the last `chain_getHeader` call, `None` until measured
+    rpc_latency: Option<Result<Duration, String>>,
+    /// a latency measurement is running or scheduled. Pings pause while the page is hidden.
+    ping_pending: bool,
+    /// a new client is being created after a failed ping or keep-alive
+    reconnecting: bool,
+    /// aborts the scheduled keep-alive, `None` outside of interactive stages
+    keep_alive_handle: Option<AbortHandle>,
+    /// `visibilitychange` listener, removed on destroy
+    visibility_listener: Option<Closure<dyn Fn()>>,
+    /// vote through a multisig the signer is part of
+    multisig_enabled: bool,
+    /// raw input of the other signatories' addresses
+    multisig_signatories: String,
+    /// raw input of the multisig threshold
+    multisig_threshold: String,
+    /// multisig and approval step of the vote being signed
+    multisig_step: Option<(MultisigParams, MultisigStep)>,
+    /// signs the vote, the browser extension unless another signer is chosen
+    signer: Rc<dyn Signer>,
+    /// submit the extrinsic as soon as it's signed, persisted in `localStorage`. Off by default.
+    auto_submit: bool,
+    /// show the outcome once the extrinsic is in a block instead of waiting for finalization,
+    /// persisted in `localStorage`. Off by default.
+    wait_for_in_block: bool,
+    /// extension the last signing account came from, listed first when selecting an account
+    last_source: Option<String>,
+    /// accounts signed with recently, most recent first, persisted in `localStorage`
+    recent_accounts: Vec<RecentAccount>,
+    /// only list accounts of this extension, `None` lists all
+    source_filter: Option<String>,
+    /// the account list is being reloaded from the extensions
+    refreshing_accounts: bool,
+    /// why the last refresh of the account list failed
+    refresh_accounts_error: Option<String>,
+    /// weight of the reviewed remark and vote batch, to warn before it approaches the limit
+    batch_weight: Option<WeightEstimate>,
+    /// share of the max extrinsic weight from which the review warns, set with `?weightwarning=`
+    weight_warning_percent: u8,
+    /// metadata support of the reviewed account's extension
+    extension_metadata: Option<ExtensionMetadata>,
+    /// `Some(true)` once the extension stored the metadata, `Some(false)` if the user declined
+    metadata_provided: Option<Result<bool, String>>,
+    providing_metadata: bool,
+    /// indexes in `SelectAccount` of the accounts checked to vote one after another
+    selected_accounts: Vec<usize>,
+    multi_vote: Option<MultiVote>,
+    /// nonce the reviewed account will sign with, to preview the signing payload
+    review_nonce: Option<Result<u64, String>>,
+    /// installed and authorized extensions, checked when no accounts were returned
+    extension_status: Option<Result<ExtensionStatus, String>>,
+    /// offline mode, enabled with `?offline=1`: the chain values are entered manually instead of
+    /// read from an online client, and the signed extrinsic is only shown, not submitted.
+    offline: Option<OfflineInput>,
 }
 
 impl VoteComponent {
-    /// # Panics
-    /// panics if self.online_client is None.
     fn set_message(&mut self, message: String) {
-        let remark_call = node_runtime::tx()
-            .system()
-            .remark(message.as_bytes().to_vec());
-        let online_client = self.online_client.as_ref().unwrap();
-        let remark_call_bytes = remark_call
-            .encode_call_data(&online_client.metadata())
-            .unwrap();
-        self.remark_call_bytes = remark_call_bytes;
+        let remark = message.as_bytes().to_vec();
+        self.remark_call_bytes = match (&self.online_client, self.remark_with_event) {
+            (Some(online_client), false) => node_runtime::tx()
+                .system()
+                .remark(remark)
+                .encode_call_data(&online_client.metadata())
+                .unwrap(),
+            (Some(online_client), true) => node_runtime::tx()
+                .system()
+                .remark_with_event(remark)
+                .encode_call_data(&online_client.metadata())
+                .unwrap(),
+            (None, _) => Self::remark_runtime_call(remark, self.remark_with_event).encode(),
+        };
         self.message = message;
     }
 
-    fn set_vote(&mut self, balance: u128, conviction: Conviction) {
-        let vote_call = node_runtime::tx().conviction_voting().vote(
-            275,
-            AccountVote::Standard {
-                vote: Vote(conviction.to_value()),
-                balance: balance * 1000000000000,
+    /// the message a new vote starts with, see `preset_remark`
+    fn initial_message(&self) -> String {
+        match self.preset_remark {
+            true => remark_tag(self.referendum),
+            false => "".to_string(),
+        }
+    }
+
+    fn remark_runtime_call(remark: Vec<u8>, with_event: bool) -> RuntimeCall {
+        RuntimeCall::System(match with_event {
+            false => SystemCall::remark { remark },
+            true => SystemCall::remark_with_event { remark },
+        })
+    }
+
+    /// balance the vote is pre-filled with, one unit of the token, e.g. 1 KSM
+    fn default_balance(&self) -> Balance {
+        Balance::from_planck(planck_per_unit(self.chain_info.decimals))
+    }
+
+    fn set_vote(&mut self, balance: Balance, conviction: Conviction) {
+        self.balance = balance;
+        self.conviction = conviction.capped(&self.max_conviction);
+        self.vote_call_bytes = match &self.online_client {
+            Some(online_client) => {
+                let vote_call = node_runtime::tx().conviction_voting().vote(
+                    self.referendum,
+                    AccountVote::Standard {
+                        vote: Vote(self.conviction.to_value()),
+                        balance: self.balance.as_planck(),
+                    },
+                );
+                vote_call
+                    .encode_call_data(&online_client.metadata())
+                    .unwrap()
+            }
+            // offline, the call is encoded with the types generated from the bundled metadata
+            None => self.vote_runtime_call().encode(),
+        };
+    }
+
+    /// name and id of a track, e.g. "root (track 0)"
+    fn track_label(&self, track: u16) -> String {
+        match self.tracks.iter().find(|t| t.id == track) {
+            Some(t) => format!("{} (track {})", t.name, t.id),
+            None => format!("track {track}"),
+        }
+    }
+
+    fn switch_referendum(&mut self, ctx: &Context<Self>, referendum: u32) {
+        // keep the tag in sync, unless it was edited
+        if self.message == remark_tag(self.referendum) {
+            self.set_message(remark_tag(referendum));
+        }
+        self.referendum = referendum;
+        self.fetch_tally(ctx);
+        self.fetch_referendum_count(ctx);
+        // anything signed so far was for the previous referendum
+        if self.online_client.is_some() || self.offline.is_some() {
+            self.flow.stage = SigningStage::EnterBalance;
+            self.set_vote(self.balance, self.conviction.clone());
+        }
+    }
+
+    fn fetch_chain_info(&mut self, ctx: &Context<Self>) {
+        let Some(api) = self.online_client.clone() else {
+            return;
+        };
+        self.spawn(ctx, async move {
+            match ChainInfo::fetch(&api).await {
+                Ok(chain_info) => Message::ReceivedChainInfo(chain_info),
+                Err(err) => Message::ChainInfoFailed(err.into()),
+            }
+        });
+    }
+
+    fn fetch_tally(&mut self, ctx: &Context<Self>) {
+        self.tally = None;
+        let Some(api) = self.online_client.clone() else {
+            return;
+        };
+        let referendum = self.referendum;
+        self.spawn_batch(ctx, async move {
+            match fetch_tally(&api, referendum).await {
+                Ok(Some(tally)) => vec![Message::ReceivedTally(referendum, tally)],
+                // no projection without a tally
+                Ok(None) | Err(_) => vec![],
+            }
+        });
+    }
+
+    fn fetch_referendum_count(&mut self, ctx: &Context<Self>) {
+        let Some(api) = self.online_client.clone() else {
+            return;
+        };
+        self.spawn_batch(ctx, async move {
+            match fetch_referendum_count(&api).await {
+                Ok(count) => vec![Message::ReceivedReferendumCount(count)],
+                // voting on a missing referendum fails on-chain with a clear error anyway
+                Err(_) => vec![],
+            }
+        });
+    }
+
+    /// lock of the vote being cast, if the referendum ended at `block`
+    fn pending_lock(&self, block: u32, vote_locking_period: u32) -> LockEntry {
+        LockEntry::pending(
+            self.balance,
+            self.conviction.lock_periods(),
+            block,
+            vote_locking_period,
+        )
+    }
+
+    /// hands the signed extrinsic off to be submitted from a connected device, for extrinsics
+    /// signed offline or on a device set to only sign
+    fn handoff_html(&self, signed_extrinsic_hex: &AttrValue) -> Html {
+        let download_url = format!("data:text/plain,{signed_extrinsic_hex}");
+        let file_name = format!("ref-{}-signed-extrinsic.txt", self.referendum);
+        let connected = if self.offline.is_some() {
+            "This device isn't connected to the network and submitted nothing."
+        } else {
+            "This device is set to only sign (signeronly=1) and submitted nothing."
+        };
+        html!(
+            <div class="handoff">
+                <h6>{"1. Signed on this device ✓"}</h6>
+                <div class="mb">{connected}</div>
+                <h6>{"2. Submit from a connected device"}</h6>
+                <HexDisplay label="Signed extrinsic: " hint={SIGNED_EXTRINSIC_HINT} value={signed_extrinsic_hex.clone()}/>
+                <div class="mb">
+                    <a class="button" href={download_url} download={file_name}>{"Download as a file"}</a>
+                </div>
+                <ol class="mb">
+                    <li>{"Copy the signed extrinsic above, or download it, and move it to a device connected to the network."}</li>
+                    <li>{"There, open polkadot.js apps, go to Developer > RPC calls, choose author.submitExtrinsic, paste the extrinsic and submit it."}</li>
+                    <li>
+                        {"Check the vote was recorded on the "}
+                        <Link<Route> to={Route::Lookup { id: self.referendum }}>{"vote lookup page"}</Link<Route>>
+                        {"."}
+                    </li>
+                </ol>
+                <div class="mb">
+                    <small>{"It's only valid while the nonce it was signed with is unused and the runtime's transaction version doesn't change."}</small>
+                </div>
+            </div>
+        )
+    }
+
+    /// the referendum hasn't been created (yet), so a vote on it would fail
+    fn referendum_missing(&self) -> bool {
+        matches!(self.referendum_count, Some(count) if self.referendum >= count)
+    }
+
+    fn explorer(&self) -> Explorer {
+        Explorer {
+            chain: self.chain_info.explorer,
+            custom_base_url: self.custom_explorer_url.clone(),
+        }
+    }
+
+    /// like `send_future`, but the future is aborted when the component is destroyed, so it
+    /// doesn't call back into a dead component
+    fn spawn(&mut self, ctx: &Context<Self>, future: impl Future<Output = Message> + 'static) {
+        self.spawn_batch(ctx, future.map(|message| vec![message]));
+    }
+
+    /// like `send_future_batch`, aborted when the component is destroyed
+    fn spawn_batch(
+        &mut self,
+        ctx: &Context<Self>,
+        future: impl Future<Output = Vec<Message>> + 'static,
+    ) {
+        let (abort_handle, abort_registration) = AbortHandle::new_pair();
+        let running = Rc::new(());
+        self.tasks.retain(|(_, running)| running.strong_count() > 0);
+        self.tasks.push((abort_handle, Rc::downgrade(&running)));
+        ctx.link().send_future_batch(async move {
+            let _running = running;
+            Abortable::new(future, abort_registration)
+                .await
+                .unwrap_or_default()
+        });
+    }
+
+    /// sets up the component with a new client and moves to entering the message. `false`, with
+    /// `Message::Error` sent, if the client can't be used.
+    fn set_online_client(
+        &mut self,
+        ctx: &Context<Self>,
+        online_client: OnlineClient<PolkadotConfig>,
+        endpoint: String,
+    ) -> bool {
+        if let Err(err) = check_kusama_genesis(online_client.genesis_hash()) {
+            ctx.link().send_message(Message::Error(err));
+            return false;
+        }
+        self.rpc_endpoint = endpoint;
+        match ChainParams::from_client(&online_client) {
+            Ok(chain_params) => self.chain_params = Some(chain_params),
+            Err(err) => {
+                ctx.link().send_message(Message::Error(err.into()));
+                return false;
+            }
+        }
+        match read_tracks(&online_client) {
+            Ok(tracks) => self.tracks = tracks,
+            Err(err) => {
+                ctx.link().send_message(Message::Error(err.into()));
+                return false;
+            }
+        }
+        self.metadata_mismatch = check_vote_call_index(&online_client.metadata())
+            .err()
+            .map(|err| err.to_string());
+        self.online_client = Some(online_client);
+        self.fetch_chain_info(ctx);
+        self.fetch_tally(ctx);
+        self.fetch_referendum_count(ctx);
+        ctx.link().send_message(Message::PingRpc);
+        self.flow.stage = SigningStage::EnterMessage;
+        self.set_message(self.initial_message());
+        self.set_vote(self.default_balance(), Conviction::Lock1X);
+        true
+    }
+
+    /// replaces a dropped connection before it's needed to sign or submit. An injected client is
+    /// never replaced, its error is shown instead.
+    fn reconnect(&mut self, ctx: &Context<Self>) {
+        if self.reconnecting || Self::injected_client(ctx) {
+            return;
+        }
+        self.reconnecting = true;
+        self.spawn(ctx, async {
+            match connect_with_retry(KUSAMA_RPC_ENDPOINTS, CONNECT_ATTEMPTS).await {
+                Ok((api, endpoint)) => Message::Reconnected(api, endpoint),
+                Err(err) => Message::ReconnectFailed(err.to_string()),
+            }
+        });
+    }
+
+    /// schedules the keep-alive while the stage waits on the user and the connection is up,
+    /// and cancels it otherwise, e.g. on reset or while reconnecting
+    fn update_keep_alive(&mut self, ctx: &Context<Self>) {
+        let api = match &self.online_client {
+            Some(api) if self.flow.stage.is_interactive() && !self.reconnecting => api.clone(),
+            _ => return self.cancel_keep_alive(),
+        };
+        if self.keep_alive_handle.is_some() {
+            return;
+        }
+        let (abort_handle, abort_registration) = AbortHandle::new_pair();
+        self.keep_alive_handle = Some(abort_handle);
+        let keep_alive = async move {
+            sleep(KEEP_ALIVE_INTERVAL).await;
+            // paused while the page is hidden, the visibility check reconnects when it's shown
+            let result = match is_page_visible() {
+                true => api.rpc().header(None).await.map(|_| ()),
+                false => Ok(()),
+            };
+            vec![Message::KeptAlive(result.map_err(|err| err.to_string()))]
+        };
+        self.spawn_batch(
+            ctx,
+            Abortable::new(keep_alive, abort_registration).map(Result::unwrap_or_default),
+        );
+    }
+
+    fn cancel_keep_alive(&mut self) {
+        if let Some(handle) = self.keep_alive_handle.take() {
+            handle.abort();
+        }
+    }
+
+    /// whether the client was passed in by the host page, which is never replaced by a new
+    /// connection
+    fn injected_client(ctx: &Context<Self>) -> bool {
+        ctx.props().client.is_some()
+    }
+
+    fn connect(&mut self, ctx: &Context<Self>) {
+        self.spawn(
+            ctx,
+            connect_with_retry(KUSAMA_RPC_ENDPOINTS, CONNECT_ATTEMPTS).map(|res| match res {
+                Ok((online_client, endpoint)) => {
+                    Message::OnlineClientCreated(online_client, endpoint)
+                }
+                Err(err) => Message::Error(anyhow!("Online Client could not be created:\n{err}")),
+            }),
+        );
+    }
+
+    /// the signer's vote already recorded on this referendum, which voting again replaces. Not
+    /// known for multisig votes, which are cast by the multisig account rather than the signer.
+    fn existing_vote(&self) -> Option<CastVote> {
+        if self.multisig_enabled {
+            return None;
+        }
+        let Some(Ok(votes)) = &self.account_votes else {
+            return None;
+        };
+        votes
+            .iter()
+            .find(|track_vote| track_vote.referendum == self.referendum)
+            .map(|track_vote| track_vote.vote.clone())
+    }
+
+    /// short name of the current stage, including the submitting stage once signed
+    fn stage_label(&self) -> &'static str {
+        self.flow.stage.label()
+    }
+
+    /// progress of the current stage, announced to screen readers
+    fn status_announcement(&self) -> String {
+        match &self.flow.stage {
+            SigningStage::Error(_) => "An error occurred".to_string(),
+            SigningStage::CreatingOnlineClient => {
+                format!("Connecting to {}", self.chain_info.name)
+            }
+            SigningStage::EnterMessage => "Connected. Enter an optional message".to_string(),
+            SigningStage::EnterBalance => "Enter the balance to vote with".to_string(),
+            SigningStage::RequestingAccounts => "Querying extensions for accounts".to_string(),
+            SigningStage::SelectAccount(accounts) => {
+                format!("{} accounts found. Select an account", accounts.len())
+            }
+            SigningStage::ReviewVote(_) => "Review the vote before signing".to_string(),
+            SigningStage::Signing(_) => "Waiting for the extension to sign".to_string(),
+            SigningStage::SigningDeclined { .. } => {
+                "The extension didn't sign the vote".to_string()
+            }
+            SigningStage::DelegationBlocked { .. } => {
+                "The account delegates its votes on this track".to_string()
+            }
+            SigningStage::OfflineSigned { .. } => "Extrinsic signed offline".to_string(),
+            SigningStage::SigningSuccess {
+                submitting_stage, ..
+            } => match submitting_stage {
+                SubmittingStage::Initial { .. } => "Extrinsic signed, ready to submit".to_string(),
+                SubmittingStage::Submitting { .. } => "Submitting the extrinsic".to_string(),
+                SubmittingStage::Submitted { .. } => "Extrinsic submitted".to_string(),
+                SubmittingStage::Finalized(outcome)
+                    if outcome.is_success() && outcome.finalized =>
+                {
+                    "Vote finalized".to_string()
+                }
+                SubmittingStage::Finalized(outcome) if outcome.is_success() => {
+                    "Vote included in a block".to_string()
+                }
+                SubmittingStage::Finalized(_) => "The extrinsic failed".to_string(),
+                SubmittingStage::Cancelled => "Submission cancelled".to_string(),
+                SubmittingStage::Error(_) => "Submission failed".to_string(),
             },
+        }
+    }
+
+    /// stages waiting on the network or the browser extension
+    fn is_async_stage(&self) -> bool {
+        matches!(
+            self.stage_label(),
+            "creating_online_client" | "requesting_accounts" | "signing" | "submitting"
+        )
+    }
+
+    /// restarts the watchdog for the current stage
+    fn arm_watchdog(&mut self, ctx: &Context<Self>) {
+        self.stage_epoch = self.stage_epoch.wrapping_add(1);
+        self.stage_stuck = false;
+        if self.is_async_stage() {
+            let epoch = self.stage_epoch;
+            self.spawn(ctx, async move {
+                sleep(STAGE_TIMEOUT).await;
+                Message::StageTimeout(epoch)
+            });
+        }
+    }
+
+    /// shows the review of the vote with `account`, loading its fee, free balance and nonce
+    fn review_with(&mut self, ctx: &Context<Self>, account: Account) {
+        let account_id = match parse_account_id(&account.address) {
+            Ok(account_id) => account_id,
+            Err(err) => return ctx.link().send_message(Message::Error(err)),
+        };
+        self.extension_metadata = None;
+        self.metadata_provided = None;
+        if let (Some(chain_params), None) = (&self.chain_params, &self.offline) {
+            let source = account.source.clone();
+            let genesis_hash = chain_params.genesis_hash;
+            // extensions without metadata support keep showing the raw call, nothing to offer
+            self.spawn_batch(ctx, async move {
+                match get_extension_metadata(source, genesis_hash).await {
+                    Ok(metadata) => vec![Message::ReceivedExtensionMetadata(metadata)],
+                    Err(_) => vec![],
+                }
+            });
+        }
+        self.fee_estimate = None;
+        self.free_balance = None;
+        // same nonce as `SignWithAccount` uses, unless a transaction lands meanwhile
+        self.review_nonce = match (&self.offline, self.flow.next_nonces.get(&account.address)) {
+            (Some(offline), _) => Some(
+                offline
+                    .parse()
+                    .map(|(_, nonce)| nonce)
+                    .map_err(|err| err.to_string()),
+            ),
+            (None, Some(nonce)) => Some(Ok(*nonce)),
+            (None, None) => None,
+        };
+        if let (Some(api), None) = (self.online_client.clone(), &self.review_nonce) {
+            let account_id = account_id.clone();
+            self.spawn(ctx, async move {
+                match api.tx().account_nonce(&account_id).await {
+                    Ok(nonce) => Message::ReceivedReviewNonce(nonce),
+                    Err(err) => Message::ReviewNonceFailed(err.into()),
+                }
+            });
+        }
+        if let Some(api) = self.online_client.clone() {
+            let account_id = account_id.clone();
+            self.spawn(ctx, async move {
+                match fetch_free_balance(&api, &account_id).await {
+                    Ok(free) => Message::ReceivedFreeBalance(free),
+                    Err(err) => Message::FreeBalanceFailed(err.into()),
+                }
+            });
+        }
+        // a percentage is estimated once it's resolved against the free balance
+        if self.balance_resolved() {
+            self.estimate_vote(ctx, &account);
+        } else {
+            self.batch_weight = None;
+            self.lock_impact = None;
+        }
+        self.account_votes = None;
+        self.lock_schedule = None;
+        // the locks and delegations of a multisig vote are the multisig account's
+        self.delegation_checked = self.offline.is_some() || self.multisig_enabled;
+        if let (Some(api), None) = (self.online_client.clone(), &self.offline) {
+            let votes_api = api.clone();
+            let votes_account_id = account_id.clone();
+            let address = account.address.clone();
+            let tracks = self.tracks.clone();
+            self.spawn(ctx, async move {
+                match fetch_account_votes(&votes_api, &votes_account_id, &tracks).await {
+                    Ok(votes) => Message::ReceivedAccountVotes(address, votes),
+                    Err(err) => Message::AccountVotesFailed(address, err.into()),
+                }
+            });
+            if !self.multisig_enabled {
+                let schedule_api = api.clone();
+                let schedule_account_id = account_id.clone();
+                let address = account.address.clone();
+                let tracks = self.tracks.clone();
+                self.spawn_batch(ctx, async move {
+                    match fetch_lock_schedule(&schedule_api, &schedule_account_id, &tracks).await {
+                        Ok(schedule) => vec![Message::ReceivedLockSchedule(address, schedule)],
+                        Err(_) => vec![],
+                    }
+                });
+
+                let address = account.address.clone();
+                let referendum = self.referendum;
+                self.spawn(ctx, async move {
+                    match fetch_delegation_for_referendum(&api, &account_id, referendum).await {
+                        Ok(delegation) => Message::DelegationChecked(address, delegation),
+                        Err(err) => {
+                            // not being able to check should not prevent voting
+                            web_sys::console::log_1(
+                                &format!("Delegation check failed: {err}").into(),
+                            );
+                            Message::DelegationChecked(address, None)
+                        }
+                    }
+                });
+            }
+        }
+        self.flow.stage = SigningStage::ReviewVote(account);
+    }
+
+    /// a balance entered as a percentage is resolved to an amount once the free balance of the
+    /// reviewed account is known
+    fn balance_resolved(&self) -> bool {
+        self.balance_percentage.is_none() || matches!(self.free_balance, Some(Ok(_)))
+    }
+
+    /// the balance of the vote, or the percentage it will be resolved from
+    fn balance_label(&self) -> String {
+        match self.balance_percentage {
+            Some(percent) if !self.balance_resolved() => format!("{percent}% of the free balance"),
+            _ => self.chain_info.format_balance(self.balance),
+        }
+    }
+
+    /// estimates the fee, batch weight and lock impact of voting `self.balance` with `account`,
+    /// again whenever the balance changes during the review
+    fn estimate_vote(&mut self, ctx: &Context<Self>, account: &Account) {
+        self.fee_estimate = None;
+        self.batch_weight = None;
+        self.lock_impact = None;
+        // the call of a multisig vote is only known once its step is looked up, and its locks
+        // are the multisig account's
+        let (Some(api), false) = (self.online_client.clone(), self.multisig_enabled) else {
+            return;
+        };
+        // checked when the review started
+        let Ok(account_id) = parse_account_id(&account.address) else {
+            return;
+        };
+
+        let call_data = self.call_bytes();
+        let fee_api = api.clone();
+        let fee_account_id = account_id.clone();
+        self.spawn(ctx, async move {
+            match estimate_fee(&fee_api, &call_data, fee_account_id).await {
+                Ok(fee) => Message::ReceivedFeeEstimate(Balance::from_planck(fee)),
+                Err(err) => Message::FeeEstimateFailed(err.into()),
+            }
+        });
+
+        // a single vote is far below the limit, only a batch with a long remark can approach it
+        if self.batch_calls().is_some() {
+            let call_data = self.call_bytes();
+            let weight_api = api.clone();
+            let weight_account_id = account_id.clone();
+            // without an estimate the review simply shows no warning
+            self.spawn_batch(ctx, async move {
+                match estimate_weight(&weight_api, &call_data, weight_account_id).await {
+                    Ok(weight) => vec![Message::ReceivedBatchWeight(weight)],
+                    Err(_) => vec![],
+                }
+            });
+        }
+
+        if self.offline.is_none() {
+            let address = account.address.clone();
+            let balance = self.balance;
+            let referendum = self.referendum;
+            self.spawn(ctx, async move {
+                match fetch_lock_impact(&api, &account_id, referendum, balance).await {
+                    Ok(lock_impact) => Message::ReceivedLockImpact(address, lock_impact),
+                    Err(err) => Message::LockImpactFailed(address, err.into()),
+                }
+            });
+        }
+    }
+
+    /// the account being reviewed or signed with
+    fn stage_account(&self) -> Option<&Account> {
+        match &self.flow.stage {
+            SigningStage::ReviewVote(account)
+            | SigningStage::Signing(account)
+            | SigningStage::SigningDeclined { account, .. }
+            | SigningStage::DelegationBlocked { account, .. }
+            | SigningStage::OfflineSigned {
+                signer_account: account,
+                ..
+            }
+            | SigningStage::SigningSuccess {
+                signer_account: account,
+                ..
+            } => Some(account),
+            _ => None,
+        }
+    }
+
+    /// records the result of the current account of a multi-account vote and moves on to the
+    /// next one. Returns `false` if no multi-account vote is running.
+    fn finish_queued_account(
+        &mut self,
+        ctx: &Context<Self>,
+        result: Result<String, String>,
+    ) -> bool {
+        let Some(multi_vote) = &mut self.multi_vote else {
+            return false;
+        };
+        if let Some(account) = multi_vote.current.take() {
+            multi_vote.results.push((account, result));
+            ctx.link().send_message(Message::NextQueuedAccount);
+        }
+        true
+    }
+
+    /// what `reduce` reads from the rest of the page
+    fn flow_inputs(&self) -> FlowInputs {
+        FlowInputs {
+            call_data: self.call_bytes(),
+            review_ready: self.balance_resolved() && self.delegation_checked,
+            connected: self.online_client.is_some() || self.offline.is_some(),
+            auto_submit: self.auto_submit,
+            signer_only: self.signer_only,
+            multi_vote: self.multi_vote.is_some(),
+            existing_vote: self.existing_vote(),
+        }
+    }
+
+    /// transitions the signing flow with `msg` and runs the resulting effects
+    fn update_flow(&mut self, ctx: &Context<Self>, msg: FlowMessage) {
+        let inputs = self.flow_inputs();
+        let (flow, effects) = reduce(std::mem::take(&mut self.flow), msg, &inputs);
+        self.flow = flow;
+        for effect in effects {
+            self.run_effect(ctx, effect);
+        }
+    }
+
+    fn run_effect(&mut self, ctx: &Context<Self>, effect: Effect) {
+        match effect {
+            Effect::AbortTasks => {
+                if let Some(handle) = self.submission_handle.take() {
+                    handle.abort();
+                }
+                self.keep_alive_handle = None;
+                for (handle, _) in self.tasks.drain(..) {
+                    handle.abort();
+                }
+            }
+            Effect::ResetForm => {
+                self.balance_error = None;
+                self.balance_percentage = None;
+                self.free_balance = None;
+                self.account_votes = None;
+                self.fee_estimate = None;
+                self.lock_impact = None;
+                self.lock_schedule = None;
+                self.multisig_enabled = false;
+                self.multisig_signatories = "".to_string();
+                self.multisig_threshold = "2".to_string();
+                self.multisig_step = None;
+                self.source_filter = None;
+                self.selected_accounts.clear();
+                self.multi_vote = None;
+                self.refreshing_accounts = false;
+                self.refresh_accounts_error = None;
+                self.providing_metadata = false;
+                self.remark_with_event = false;
+                self.set_message(self.initial_message());
+                self.set_vote(self.default_balance(), Conviction::Lock1X);
+            }
+            Effect::Connect => self.connect(ctx),
+            Effect::RestartBackgroundTasks => self.restart_background_tasks(ctx),
+            Effect::RememberAccount(account) => {
+                local_storage_set(LAST_SOURCE_KEY, &account.source);
+                self.last_source = Some(account.source.clone());
+                self.recent_accounts =
+                    remember_account(&self.recent_accounts, &account, js_sys::Date::now());
+                if let Ok(json) = serde_json::to_string(&self.recent_accounts) {
+                    local_storage_set(RECENT_ACCOUNTS_KEY, &json);
+                }
+            }
+            Effect::Sign {
+                account,
+                epoch,
+                nonce,
+            } => self.request_signature(ctx, account, epoch, nonce),
+            Effect::Notify(event) => self.notify(event),
+            Effect::Validate(signed_extrinsic) => {
+                if let Some(api) = self.online_client.clone() {
+                    self.spawn(ctx, async move {
+                        let result = validate_extrinsic(&api, &signed_extrinsic).await;
+                        Message::ValidationResult(match result {
+                            Ok(result) => describe_validation(&result),
+                            Err(err) => Err(format!("validation failed: {err}")),
+                        })
+                    });
+                }
+            }
+            Effect::Submit { extrinsic, watch } => self.submit(ctx, extrinsic, watch),
+            Effect::Review(account) => self.review_with(ctx, account),
+            Effect::FinishQueued(result) => {
+                self.finish_queued_account(ctx, result);
+            }
+            Effect::Error(err) => ctx.link().send_message(Message::Error(anyhow!(err))),
+        }
+    }
+
+    /// asks the signer to sign the vote with `account`, offline or with the online client. The
+    /// result arrives as `Message::SigningFinished` tagged with `epoch`.
+    fn request_signature(
+        &mut self,
+        ctx: &Context<Self>,
+        account: Account,
+        epoch: u32,
+        nonce: Option<u64>,
+    ) {
+        let account_id = match parse_account_id(&account.address) {
+            Ok(account_id) => account_id,
+            Err(err) => return ctx.link().send_message(Message::Error(err)),
+        };
+        let account_address = account.address;
+        let account_source = account.source;
+        let signer = self.signer.clone();
+
+        if let Some(offline) = self.offline.as_ref().map(OfflineInput::parse) {
+            match offline {
+                Ok((chain_params, nonce)) => {
+                    let call_data = self.call_bytes();
+                    self.spawn(
+                        ctx,
+                        sign_offline(
+                            call_data,
+                            chain_params,
+                            nonce,
+                            account_id,
+                            account_source,
+                            account_address,
+                            signer,
+                        )
+                        .map(move |result| Message::SigningFinished(epoch, result)),
+                    );
+                }
+                Err(err) => ctx.link().send_message(Message::Error(err)),
+            }
+        } else {
+            let vote_call = node_runtime::tx().conviction_voting().vote(
+                self.referendum,
+                AccountVote::Standard {
+                    vote: Vote(self.conviction.to_value()),
+                    balance: self.balance.as_planck(),
+                },
+            );
+
+            let batch_call = self
+                .batch_calls()
+                .map(|calls| node_runtime::tx().utility().batch_all(calls));
+
+            let api = self.online_client.as_ref().unwrap().clone();
+            let chain_params = self.chain_params.clone().unwrap();
+
+            self.multisig_step = None;
+            if self.multisig_enabled {
+                match MultisigParams::new(
+                    &account_id,
+                    &self.multisig_signatories,
+                    &self.multisig_threshold,
+                ) {
+                    Ok(params) => {
+                        let call_hash = call_hash(&self.call_bytes());
+                        self.spawn(ctx, async move {
+                            match fetch_multisig_step(&api, &params, call_hash, &account_id).await {
+                                Ok(step) => Message::MultisigPrepared(epoch, params, step),
+                                Err(err) => Message::SigningFinished(
+                                    epoch,
+                                    SigningResult::Failed(err.to_string()),
+                                ),
+                            }
+                        });
+                    }
+                    Err(err) => ctx.link().send_message(Message::Error(err)),
+                }
+            } else {
+                self.spawn(ctx, async move {
+                    let result = match batch_call {
+                        Some(batch_call) => {
+                            sign_call(
+                                api,
+                                chain_params,
+                                batch_call,
+                                account_id,
+                                account_source,
+                                account_address,
+                                nonce,
+                                signer,
+                            )
+                            .await
+                        }
+                        None => {
+                            sign_call(
+                                api,
+                                chain_params,
+                                vote_call,
+                                account_id,
+                                account_source,
+                                account_address,
+                                nonce,
+                                signer,
+                            )
+                            .await
+                        }
+                    };
+                    Message::SigningFinished(epoch, result)
+                });
+            }
+        }
+    }
+
+    /// submits the encoded signed extrinsic, watching it unless `watch` is false
+    fn submit(&mut self, ctx: &Context<Self>, extrinsic: Vec<u8>, watch: bool) {
+        // only extrinsics signed with an online client reach this stage
+        let Some(api) = self.online_client.clone() else {
+            return ctx
+                .link()
+                .send_message(Message::ExtrinsicFailed(anyhow!("not connected to a node")));
+        };
+        let signed_extrinsic = SubmittableExtrinsic::from_bytes(api.clone(), extrinsic);
+
+        // a relay is always waited for, it reports the block the extrinsic is in
+        if !watch && self.submission_backend == SubmissionBackend::Direct {
+            self.spawn(ctx, async move {
+                match submit_and_get_hash(signed_extrinsic).await {
+                    Ok(hash) => Message::ExtrinsicSubmitted(hash),
+                    Err(err) => Message::ExtrinsicFailed(err.into()),
+                }
+            });
+            return;
+        }
+
+        let (abort_handle, abort_registration) = AbortHandle::new_pair();
+        self.submission_handle = Some(abort_handle);
+
+        let submission = Abortable::new(
+            submit_through(
+                self.submission_backend.clone(),
+                api,
+                signed_extrinsic,
+                self.wait_for_in_block,
+            ),
+            abort_registration,
         );
-        let online_client = self.online_client.as_ref().unwrap();
-        let vote_call_bytes = vote_call
-            .encode_call_data(&online_client.metadata())
-            .unwrap();
-        self.vote_call_bytes = vote_call_bytes;
-        self.balance = balance;
-        self.conviction = conviction;
+        self.spawn(ctx, async move {
+            match submission.await {
+                Ok(Ok(outcome)) => Message::ExtrinsicFinalized(outcome),
+                Ok(Err(err)) => Message::ExtrinsicFailed(err),
+                // the watch was aborted, `CancelSubmission` already updated the stage
+                Err(_aborted) => Message::CancelSubmission,
+            }
+        });
+    }
+
+    /// refreshes the ages shown in the feed every `FEED_CLOCK_INTERVAL`, unless already running
+    fn start_feed_clock(&mut self, ctx: &Context<Self>) {
+        if !self.feed_clock_running {
+            self.feed_clock_running = true;
+            ctx.link().send_message(Message::FeedClockTick);
+        }
+    }
+
+    /// restarts the chain queries, polling and the feed after `Reset` aborted all tasks
+    fn restart_background_tasks(&mut self, ctx: &Context<Self>) {
+        self.ping_pending = false;
+        self.reconnecting = false;
+        self.feed_clock_running = false;
+        self.fetch_chain_info(ctx);
+        self.fetch_tally(ctx);
+        self.fetch_referendum_count(ctx);
+        ctx.link().send_message(Message::PingRpc);
+        self.start_feed_clock(ctx);
+        if self.feed_handle.is_some() {
+            ctx.link().send_message(Message::SubscribeFinalizedBlock);
+        }
+        self.arm_watchdog(ctx);
+    }
+
+    /// restarts the watchdog of the finalized blocks feed
+    fn arm_feed_watchdog(&mut self, ctx: &Context<Self>) {
+        self.feed_epoch = self.feed_epoch.wrapping_add(1);
+        let epoch = self.feed_epoch;
+        self.spawn(ctx, async move {
+            sleep(FEED_STALL_TIMEOUT).await;
+            Message::FeedStalled(epoch)
+        });
+    }
+
+    /// replaces the connection of a stalled or failed feed. `Reconnected` restarts the feed,
+    /// and with it the watchdog.
+    fn reconnect_feed(&mut self, ctx: &Context<Self>) {
+        if Self::injected_client(ctx) {
+            self.feed_marker = Some(
+                "The injected client is not responding, reconnect it from the host page".into(),
+            );
+            return;
+        }
+        self.spawn(ctx, async {
+            match connect_with_retry(KUSAMA_RPC_ENDPOINTS, CONNECT_ATTEMPTS).await {
+                Ok((api, endpoint)) => Message::Reconnected(api, endpoint),
+                Err(err) => Message::FeedReconnectFailed(err.to_string()),
+            }
+        });
+    }
+
+    fn notify(&self, event: StatusEvent) {
+        if self.post_status {
+            post_status(&event);
+        }
+    }
+
+    /// the remark and the vote batched together, if a message was entered
+    fn batch_calls(&self) -> Option<Vec<RuntimeCall>> {
+        if self.message.is_empty() {
+            return None;
+        }
+        let remark =
+            Self::remark_runtime_call(self.message.as_bytes().to_vec(), self.remark_with_event);
+        Some(vec![remark, self.vote_runtime_call()])
+    }
+
+    /// the call that gets signed: the vote, or the remark and vote batch
+    fn runtime_call(&self) -> RuntimeCall {
+        match self.batch_calls() {
+            Some(calls) => RuntimeCall::Utility(UtilityCall::batch_all { calls }),
+            None => self.vote_runtime_call(),
+        }
+    }
+
+    /// encoded `runtime_call`
+    fn call_bytes(&self) -> Vec<u8> {
+        match self.batch_calls() {
+            Some(_) => self.runtime_call().encode(),
+            None => self.vote_call_bytes.clone(),
+        }
+    }
+
+    /// checks the encoded vote call decodes to the vote chosen by the user
+    fn verify_vote(&self) -> Result<(), anyhow::Error> {
+        let intended = IntendedVote {
+            referendum: self.referendum,
+            aye: true,
+            balance: self.balance,
+            conviction: self.conviction.slider_position(),
+        };
+        verify_vote_call(&self.vote_call_bytes, &intended)
+    }
+
+    /// the vote as a runtime call, to be wrapped by other calls (e.g. multisig)
+    fn vote_runtime_call(&self) -> RuntimeCall {
+        RuntimeCall::ConvictionVoting(ConvictionVotingCall::vote {
+            poll_index: self.referendum,
+            vote: AccountVote::Standard {
+                vote: Vote(self.conviction.to_value()),
+                balance: self.balance.as_planck(),
+            },
+        })
     }
 
     fn is_selected(&self, conviction: Conviction) -> String {
@@ -69,56 +1255,159 @@ impl VoteComponent {
     }
 }
 
-pub enum SigningStage {
-    Error(String),
-    CreatingOnlineClient,
-    EnterMessage,
-    EnterBalance,
-    RequestingAccounts,
-    SelectAccount(Vec<Account>),
-    Signing(Account),
-    SigningSuccess {
-        signer_account: Account,
-        signature: MultiSignature,
-        signed_extrinsic_hex: String,
-        submitting_stage: SubmittingStage,
-    },
-}
-
-pub enum SubmittingStage {
-    Initial {
-        signed_extrinsic: SubmittableExtrinsic<PolkadotConfig, OnlineClient<PolkadotConfig>>,
-    },
-    Submitting,
-    Success {
-        remark_event: node_runtime::system::events::ExtrinsicSuccess,
-    },
-    Error(anyhow::Error),
+/// Votes from several accounts of the user, signed and submitted one after another with the
+/// usual review, sign and submit stages.
+#[derive(Clone, Debug)]
+struct MultiVote {
+    /// accounts still to vote with, in order
+    pending: Vec<Account>,
+    /// account going through the stages now
+    current: Option<Account>,
+    /// accounts done, with e.g. "finalized in block 0x..." or why they failed
+    results: Vec<(Account, Result<String, String>)>,
 }
 
+#[allow(clippy::enum_variant_names)]
 pub enum Message {
     Error(anyhow::Error),
-    OnlineClientCreated(OnlineClient<PolkadotConfig>),
+    /// String is the endpoint the client is connected to
+    OnlineClientCreated(OnlineClient<PolkadotConfig>, String),
+    ReceivedChainInfo(ChainInfo),
+    ChainInfoFailed(anyhow::Error),
+    /// u32 is the referendum the tally is for
+    ReceivedTally(u32, Tally),
+    ReceivedReferendumCount(u32),
     ChangeMessage(String),
+    ToggleRemarkWithEvent,
+    /// fills the message with the `remark_tag` of the referendum
+    UseRemarkTag,
+    /// continue with the entered message, batched with the vote
+    ConfirmMessage,
+    SkipMessage,
     ChangeBalance(String),
     ChangeConviction(Conviction),
+    ToggleMultisig,
+    ToggleAutoSubmit,
+    DismissPublicRpcNotice,
+    ToggleWaitForInBlock,
+    ChangeMultisigSignatories(String),
+    ChangeMultisigThreshold(String),
+    ChangeOfflineInput(OfflineField, String),
     RequestAccounts,
     ReceivedAccounts(Vec<Account>),
+    /// shows the review of the vote with the account at this index of `SelectAccount`
+    ReviewWithAccount(usize),
+    ReceivedFreeBalance(Balance),
+    FreeBalanceFailed(anyhow::Error),
+    ReceivedReviewNonce(u64),
+    ReviewNonceFailed(anyhow::Error),
+    /// lowers the vote so that the free balance also covers the estimated fee
+    ReduceBalanceForFee,
+    ReceivedFeeEstimate(Balance),
+    FeeEstimateFailed(anyhow::Error),
+    ReceivedBatchWeight(WeightEstimate),
+    /// signs with the reviewed account
+    SignWithAccount,
+    ReceivedExtensionMetadata(ExtensionMetadata),
+    /// asks the reviewed account's extension to store the chain metadata
+    ProvideMetadata,
+    MetadataProvided(Result<bool, String>),
     /// usize represents account index in Vec<Account>
-    SignWithAccount(usize),
-    ReceivedSignature(
-        MultiSignature,
-        SubmittableExtrinsic<PolkadotConfig, OnlineClient<PolkadotConfig>>,
-    ),
-    SubmitSigned,
-    ExtrinsicFinalized {
-        remark_event: node_runtime::system::events::ExtrinsicSuccess,
+    ToggleAccountSelection(usize),
+    /// votes with each of `selected_accounts`, one after another
+    VoteWithSelected,
+    /// reviews the vote with the next account of the multi-account vote
+    NextQueuedAccount,
+    SkipQueuedAccount,
+    /// `None` shows the accounts of all extensions
+    FilterSource(Option<String>),
+    RefreshAccounts,
+    RefreshedAccounts(Vec<Account>),
+    RefreshAccountsFailed(anyhow::Error),
+    /// sent when no accounts were returned
+    ReceivedExtensionStatus(ExtensionStatus),
+    ExtensionStatusFailed(anyhow::Error),
+    /// the delegation of the reviewed account on the referendum's track, if any. The `String`
+    /// of this and the following messages is the address the data was loaded for.
+    DelegationChecked(String, Option<Delegation>),
+    /// u32 is the signing epoch the lookup was started in, see `FlowState::signing_epoch`
+    MultisigPrepared(u32, MultisigParams, MultisigStep),
+    ReceivedAccountVotes(String, Vec<TrackVote>),
+    AccountVotesFailed(String, anyhow::Error),
+    ReceivedLockImpact(String, LockImpact),
+    LockImpactFailed(String, anyhow::Error),
+    ReceivedLockSchedule(String, LockSchedule),
+    /// u32 is the signing epoch the request was made in
+    SigningFinished(u32, SigningResult),
+    /// re-sends the declined signing request to the extension
+    RetrySigning,
+    /// signs again with the nonce of the declined request, or a fresh one if it was used meanwhile
+    ResumeSigning(Option<u64>),
+    /// dry-run of the signed extrinsic, see `VoteComponent::validation`
+    ValidationResult(Result<String, String>),
+    /// `watch: false` only waits for the node to accept the extrinsic
+    SubmitSigned {
+        watch: bool,
     },
+    CancelSubmission,
+    /// submits the last extrinsic again after a failure, unless it was included meanwhile
+    RetrySubmission,
+    ExtrinsicSubmitted(H256),
+    ExtrinsicFinalized(SubmissionOutcome),
     ExtrinsicFailed(anyhow::Error),
     SubscribeFinalizedBlock,
-    PushFinalizedBlock(AttrValue),
+    /// the subscription ended, or was aborted to be restarted
+    FeedStopped {
+        aborted: bool,
+    },
+    PushFinalizedBlock(FinalizedBlock),
+    ChangeFeedVerbosity(FeedVerbosity),
+    /// u32 is the feed epoch the watchdog was armed for
+    FeedStalled(u32),
+    /// the subscription failed, e.g. because the connection dropped
+    FeedFailed(String),
+    /// a stalled or dropped endpoint could not be replaced
+    FeedReconnectFailed(String),
+    /// a dropped connection could not be replaced, retried with the next ping
+    ReconnectFailed(String),
+    /// result of a keep-alive request, scheduled again while the stage is interactive
+    KeptAlive(Result<(), String>),
+    /// u32 is the stage epoch the watchdog was armed for
+    StageTimeout(u32),
+    RetryStage,
+    CancelStage,
+    ToggleAdvanced,
+    ToggleDiagnostics,
+    ToggleEvents,
+    /// copies the polkadot.js apps link of the signed extrinsic
+    CopyAppsLink(String),
+    AppsLinkCopied(bool),
+    /// refreshes the ages shown in the feed
+    FeedClockTick,
+    ChangeExplorerUrl(String),
+    SaveExplorerUrl,
+    /// back to Subscan
+    ClearExplorerUrl,
+    /// back to a clean `EnterBalance`, keeping the connection
+    Reset,
+    /// forgets the locally tracked nonces, so the next extrinsic uses the on-chain nonce
+    ResyncNonces,
+    VisibilityChanged(bool),
+    PingRpc,
+    RpcLatencyMeasured(Result<Duration, String>),
+    /// a new online client replaced a dropped connection, without changing the stage
+    Reconnected(OnlineClient<PolkadotConfig>, String),
+    /// the submitted extrinsic was not found in recent blocks (yet)
+    SubmissionNotFound,
+    #[cfg(feature = "debug")]
+    DebugDumpCall,
+    #[cfg(feature = "debug")]
+    DebugSetReferendum(u32),
+    #[cfg(feature = "debug")]
+    DebugForceStage(String),
 }
 
+const LOCKNONE: u8 = 128;
 const LOCK1X: u8 = 129;
 const LOCK2X: u8 = 130;
 const LOCK3X: u8 = 131;
@@ -128,6 +1417,8 @@ const LOCK6X: u8 = 134;
 
 #[derive(Clone, PartialEq, EnumIter)]
 pub enum Conviction {
+    /// no lock, the vote counts at 0.1x
+    None,
     Lock1X,
     Lock2X,
     Lock3X,
@@ -139,6 +1430,7 @@ pub enum Conviction {
 impl Conviction {
     pub fn to_value(&self) -> u8 {
         match &self {
+            Self::None => LOCKNONE,
             Self::Lock1X => LOCK1X,
             Self::Lock2X => LOCK2X,
             Self::Lock3X => LOCK3X,
@@ -147,11 +1439,109 @@ impl Conviction {
             Self::Lock6X => LOCK6X,
         }
     }
+
+    /// maps a position of the conviction slider (0 to 6) to a conviction
+    pub fn from_slider(position: u8) -> Self {
+        match position {
+            0 => Self::None,
+            1 => Self::Lock1X,
+            2 => Self::Lock2X,
+            3 => Self::Lock3X,
+            4 => Self::Lock4X,
+            5 => Self::Lock5X,
+            _ => Self::Lock6X,
+        }
+    }
+
+    pub fn slider_position(&self) -> u8 {
+        self.to_value() - LOCKNONE
+    }
+
+    /// this conviction, lowered to `max` if it's above
+    pub fn capped(self, max: &Conviction) -> Self {
+        if self.slider_position() > max.slider_position() {
+            max.clone()
+        } else {
+            self
+        }
+    }
+
+    /// number of vote locking periods the balance is locked for after the referendum ends
+    pub fn lock_periods(&self) -> u32 {
+        match self {
+            Self::None => 0,
+            Self::Lock1X => 1,
+            Self::Lock2X => 2,
+            Self::Lock3X => 4,
+            Self::Lock4X => 8,
+            Self::Lock5X => 16,
+            Self::Lock6X => 32,
+        }
+    }
+
+    /// votes counted for `balance` with this conviction, 0.1x without lock
+    pub fn votes(&self, balance: Balance) -> Balance {
+        let planck = balance.as_planck();
+        Balance::from_planck(match self {
+            Self::None => planck / 10,
+            // 1x to 6x, in the order of the slider
+            conviction => planck.saturating_mul(conviction.slider_position() as u128),
+        })
+    }
+
+    /// how long the balance stays locked after the referendum ends, given the runtime's
+    /// vote locking period (in blocks) and expected block time (in milliseconds)
+    pub fn lock_duration(&self, vote_locking_period: u32, expected_block_time: u64) -> Duration {
+        let blocks = self.lock_periods() as u64 * vote_locking_period as u64;
+        Duration::from_millis(blocks * expected_block_time)
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Self::None => "No lock".to_string(),
+            _ => format!("Lock {}", self),
+        }
+    }
+}
+
+/// color of a conviction in the picker and the summary, darker and warmer for longer locks.
+/// Each has a contrast ratio of at least 4.5:1 with white.
+fn conviction_color(conviction: &Conviction) -> &'static str {
+    match conviction {
+        Conviction::None => "#5F6B73",
+        Conviction::Lock1X => "#1E6F5C",
+        Conviction::Lock2X => "#2B6CB0",
+        Conviction::Lock3X => "#6B46C1",
+        Conviction::Lock4X => "#9C4221",
+        Conviction::Lock5X => "#C53030",
+        Conviction::Lock6X => "#822727",
+    }
 }
 
+/// inline style setting `--conviction-color`, used by the `.convictions` and
+/// `.conviction-tag` styles
+fn conviction_style(conviction: &Conviction) -> String {
+    format!("--conviction-color: {}", conviction_color(conviction))
+}
+
+/// tells the call data shown before signing apart from the signed extrinsic shown after
+const CALL_DATA_HINT: &str =
+    "What you're about to sign. It's not signed, so it can't be submitted on its own.";
+const SIGNED_EXTRINSIC_HINT: &str =
+    "The call data signed by your account, ready to be submitted to the network.";
+
+/// standard remark tag of an AYE vote on `referendum`, e.g. "REF-275:AYE", indexed off-chain
+fn remark_tag(referendum: u32) -> String {
+    format!("REF-{referendum}:AYE")
+}
+
+/// shown whenever `Conviction::None` is selected, its 0.1x weight is easy to overlook
+const NO_LOCK_NOTICE: &str = "No lock: your tokens are not locked but your vote counts at 0.1x.";
+
 impl std::fmt::Display for Conviction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            Self::None => write!(f, "0.1x"),
             Self::Lock1X => write!(f, "1x"),
             Self::Lock2X => write!(f, "2x"),
             Self::Lock3X => write!(f, "3x"),
@@ -165,222 +1555,978 @@ impl std::fmt::Display for Conviction {
 impl Component for VoteComponent {
     type Message = Message;
 
-    type Properties = ();
+    type Properties = VoteProps;
 
     fn create(ctx: &Context<Self>) -> Self {
-        ctx.link().send_future(OnlineClient::<PolkadotConfig>::from_url("wss://rpc.ibp.network/kusama").map(|res| {
-            match res {
-                Ok(online_client) => Message::OnlineClientCreated(online_client),
-                Err(err) => Message::Error(anyhow!("Online Client could not be created. Make sure you have a local node running:\n{err}")),
-            }
-        }));
-        VoteComponent {
+        let offline = (query_param("offline").as_deref() == Some("1")).then(OfflineInput::default);
+        let token_override = TokenOverride::parse(query_param("symbol"), query_param("decimals"));
+        let (submission_backend, relay_error) =
+            match SubmissionBackend::from_param(query_param("relay")) {
+                Ok(backend) => (backend, None),
+                Err(err) => (SubmissionBackend::Direct, Some(err.to_string())),
+            };
+        let custom_explorer_url = local_storage_get(EXPLORER_URL_KEY).filter(|url| !url.is_empty());
+        let mut component = VoteComponent {
+            referendum: ctx.props().referendum_index,
             message: "".to_string(),
+            remark_with_event: false,
+            preset_remark: query_param("remarktag").as_deref() == Some("1"),
             conviction: Conviction::Lock1X,
-            balance: 100,
-            stage: SigningStage::CreatingOnlineClient,
+            balance: Balance::default(),
+            balance_error: None,
+            balance_percentage: None,
+            free_balance: None,
+            flow: FlowState::default(),
             online_client: None,
+            rpc_endpoint: KUSAMA_RPC_ENDPOINT.to_string(),
+            chain_params: None,
+            metadata_mismatch: None,
+            tracks: vec![],
+            chain_info: ChainInfo::kusama().with_override(&token_override),
+            token_override,
+            public_rpc_notice_dismissed: local_storage_get(PUBLIC_RPC_NOTICE_KEY).as_deref()
+                == Some("true"),
+            submission_backend,
+            relay_error,
+            signer_only: query_param("signeronly").as_deref() == Some("1"),
+            custom_explorer_url: custom_explorer_url.clone(),
+            explorer_url_input: custom_explorer_url.unwrap_or_default(),
+            explorer_url_error: None,
             remark_call_bytes: vec![],
             vote_call_bytes: vec![],
             finalized_blocks: vec![],
+            feed_clock: js_sys::Date::now(),
+            feed_clock_running: false,
+            feed_handle: None,
+            feed_marker: None,
+            feed_epoch: 0,
+            feed_verbosity: local_storage_get(FEED_VERBOSITY_KEY)
+                .and_then(|key| FeedVerbosity::from_key(&key))
+                .unwrap_or_default(),
+            submission_handle: None,
+            tasks: vec![],
+            account_votes: None,
+            fee_estimate: None,
+            lock_impact: None,
+            lock_schedule: None,
+            delegation_checked: false,
+            tally: None,
+            referendum_count: None,
+            stage_epoch: 0,
+            stage_stuck: false,
+            post_status: query_param("postmessage").as_deref() == Some("1"),
+            max_conviction: query_param("maxconviction")
+                .and_then(|position| position.parse::<u8>().ok())
+                .map_or(Conviction::Lock6X, Conviction::from_slider),
+            embed: query_param("embed").as_deref() == Some("1"),
+            show_advanced: false,
+            show_diagnostics: false,
+            show_events: false,
+            browser_support: BrowserSupport::detect(),
+            page_visible: is_page_visible(),
+            rpc_latency: None,
+            ping_pending: false,
+            reconnecting: false,
+            keep_alive_handle: None,
+            visibility_listener: add_visibility_listener(
+                ctx.link().callback(Message::VisibilityChanged),
+            ),
+            multisig_enabled: false,
+            multisig_signatories: "".to_string(),
+            multisig_threshold: "2".to_string(),
+            multisig_step: None,
+            signer: Rc::new(ExtensionSigner),
+            auto_submit: local_storage_get(AUTO_SUBMIT_KEY).as_deref() == Some("true"),
+            wait_for_in_block: local_storage_get(WAIT_FOR_IN_BLOCK_KEY).as_deref() == Some("true"),
+            last_source: local_storage_get(LAST_SOURCE_KEY),
+            recent_accounts: local_storage_get(RECENT_ACCOUNTS_KEY)
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default(),
+            source_filter: None,
+            refreshing_accounts: false,
+            refresh_accounts_error: None,
+            extension_status: None,
+            review_nonce: None,
+            batch_weight: None,
+            weight_warning_percent: query_param("weightwarning")
+                .and_then(|percent| percent.parse::<u8>().ok())
+                .filter(|percent| (1..=100).contains(percent))
+                .unwrap_or(DEFAULT_WEIGHT_WARNING_PERCENT),
+            extension_metadata: None,
+            metadata_provided: None,
+            providing_metadata: false,
+            selected_accounts: vec![],
+            multi_vote: None,
+            offline,
+        };
+        component.balance = component.default_balance();
+        if component.offline.is_some() {
+            component.flow.stage = SigningStage::EnterMessage;
+            component.set_message(component.initial_message());
+            component.set_vote(component.default_balance(), Conviction::Lock1X);
+        } else if let Some(SharedClient(client)) = &ctx.props().client {
+            // set up right away, so the connecting stage is never shown
+            let endpoint = ctx
+                .props()
+                .client_endpoint
+                .as_ref()
+                .map_or(INJECTED_CLIENT_LABEL, |endpoint| endpoint.as_str());
+            component.set_online_client(ctx, client.as_ref().clone(), endpoint.to_string());
+        } else {
+            component.connect(ctx);
         }
+        component.arm_watchdog(ctx);
+        component.update_keep_alive(ctx);
+        #[cfg(feature = "debug")]
+        crate::debug_console::set_vote_scope(Some(ctx.link().clone()));
+        component
     }
 
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let previous_stage = self.stage_label();
         match msg {
-            Message::OnlineClientCreated(online_client) => {
-                self.online_client = Some(online_client);
-                // self.stage = SigningStage::EnterMessage;
-                // self.set_message("Hello".into());
-                self.stage = SigningStage::EnterBalance;
-                self.set_vote(1, Conviction::Lock1X);
+            Message::OnlineClientCreated(online_client, endpoint) => {
+                if !self.set_online_client(ctx, online_client, endpoint) {
+                    return false;
+                }
+            }
+            Message::ReceivedChainInfo(chain_info) => {
+                self.chain_info = chain_info.with_override(&self.token_override)
+            }
+            Message::ReceivedReferendumCount(count) => self.referendum_count = Some(count),
+            Message::ReceivedTally(referendum, tally) => {
+                // ignore the tally of a referendum switched away from
+                if referendum == self.referendum {
+                    self.tally = Some(tally);
+                }
+            }
+            Message::ChainInfoFailed(err) => {
+                // the registry entry is used instead
+                web_sys::console::log_1(&format!("Fetching chain properties failed: {err}").into());
             }
             Message::ChangeMessage(message) => {
                 self.set_message(message);
             }
+            Message::ToggleRemarkWithEvent => {
+                self.remark_with_event = !self.remark_with_event;
+                self.set_message(self.message.clone());
+            }
+            Message::UseRemarkTag => self.set_message(remark_tag(self.referendum)),
+            Message::ConfirmMessage => self.flow.stage = SigningStage::EnterBalance,
+            Message::SkipMessage => {
+                self.set_message("".into());
+                self.flow.stage = SigningStage::EnterBalance;
+            }
             Message::ChangeBalance(balance) => {
-                let value = balance.parse::<u128>().unwrap_or(100);
-                self.set_vote(value, self.conviction.clone());
+                self.balance_percentage = None;
+                match parse_percentage(&balance) {
+                    Some(_) if self.offline.is_some() => {
+                        self.balance_error =
+                            Some("Percentages need a connection to read the free balance".into())
+                    }
+                    Some(Ok(percent)) => {
+                        self.balance_error = None;
+                        self.balance_percentage = Some(percent);
+                        // resolved against the account chosen next
+                        self.free_balance = None;
+                    }
+                    Some(Err(err)) => self.balance_error = Some(err.to_string()),
+                    None => match parse_amount(&balance, self.chain_info.decimals) {
+                        Ok(balance) => {
+                            self.balance_error = None;
+                            self.set_vote(balance, self.conviction.clone());
+                        }
+                        Err(err) => self.balance_error = Some(err.to_string()),
+                    },
+                }
+            }
+            Message::ChangeConviction(conviction) => {
+                self.set_vote(self.balance, conviction);
+            }
+            Message::ToggleMultisig => self.multisig_enabled = !self.multisig_enabled,
+            Message::DismissPublicRpcNotice => {
+                self.public_rpc_notice_dismissed = true;
+                local_storage_set(PUBLIC_RPC_NOTICE_KEY, "true");
+            }
+            Message::ToggleAutoSubmit => {
+                self.auto_submit = !self.auto_submit;
+                local_storage_set(AUTO_SUBMIT_KEY, &self.auto_submit.to_string());
+            }
+            Message::ToggleWaitForInBlock => {
+                self.wait_for_in_block = !self.wait_for_in_block;
+                local_storage_set(WAIT_FOR_IN_BLOCK_KEY, &self.wait_for_in_block.to_string());
+            }
+            Message::ChangeMultisigSignatories(signatories) => {
+                self.multisig_signatories = signatories;
             }
-            Message::ChangeConviction(conviction) => {
-                self.set_vote(self.balance, conviction);
+            Message::ChangeMultisigThreshold(threshold) => self.multisig_threshold = threshold,
+            Message::ChangeOfflineInput(field, value) => {
+                if let Some(offline) = &mut self.offline {
+                    offline.set(field, value);
+                }
             }
+            Message::RequestAccounts if self.metadata_mismatch.is_some() => return false,
             Message::RequestAccounts => {
-                self.stage = SigningStage::RequestingAccounts;
-                ctx.link().send_future(get_accounts().map(
-                    |accounts_or_err| match accounts_or_err {
+                self.flow.stage = SigningStage::RequestingAccounts;
+                self.refresh_accounts_error = None;
+                self.spawn(
+                    ctx,
+                    get_accounts().map(|accounts_or_err| match accounts_or_err {
                         Ok(accounts) => Message::ReceivedAccounts(accounts),
                         Err(err) => Message::Error(err),
-                    },
-                ));
+                    }),
+                );
             }
+            // ignore accounts requested before a `Reset`
+            Message::ReceivedAccounts(_)
+                if !matches!(self.flow.stage, SigningStage::RequestingAccounts) => {}
             Message::ReceivedAccounts(accounts) => {
-                self.stage = SigningStage::SelectAccount(accounts);
+                self.notify(StatusEvent::AccountsLoaded {
+                    count: accounts.len(),
+                });
+                self.source_filter = None;
+                self.selected_accounts.clear();
+                self.extension_status = None;
+                if accounts.is_empty() {
+                    self.spawn(ctx, check_extension_status());
+                }
+                self.flow.stage = SigningStage::SelectAccount(accounts);
             }
-            Message::Error(err) => self.stage = SigningStage::Error(err.to_string()),
-            Message::SignWithAccount(i) => {
-                if let SigningStage::SelectAccount(accounts) = &self.stage {
-                    let account = accounts.get(i).unwrap();
-                    let account_address = account.address.clone();
+            Message::FilterSource(source) => self.source_filter = source,
+            Message::RefreshAccounts => {
+                self.refreshing_accounts = true;
+                self.refresh_accounts_error = None;
+                self.spawn(
+                    ctx,
+                    get_accounts().map(|accounts_or_err| match accounts_or_err {
+                        Ok(accounts) => Message::RefreshedAccounts(accounts),
+                        Err(err) => Message::RefreshAccountsFailed(err),
+                    }),
+                );
+            }
+            Message::RefreshedAccounts(refreshed) => {
+                self.refreshing_accounts = false;
+                if let SigningStage::SelectAccount(accounts) = &mut self.flow.stage {
+                    *accounts = merge_accounts(accounts, refreshed);
+                    // indexes may have moved
+                    self.selected_accounts.clear();
+                    self.extension_status = None;
+                    // keep the filter while its extension still has accounts
+                    if let Some(source) = &self.source_filter {
+                        if !accounts.iter().any(|account| &account.source == source) {
+                            self.source_filter = None;
+                        }
+                    }
+                    if accounts.is_empty() {
+                        self.spawn(ctx, check_extension_status());
+                    }
+                }
+            }
+            Message::RefreshAccountsFailed(err) => {
+                self.refreshing_accounts = false;
+                self.refresh_accounts_error = Some(err.to_string());
+            }
+            Message::ReceivedExtensionStatus(status) => self.extension_status = Some(Ok(status)),
+            Message::ExtensionStatusFailed(err) => {
+                self.extension_status = Some(Err(err.to_string()))
+            }
+            Message::Error(err) => {
+                // a failing account doesn't stop the other accounts of a multi-account vote
+                let queued = matches!(
+                    self.flow.stage,
+                    SigningStage::ReviewVote(_) | SigningStage::Signing(_)
+                ) && self.finish_queued_account(ctx, Err(err.to_string()));
+                if !queued {
+                    self.flow.stage = SigningStage::Error(err.to_string());
+                }
+            }
+            Message::SigningFinished(epoch, result) => {
+                self.update_flow(ctx, FlowMessage::SigningFinished(epoch, result))
+            }
+            Message::RetrySigning => {
+                if let SigningStage::SigningDeclined { account, nonce, .. } = &self.flow.stage {
+                    let nonce = *nonce;
+                    match (&self.offline, self.online_client.clone()) {
+                        // the offline nonce is the one entered by the user
+                        (Some(_), _) | (None, None) => {
+                            ctx.link().send_message(Message::ResumeSigning(Some(nonce)))
+                        }
+                        (None, Some(api)) => {
+                            let account_id = match parse_account_id(&account.address) {
+                                Ok(account_id) => account_id,
+                                Err(err) => {
+                                    ctx.link().send_message(Message::Error(err));
+                                    return false;
+                                }
+                            };
+                            // the on-chain nonce lags behind while earlier extrinsics are pending,
+                            // the nonce is only stale once the chain moved past it
+                            self.spawn(ctx, async move {
+                                match api.tx().account_nonce(&account_id).await {
+                                    Ok(current) if current <= nonce => {
+                                        Message::ResumeSigning(Some(nonce))
+                                    }
+                                    _ => Message::ResumeSigning(None),
+                                }
+                            });
+                        }
+                    }
+                }
+            }
+            Message::ResumeSigning(nonce) => {
+                if let SigningStage::SigningDeclined { account, .. } = &self.flow.stage {
+                    match nonce {
+                        Some(nonce) => self.flow.next_nonces.insert(account.address.clone(), nonce),
+                        None => self.flow.next_nonces.remove(&account.address),
+                    };
+                    self.flow.stage = SigningStage::ReviewVote(account.clone());
+                    ctx.link().send_message(Message::SignWithAccount);
+                }
+            }
+            Message::ReviewWithAccount(i) => {
+                if let SigningStage::SelectAccount(accounts) = &self.flow.stage {
+                    let account = accounts.get(i).unwrap().clone();
+                    self.flow.resign_reason = None;
+                    self.review_with(ctx, account);
+                }
+            }
+            Message::ReceivedBatchWeight(weight) => self.batch_weight = Some(weight),
+            Message::ReceivedExtensionMetadata(metadata) => {
+                self.extension_metadata = Some(metadata)
+            }
+            Message::ProvideMetadata => {
+                let (Some(api), Some(chain_params), SigningStage::ReviewVote(account), false) = (
+                    self.online_client.clone(),
+                    self.chain_params.clone(),
+                    &self.flow.stage,
+                    self.providing_metadata,
+                ) else {
+                    return false;
+                };
+                self.providing_metadata = true;
+                let chain_info = self.chain_info.clone();
+                let source = account.source.clone();
+                self.spawn(ctx, async move {
+                    let provided = provide_metadata(&api, &chain_info, &chain_params, source).await;
+                    Message::MetadataProvided(provided.map_err(|err| err.to_string()))
+                });
+            }
+            Message::MetadataProvided(provided) => {
+                self.providing_metadata = false;
+                if let (Ok(true), Some(metadata), Some(chain_params)) =
+                    (&provided, &mut self.extension_metadata, &self.chain_params)
+                {
+                    metadata.spec_version = Some(chain_params.spec_version);
+                }
+                self.metadata_provided = Some(provided);
+            }
+            Message::ToggleAccountSelection(i) => {
+                match self
+                    .selected_accounts
+                    .iter()
+                    .position(|selected| *selected == i)
+                {
+                    Some(position) => {
+                        self.selected_accounts.remove(position);
+                    }
+                    None => self.selected_accounts.push(i),
+                }
+            }
+            Message::VoteWithSelected => {
+                if let SigningStage::SelectAccount(accounts) = &self.flow.stage {
+                    let pending = self
+                        .selected_accounts
+                        .iter()
+                        .filter_map(|i| accounts.get(*i).cloned())
+                        .collect();
+                    self.multi_vote = Some(MultiVote {
+                        pending,
+                        current: None,
+                        results: vec![],
+                    });
+                    self.selected_accounts.clear();
+                    ctx.link().send_message(Message::NextQueuedAccount);
+                }
+            }
+            Message::NextQueuedAccount => {
+                let Some(multi_vote) = &mut self.multi_vote else {
+                    return false;
+                };
+                if multi_vote.pending.is_empty() {
+                    // all done, the results stay shown with the stage of the last account,
+                    // unless it failed before signing
+                    if matches!(
+                        self.flow.stage,
+                        SigningStage::ReviewVote(_) | SigningStage::Signing(_)
+                    ) {
+                        ctx.link().send_message(Message::RequestAccounts);
+                    }
+                    return true;
+                }
+                let account = multi_vote.pending.remove(0);
+                multi_vote.current = Some(account.clone());
+                self.flow.call_comparison = None;
+                self.flow.signed_nonce = None;
+                self.flow.submitted_extrinsic = None;
+                self.flow.resign_reason = None;
+                self.review_with(ctx, account);
+            }
+            Message::SkipQueuedAccount => {
+                if let SigningStage::ReviewVote(_) = &self.flow.stage {
+                    self.finish_queued_account(ctx, Err("skipped".to_string()));
+                }
+            }
+            Message::ReceivedFreeBalance(free) => {
+                if let SigningStage::ReviewVote(account) = &self.flow.stage {
+                    let account = account.clone();
+                    self.free_balance = Some(Ok(free));
+                    // the estimates were waiting for the amount
+                    if let Some(percent) = self.balance_percentage {
+                        self.set_vote(free.percentage(percent), self.conviction.clone());
+                        self.estimate_vote(ctx, &account);
+                    }
+                }
+            }
+            Message::ReceivedReviewNonce(nonce) => self.review_nonce = Some(Ok(nonce)),
+            Message::ReviewNonceFailed(err) => self.review_nonce = Some(Err(err.to_string())),
+            Message::ReduceBalanceForFee => {
+                if let (Some(Ok(free)), Some(Ok(fee))) = (&self.free_balance, &self.fee_estimate) {
+                    if let Some(shortfall) = fee_shortfall(*free, self.balance, *fee) {
+                        let reduced = self
+                            .balance
+                            .as_planck()
+                            .saturating_sub(shortfall.as_planck());
+                        // the vote is an amount from now on
+                        self.balance_percentage = None;
+                        self.set_vote(Balance::from_planck(reduced), self.conviction.clone());
+                        if let SigningStage::ReviewVote(account) = &self.flow.stage {
+                            let account = account.clone();
+                            self.estimate_vote(ctx, &account);
+                        }
+                    }
+                }
+            }
+            Message::FreeBalanceFailed(err) => self.free_balance = Some(Err(err.to_string())),
+            Message::ReceivedFeeEstimate(fee) => self.fee_estimate = Some(Ok(fee)),
+            Message::FeeEstimateFailed(err) => self.fee_estimate = Some(Err(err.to_string())),
+            Message::SignWithAccount => self.update_flow(ctx, FlowMessage::SignWithAccount),
+            Message::MultisigPrepared(epoch, ..) if epoch != self.flow.signing_epoch => {
+                return false
+            }
+            Message::MultisigPrepared(epoch, params, step) => {
+                if let SigningStage::Signing(account) = &self.flow.stage {
+                    let account_id = match parse_account_id(&account.address) {
+                        Ok(account_id) => account_id,
+                        Err(err) => {
+                            ctx.link().send_message(Message::Error(err));
+                            return false;
+                        }
+                    };
                     let account_source = account.source.clone();
-                    let account_id: AccountId32 = account_address.parse().unwrap();
-
-                    self.stage = SigningStage::Signing(account.clone());
-
-                    let vote_call = node_runtime::tx().conviction_voting().vote(
-                        275,
-                        AccountVote::Standard {
-                            vote: Vote(self.conviction.to_value()),
-                            balance: self.balance * 1000000000000,
-                        },
-                    );
-
+                    let account_address = account.address.clone();
+                    let nonce = self.flow.next_nonces.get(&account_address).copied();
+                    let signer = self.signer.clone();
                     let api = self.online_client.as_ref().unwrap().clone();
+                    let chain_params = self.chain_params.clone().unwrap();
 
-                    ctx.link().send_future(async move {
-                        let Ok(account_nonce) = api.tx().account_nonce(&account_id).await else {
-                            return Message::Error(anyhow!("Fetching account nonce failed"));
-                        };
-
-                        let Ok(call_data) = api.tx().call_data(&vote_call) else {
-                            return Message::Error(anyhow!("could not encode call data"));
-                        };
-
-                        let Ok(signature) = extension_signature_for_extrinsic(
-                            &call_data,
-                            &api,
-                            account_nonce,
-                            account_source,
-                            account_address,
-                        )
-                        .await
-                        else {
-                            return Message::Error(anyhow!("Signing via extension failed"));
-                        };
-
-                        let Ok(multi_signature) = MultiSignature::decode(&mut &signature[..])
-                        else {
-                            return Message::Error(anyhow!("MultiSignature Decoding"));
-                        };
-
-                        let Ok(partial_signed) = api.tx().create_partial_signed_with_nonce(
-                            &vote_call,
-                            account_nonce,
-                            Default::default(),
-                        ) else {
-                            return Message::Error(anyhow!("PartialExtrinsic creation failed"));
-                        };
-
-                        // Apply the signature
-                        let signed_extrinsic = partial_signed
-                            .sign_with_address_and_signature(&account_id.into(), &multi_signature);
-
-                        // check the TX validity (to debug in the js console if the extrinsic would work)
-                        // let dry_res = signed_extrinsic.validate().await;
-                        // web_sys::console::log_1(&format!("Validation Result: {:?}", dry_res).into());
-
-                        // return the signature and signed extrinsic
-                        Message::ReceivedSignature(multi_signature, signed_extrinsic)
-                    });
+                    let multisig_tx = node_runtime::tx().multisig();
+                    let other_signatories = params.other_signatories.clone();
+                    if let MultisigStep::Execute { .. } = step {
+                        let call = multisig_tx.as_multi(
+                            params.threshold,
+                            other_signatories,
+                            step.timepoint(),
+                            self.runtime_call(),
+                            vote_max_weight(),
+                        );
+                        self.spawn(
+                            ctx,
+                            sign_call(
+                                api,
+                                chain_params,
+                                call,
+                                account_id,
+                                account_source,
+                                account_address,
+                                nonce,
+                                signer,
+                            )
+                            .map(move |result| Message::SigningFinished(epoch, result)),
+                        );
+                    } else {
+                        let call = multisig_tx.approve_as_multi(
+                            params.threshold,
+                            other_signatories,
+                            step.timepoint(),
+                            call_hash(&self.call_bytes()),
+                            vote_max_weight(),
+                        );
+                        self.spawn(
+                            ctx,
+                            sign_call(
+                                api,
+                                chain_params,
+                                call,
+                                account_id,
+                                account_source,
+                                account_address,
+                                nonce,
+                                signer,
+                            )
+                            .map(move |result| Message::SigningFinished(epoch, result)),
+                        );
+                    }
+                    self.multisig_step = Some((params, step));
+                }
+            }
+            // ignore what was loaded for an account reviewed before
+            Message::DelegationChecked(address, _)
+            | Message::ReceivedAccountVotes(address, _)
+            | Message::AccountVotesFailed(address, _)
+            | Message::ReceivedLockImpact(address, _)
+            | Message::LockImpactFailed(address, _)
+            | Message::ReceivedLockSchedule(address, _)
+                if self.stage_account().map(|account| &account.address) != Some(&address) =>
+            {
+                return false
+            }
+            Message::DelegationChecked(_, delegation) => {
+                self.delegation_checked = true;
+                if let (SigningStage::ReviewVote(account), Some(delegation)) =
+                    (&self.flow.stage, delegation)
+                {
+                    let undelegate_call = node_runtime::tx()
+                        .conviction_voting()
+                        .undelegate(delegation.track);
+                    let online_client = self.online_client.as_ref().unwrap();
+                    let undelegate_call_bytes = undelegate_call
+                        .encode_call_data(&online_client.metadata())
+                        .unwrap();
+                    self.flow.stage = SigningStage::DelegationBlocked {
+                        account: account.clone(),
+                        delegation,
+                        undelegate_call_hex: hex_value(undelegate_call_bytes),
+                    };
                 }
             }
-            Message::ReceivedSignature(signature, signed_extrinsic) => {
-                if let SigningStage::Signing(account) = &self.stage {
-                    let signed_extrinsic_hex =
-                        format!("0x{}", hex::encode(signed_extrinsic.encoded()));
-                    self.stage = SigningStage::SigningSuccess {
-                        signer_account: account.clone(),
-                        signature,
-                        signed_extrinsic_hex,
-                        submitting_stage: SubmittingStage::Initial { signed_extrinsic },
+            Message::ReceivedAccountVotes(_, votes) => {
+                self.account_votes = Some(Ok(votes));
+            }
+            Message::AccountVotesFailed(_, err) => {
+                self.account_votes = Some(Err(err.to_string()));
+            }
+            Message::ReceivedLockImpact(_, lock_impact) => {
+                self.lock_impact = Some(Ok(lock_impact));
+            }
+            Message::LockImpactFailed(_, err) => {
+                self.lock_impact = Some(Err(err.to_string()));
+            }
+            Message::ReceivedLockSchedule(_, schedule) => {
+                self.lock_schedule = Some(schedule);
+            }
+            Message::ValidationResult(result) => self.flow.validation = Some(result),
+            Message::SubmitSigned { watch } => {
+                self.update_flow(ctx, FlowMessage::SubmitSigned { watch })
+            }
+            Message::RetrySubmission => {
+                let (
+                    Some(api),
+                    Some(encoded),
+                    SigningStage::SigningSuccess {
+                        signer_account,
+                        submitting_stage: submitting_stage @ SubmittingStage::Error(_),
+                        ..
+                    },
+                ) = (
+                    self.online_client.clone(),
+                    self.flow.submitted_extrinsic.clone(),
+                    &mut self.flow.stage,
+                )
+                else {
+                    return false;
+                };
+                if let Some(nonce) = self.flow.signed_nonce {
+                    self.flow
+                        .next_nonces
+                        .insert(signer_account.address.clone(), nonce + 1);
+                }
+                *submitting_stage = SubmittingStage::Submitting {
+                    extrinsic_hash: extrinsic_hash(&encoded),
+                };
+
+                let (abort_handle, abort_registration) = AbortHandle::new_pair();
+                self.submission_handle = Some(abort_handle);
+
+                let submission = Abortable::new(
+                    resubmit_unless_included(
+                        api,
+                        encoded,
+                        self.submission_backend.clone(),
+                        self.wait_for_in_block,
+                    ),
+                    abort_registration,
+                );
+                self.spawn(ctx, async move {
+                    match submission.await {
+                        Ok(Ok(outcome)) => Message::ExtrinsicFinalized(outcome),
+                        Ok(Err(err)) => Message::ExtrinsicFailed(err),
+                        Err(_aborted) => Message::CancelSubmission,
                     }
+                });
+            }
+            Message::CancelSubmission => {
+                if let Some(handle) = self.submission_handle.take() {
+                    handle.abort();
+                }
+                if let SigningStage::SigningSuccess {
+                    submitting_stage: submitting_stage @ SubmittingStage::Submitting { .. },
+                    ..
+                } = &mut self.flow.stage
+                {
+                    *submitting_stage = SubmittingStage::Cancelled;
+                    self.finish_queued_account(
+                        ctx,
+                        Err("stopped watching, the vote may still be finalized".to_string()),
+                    );
                 }
             }
-            Message::SubmitSigned => {
+            Message::ExtrinsicSubmitted(extrinsic_hash) => {
                 if let SigningStage::SigningSuccess {
-                    submitting_stage: submitting_stage @ SubmittingStage::Initial { .. },
+                    submitting_stage: submitting_stage @ SubmittingStage::Submitting { .. },
                     ..
-                } = &mut self.stage
+                } = &mut self.flow.stage
                 {
-                    let SubmittingStage::Initial { signed_extrinsic } =
-                        std::mem::replace(submitting_stage, SubmittingStage::Submitting)
-                    else {
-                        panic!("unreachable")
-                    };
-
-                    ctx.link().send_future(async move {
-                        match submit_wait_finalized_and_get_extrinsic_success_event(
-                            signed_extrinsic,
-                        )
-                        .await
-                        {
-                            Ok(remark_event) => Message::ExtrinsicFinalized { remark_event },
-                            Err(err) => Message::ExtrinsicFailed(err),
-                        }
-                    });
+                    *submitting_stage = SubmittingStage::Submitted { extrinsic_hash };
                 }
             }
-            Message::ExtrinsicFinalized { remark_event } => {
+            Message::ExtrinsicFinalized(outcome) => {
+                // the result may come from `recover_submission` while still watching
+                if let Some(handle) = self.submission_handle.take() {
+                    handle.abort();
+                }
+                match &outcome.dispatch_error {
+                    None => self.notify(StatusEvent::Finalized {
+                        block_hash: format!("{:?}", outcome.block_hash),
+                    }),
+                    Some(error) => self.notify(StatusEvent::Failed {
+                        error: error.clone(),
+                    }),
+                }
+                let result = match &outcome.dispatch_error {
+                    None if outcome.finalized => {
+                        Ok(format!("finalized in block {:?}", outcome.block_hash))
+                    }
+                    None => Ok(format!("included in block {:?}", outcome.block_hash)),
+                    Some(error) => Err(error.clone()),
+                };
                 if let SigningStage::SigningSuccess {
                     submitting_stage, ..
-                } = &mut self.stage
+                } = &mut self.flow.stage
                 {
-                    *submitting_stage = SubmittingStage::Success { remark_event }
+                    *submitting_stage = SubmittingStage::Finalized(outcome)
                 }
+                self.finish_queued_account(ctx, result);
             }
             Message::ExtrinsicFailed(err) => {
+                if let Some(handle) = self.submission_handle.take() {
+                    handle.abort();
+                }
+                // the nonce may not have been used, re-sync it from chain for the next extrinsic
+                if let SigningStage::SigningSuccess { signer_account, .. } = &self.flow.stage {
+                    self.flow.next_nonces.remove(&signer_account.address);
+                }
+                self.notify(StatusEvent::Failed {
+                    error: err.to_string(),
+                });
+                self.finish_queued_account(ctx, Err(err.to_string()));
                 if let SigningStage::SigningSuccess {
                     submitting_stage, ..
-                } = &mut self.stage
+                } = &mut self.flow.stage
                 {
                     *submitting_stage = SubmittingStage::Error(err)
                 }
             }
-            Message::PushFinalizedBlock(block_attr) => {
+            Message::PushFinalizedBlock(block) => {
                 // newer lines go to the top
-                self.finalized_blocks.insert(0, block_attr);
-                // remove older block number
-                if self.finalized_blocks.len() > 1 {
-                    self.finalized_blocks.truncate(1);
+                self.finalized_blocks.insert(0, FeedEntry::received(block));
+                self.finalized_blocks.truncate(FEED_HISTORY_LEN);
+                self.feed_clock = js_sys::Date::now();
+                self.start_feed_clock(ctx);
+                self.arm_feed_watchdog(ctx);
+            }
+            Message::FeedStalled(epoch) => {
+                // blocks arrived meanwhile, or the feed is not running
+                if epoch != self.feed_epoch || self.feed_handle.is_none() {
+                    return false;
                 }
+                self.feed_marker = Some(format!(
+                    "No blocks received for {} s — the endpoint may be stalled, reconnecting...",
+                    FEED_STALL_TIMEOUT.as_secs()
+                ));
+                self.reconnect_feed(ctx);
+            }
+            Message::FeedFailed(err) => {
+                // disarms the watchdog, the feed is restarted once reconnected
+                self.feed_epoch = self.feed_epoch.wrapping_add(1);
+                self.feed_marker = Some(format!("Disconnected: {err}, reconnecting..."));
+                self.reconnect_feed(ctx);
+            }
+            Message::FeedReconnectFailed(err) => {
+                self.feed_marker = Some(format!("Reconnecting failed: {err}"));
+                // try again after another timeout
+                self.arm_feed_watchdog(ctx);
             }
             Message::SubscribeFinalizedBlock => {
-                let cb: Callback<AttrValue> = ctx.link().callback(Message::PushFinalizedBlock);
-                ctx.link()
-                    .send_future(subscribe_to_finalized_blocks(cb).map(|result| {
-                        let err = result.unwrap_err();
-                        Message::Error(err.into())
-                    }));
+                let Some(api) = self.online_client.clone() else {
+                    return false;
+                };
+                if let Some(handle) = self.feed_handle.take() {
+                    handle.abort();
+                }
+                let (abort_handle, abort_registration) = AbortHandle::new_pair();
+                self.feed_handle = Some(abort_handle);
+
+                let cb: Callback<FinalizedBlock> = ctx.link().callback(Message::PushFinalizedBlock);
+                let subscription =
+                    Abortable::new(subscribe_to_finalized_blocks(api, cb), abort_registration);
+                self.arm_feed_watchdog(ctx);
+                self.spawn(ctx, async move {
+                    match subscription.await {
+                        Ok(Err(err)) => Message::FeedFailed(err.to_string()),
+                        Ok(Ok(())) => Message::FeedStopped { aborted: false },
+                        Err(_aborted) => Message::FeedStopped { aborted: true },
+                    }
+                });
+            }
+            Message::FeedStopped { aborted } => {
+                // an aborted subscription was already replaced
+                if !aborted {
+                    self.feed_handle = None;
+                }
+            }
+            Message::ChangeFeedVerbosity(verbosity) => {
+                local_storage_set(FEED_VERBOSITY_KEY, verbosity.key());
+                self.feed_verbosity = verbosity;
+            }
+            Message::StageTimeout(epoch) => {
+                if epoch == self.stage_epoch && self.is_async_stage() {
+                    self.stage_stuck = true;
+                }
+            }
+            Message::RetryStage => match self.stage_label() {
+                "creating_online_client" => {
+                    self.stage_stuck = false;
+                    self.connect(ctx);
+                }
+                "requesting_accounts" | "signing" => {
+                    ctx.link().send_message(Message::RequestAccounts);
+                }
+                _ => {}
+            },
+            Message::CancelStage => match self.stage_label() {
+                "submitting" => ctx.link().send_message(Message::CancelSubmission),
+                _ if self.online_client.is_some() || self.offline.is_some() => {
+                    self.flow.stage = SigningStage::EnterBalance
+                }
+                _ => {
+                    self.flow.stage = SigningStage::Error(
+                        "Connecting to Kusama was cancelled. Reload the page to try again."
+                            .to_string(),
+                    )
+                }
+            },
+            Message::ToggleAdvanced => self.show_advanced = !self.show_advanced,
+            Message::ToggleDiagnostics => self.show_diagnostics = !self.show_diagnostics,
+            Message::ToggleEvents => self.show_events = !self.show_events,
+            Message::CopyAppsLink(url) => {
+                self.spawn(ctx, async move {
+                    let copied = JsFuture::from(js_copy_to_clipboard(url)).await.is_ok();
+                    Message::AppsLinkCopied(copied)
+                });
+                return false;
+            }
+            Message::AppsLinkCopied(copied) => self.flow.apps_link_copied = Some(copied),
+            Message::FeedClockTick => {
+                // restarted by the next block, or when the page is visible again
+                if self.finalized_blocks.is_empty() || !self.page_visible {
+                    self.feed_clock_running = false;
+                    return false;
+                }
+                self.feed_clock = js_sys::Date::now();
+                self.spawn(ctx, async {
+                    sleep(FEED_CLOCK_INTERVAL).await;
+                    Message::FeedClockTick
+                });
+            }
+            Message::ChangeExplorerUrl(url) => self.explorer_url_input = url,
+            Message::SaveExplorerUrl => match parse_explorer_base_url(&self.explorer_url_input) {
+                Ok(url) => {
+                    local_storage_set(EXPLORER_URL_KEY, &url);
+                    self.explorer_url_input = url.clone();
+                    self.custom_explorer_url = Some(url);
+                    self.explorer_url_error = None;
+                }
+                Err(err) => self.explorer_url_error = Some(err.to_string()),
+            },
+            Message::ClearExplorerUrl => {
+                local_storage_set(EXPLORER_URL_KEY, "");
+                self.custom_explorer_url = None;
+                self.explorer_url_input = "".to_string();
+                self.explorer_url_error = None;
+            }
+            Message::Reset => self.update_flow(ctx, FlowMessage::Reset),
+            Message::ResyncNonces => self.flow.next_nonces.clear(),
+            Message::VisibilityChanged(visible) => {
+                self.page_visible = visible;
+                if visible && !self.ping_pending {
+                    ctx.link().send_message(Message::PingRpc);
+                }
+                if visible {
+                    self.start_feed_clock(ctx);
+                }
+                if let (
+                    true,
+                    Some(api),
+                    SigningStage::SigningSuccess {
+                        submitting_stage: SubmittingStage::Submitting { extrinsic_hash },
+                        ..
+                    },
+                ) = (visible, &self.online_client, &self.flow.stage)
+                {
+                    let recovery = recover_submission(
+                        api.clone(),
+                        *extrinsic_hash,
+                        !Self::injected_client(ctx),
+                    );
+                    self.spawn_batch(ctx, recovery);
+                }
+            }
+            Message::PingRpc => match (&self.online_client, self.page_visible) {
+                (Some(api), true) => {
+                    self.ping_pending = true;
+                    let api = api.clone();
+                    self.spawn(ctx, async move {
+                        let started = js_sys::Date::now();
+                        let result = api.rpc().header(None).await;
+                        let elapsed = Duration::from_millis((js_sys::Date::now() - started) as u64);
+                        Message::RpcLatencyMeasured(
+                            result.map(|_| elapsed).map_err(|err| err.to_string()),
+                        )
+                    });
+                }
+                _ => self.ping_pending = false,
+            },
+            Message::RpcLatencyMeasured(latency) => {
+                if latency.is_err() {
+                    self.reconnect(ctx);
+                }
+                self.rpc_latency = Some(latency);
+                self.spawn(ctx, async {
+                    sleep(PING_INTERVAL).await;
+                    Message::PingRpc
+                });
+            }
+            Message::KeptAlive(result) => {
+                self.keep_alive_handle = None;
+                if let Err(err) = result {
+                    self.rpc_latency = Some(Err(err));
+                    self.reconnect(ctx);
+                }
+            }
+            Message::ReconnectFailed(err) => {
+                self.reconnecting = false;
+                self.rpc_latency = Some(Err(format!("reconnecting failed: {err}")));
+            }
+            Message::Reconnected(online_client, endpoint) => {
+                self.reconnecting = false;
+                // scheduled again with the new client
+                self.cancel_keep_alive();
+                if let Err(err) = check_kusama_genesis(online_client.genesis_hash()) {
+                    ctx.link().send_message(Message::Error(err));
+                    return false;
+                }
+                self.rpc_endpoint = endpoint;
+                let previous_params = self.chain_params.clone();
+                if let Ok(chain_params) = ChainParams::from_client(&online_client) {
+                    self.chain_params = Some(chain_params);
+                }
+                // the signature of a prepared extrinsic is bound to the runtime version it was
+                // signed for
+                let runtime_changed = match (&previous_params, &self.chain_params) {
+                    (Some(previous), Some(current)) => {
+                        previous.spec_version != current.spec_version
+                            || previous.transaction_version != current.transaction_version
+                    }
+                    _ => false,
+                };
+                self.online_client = Some(online_client);
+                self.update_flow(ctx, FlowMessage::Reconnected { runtime_changed });
+                // the old subscription is bound to the dropped connection
+                if self.feed_handle.is_some() {
+                    self.feed_marker = Some(format!("Reconnected to {}", self.rpc_endpoint));
+                    ctx.link().send_message(Message::SubscribeFinalizedBlock);
+                }
             }
+            Message::SubmissionNotFound => {}
+            #[cfg(feature = "debug")]
+            Message::DebugDumpCall => {
+                let dump = format!(
+                    "stage: {}, referendum: {}, call: {}",
+                    self.stage_label(),
+                    self.referendum,
+                    hex_value(self.call_bytes())
+                );
+                web_sys::console::log_1(&dump.into());
+                return false;
+            }
+            #[cfg(feature = "debug")]
+            Message::DebugSetReferendum(referendum) => self.switch_referendum(ctx, referendum),
+            #[cfg(feature = "debug")]
+            Message::DebugForceStage(name) => match name.as_str() {
+                "enter_message" => self.flow.stage = SigningStage::EnterMessage,
+                "enter_balance" => self.flow.stage = SigningStage::EnterBalance,
+                "select_account" => ctx.link().send_message(Message::RequestAccounts),
+                "error" => {
+                    self.flow.stage = SigningStage::Error("Forced from the console".to_string())
+                }
+                _ => {
+                    web_sys::console::warn_1(&format!("Unknown stage {name}").into());
+                    return false;
+                }
+            },
         };
+        if previous_stage != self.stage_label() {
+            self.arm_watchdog(ctx);
+        }
+        self.update_keep_alive(ctx);
+        true
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, _old_props: &Self::Properties) -> bool {
+        self.switch_referendum(ctx, ctx.props().referendum_index);
         true
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
         let message_as_hex_html = || {
             html!(
-                <div class="mb">
-                    <b>{"Hex representation of \"remark\" call in \"System\" pallet:"}</b> <br/>
-                    {format!("0x{}", hex::encode(&self.remark_call_bytes))}
-                </div>
+                <HexDisplay label={format!("Hex representation of \"{}\" call in \"System\" pallet:", if self.remark_with_event { "remark_with_event" } else { "remark" })} value={hex_value(&self.remark_call_bytes)}/>
             )
         };
 
-        let _message_html: Html = match &self.stage {
+        let message_html: Html = match &self.flow.stage {
             SigningStage::Error(_)
             | SigningStage::EnterMessage
             | SigningStage::CreatingOnlineClient => html!(<></>),
+            _ if self.message.is_empty() => html!(<></>),
             _ => {
-                let _remark_call = node_runtime::tx()
-                    .system()
-                    .remark(self.message.as_bytes().to_vec());
-
                 html!(
                     <div>
                         <div class="mb">
                             <b>{"Message: "}</b> <br/>
                             {&self.message}
                         </div>
+                        <HexDisplay label="Remark bytes:" value={hex_value(self.message.as_bytes())}/>
                         {message_as_hex_html()}
                     </div>
                 )
@@ -388,68 +2534,233 @@ impl Component for VoteComponent {
         };
 
         let vote_as_hex_html = || {
-            let encoded_call = format!("0x{}", hex::encode(&self.vote_call_bytes));
-            let url = format!("https://polkadot.js.org/apps/?rpc=wss://rpc.ibp.network/kusama#/extrinsics/decode/{}", encoded_call);
+            let encoded_call = hex_value(&self.vote_call_bytes);
+            let url = decode_call_url(self.chain_info.explorer, &encoded_call);
+            if !self.balance_resolved() {
+                return html!(
+                    <div class="mb">
+                        {format!("The call data is built once {} of the account you select is read.", self.balance_label())}
+                    </div>
+                );
+            }
+            let label = format!(
+                "Call data (unsigned) for AYE vote with {} + {} conviction:",
+                self.chain_info.format_balance(self.balance),
+                &self.conviction
+            );
+            let verification_html = match self.verify_vote() {
+                Ok(()) => html!(
+                    <div class="verified mb">
+                        {format!("✓ Verified: decodes locally to an AYE vote on ref #{} with {} and {} conviction", self.referendum, self.chain_info.format_balance(self.balance), self.conviction)}
+                    </div>
+                ),
+                Err(err) => html!(
+                    <div class="error mb">{format!("✗ The encoded call doesn't match your choice: {err}. Don't sign it.")}</div>
+                ),
+            };
             html!(
-                <div>
-                    <b>{format!("Encoded call data for AYE vote with {} KSM + {} conviction:", &self.balance, &self.conviction)}</b> <br/>
-                    <a class="encoded" href={url} target="_blank">{encoded_call}</a>
-                </div>
+                <>
+                    <HexDisplay label={label} hint={CALL_DATA_HINT} value={encoded_call} href={url}/>
+                    {verification_html}
+                </>
             )
         };
 
         let subscribe_finalized =
             ctx.link().callback(|_| Message::SubscribeFinalizedBlock);
 
-        let _finalized_block_html: Html = {
+        let finalized_block_html: Html = {
             html!(
-                <div>
-                    if self.finalized_blocks.is_empty(){
+                <div class="mb">
+                    <b>{"Finalized blocks:"}</b>
+                    <div class="source-filter">
+                        { for FeedVerbosity::iter().map(|verbosity| {
+                            let selected = verbosity == self.feed_verbosity;
+                            let verbosity_click = ctx.link().callback(move |_| Message::ChangeFeedVerbosity(verbosity));
+                            html! {
+                                <button class={classes!(selected.then_some("selected"))} onclick={verbosity_click}>{verbosity.label()}</button>
+                            }
+                        }) }
+                    </div>
+                    if self.finalized_blocks.is_empty() && self.feed_handle.is_none() {
                         <button onclick={subscribe_finalized} >{"subscribe finalized blocks"}</button>
                     }
-                    { for self.finalized_blocks.iter().map(|line| html! {<p> {line} </p>}) }
+                    if let Some(marker) = &self.feed_marker {
+                        <p><i>{marker}</i></p>
+                    }
+                    { for self.finalized_blocks.iter().map(|entry| html! {<p> {self.feed_verbosity.format(entry, self.feed_clock)} </p>}) }
                 </div>
             )
         };
 
-        let vote_html: Html = match &self.stage {
+        let lock_explainer_html = || {
+            let explanation = match (&self.conviction, &self.chain_params) {
+                (Conviction::None, _) => NO_LOCK_NOTICE.to_string(),
+                (_, None) => return html!(<></>),
+                (_, Some(chain_params)) => {
+                    let duration = self.conviction.lock_duration(
+                        chain_params.vote_locking_period,
+                        chain_params.expected_block_time,
+                    );
+                    format!(
+                        "{} will be locked for up to {} days after the referendum ends.",
+                        self.balance_label(),
+                        format_days(duration)
+                    )
+                }
+            };
+            html!(<div class="mb"><b>{"What happens to my tokens? "}</b>{explanation}</div>)
+        };
+
+        // the balance of a percentage is only known once the account is chosen
+        let projection_html: Html = match (&self.tally, self.balance_percentage) {
+            (Some(tally), None) => {
+                let projected = tally.with_aye(self.balance, self.conviction.votes(self.balance));
+                match (tally.approval(), projected.approval()) {
+                    (Some(current), Some(projected)) => html!(
+                        <div class="mb">
+                            {format!("Your vote would move AYE approval from {current:.2}% to {projected:.2}%.")}
+                        </div>
+                    ),
+                    (None, Some(_)) => html!(
+                        <div class="mb">{"Yours would be the first vote on this referendum."}</div>
+                    ),
+                    _ => html!(<></>),
+                }
+            }
+            _ => html!(<></>),
+        };
+
+        let vote_html: Html = match &self.flow.stage {
             SigningStage::Error(_)
             | SigningStage::EnterBalance
             | SigningStage::CreatingOnlineClient => html!(<></>),
-            _ => {
-                let _vote_call = node_runtime::tx().conviction_voting().vote(
-                    275,
-                    AccountVote::Standard {
-                        vote: Vote(129),
-                        balance: 10000000000000,
-                    },
-                );
-                html!(
-                    <div>
-                        {vote_as_hex_html()}
-                    </div>
-                )
-            }
+            _ => html!(
+                <div>
+                    {vote_as_hex_html()}
+                </div>
+            ),
         };
 
-        let signer_account_html: Html = match &self.stage {
+        let signer_account_html: Html = match &self.flow.stage {
             SigningStage::Signing(signer_account)
+            | SigningStage::SigningDeclined {
+                account: signer_account,
+                ..
+            }
+            | SigningStage::DelegationBlocked {
+                account: signer_account,
+                ..
+            }
+            | SigningStage::OfflineSigned { signer_account, .. }
             | SigningStage::SigningSuccess { signer_account, .. } => {
                 html!(
                     <div class="mb">
                             <b>{"Account used for signing: "}</b> <br/>
                             {"Extension: "}{&signer_account.source} <br/>
                             {"Name: "}{&signer_account.name} <br/>
-                            {"Address: "}{&signer_account.address} <br/>
+                            {"Address: "}<a href={account_url(&self.explorer(), &self.chain_info.display_address(&signer_account.address))} target="_blank">{self.chain_info.display_address(&signer_account.address)}</a> <br/>
+                            if let Some((params, step)) = &self.multisig_step {
+                                {"Voting through multisig: "}
+                                <a href={account_url(&self.explorer(), &params.account_id.to_string())} target="_blank">{params.account_id.to_string()}</a> <br/>
+                                {format!("Threshold {} of {}, this is the {}.", params.threshold, params.other_signatories.len() + 1, step)} <br/>
+                            }
+                    </div>
+                )
+            }
+            _ => html!(<></>),
+        };
+
+        let account_votes_html: Html = match &self.flow.stage {
+            // votes are looked up on-chain
+            _ if self.offline.is_some() => html!(<></>),
+            SigningStage::ReviewVote(_)
+            | SigningStage::Signing(_)
+            | SigningStage::DelegationBlocked { .. }
+            | SigningStage::SigningSuccess { .. } => {
+                let votes_html = match &self.account_votes {
+                    None => html!(<div class="loading">{"Loading recent votes..."}</div>),
+                    Some(Err(err)) => html!(<div>{"Recent votes could not be loaded: "}{err}</div>),
+                    Some(Ok(votes)) if votes.is_empty() => {
+                        html!(<div>{"No conviction votes found for this account."}</div>)
+                    }
+                    Some(Ok(votes)) => html!(
+                        <ul class="account-votes">
+                            { for votes.iter().map(|track_vote| html! {
+                                <li>
+                                    {format!("{} (track {}) • ref #{} • {}", track_vote.track_name, track_vote.track, track_vote.referendum, track_vote.vote.format(&self.chain_info))}
+                                </li>
+                            }) }
+                        </ul>
+                    ),
+                };
+                html!(
+                    <div class="mb">
+                        <b>{"Recent votes by this account: "}</b> <br/>
+                        {votes_html}
+                    </div>
+                )
+            }
+            _ => html!(<></>),
+        };
+
+        let lock_impact_html: Html = match (&self.flow.stage, &self.lock_impact) {
+            // the locks of a multisig vote apply to the multisig account, not the signer
+            _ if self.offline.is_some() || self.multisig_enabled => html!(<></>),
+            (
+                SigningStage::ReviewVote(_)
+                | SigningStage::Signing(_)
+                | SigningStage::DelegationBlocked { .. }
+                | SigningStage::SigningSuccess { .. },
+                lock_impact,
+            ) => {
+                let lock_impact_html = match lock_impact {
+                    None => html!(<div class="loading">{"Loading locks..."}</div>),
+                    Some(Err(err)) => html!(<div>{"Locks could not be loaded: "}{err}</div>),
+                    Some(Ok(lock_impact)) => html!(
+                        <div>
+                            if let Some(track) = lock_impact.track {
+                                {format!("Referendum on {}. ", self.track_label(track))}
+                            }
+                            {format!("Current lock: {}, after this vote: {}", self.chain_info.format_balance(lock_impact.current), self.chain_info.format_balance(lock_impact.after))}
+                            if lock_impact.after == lock_impact.current && lock_impact.current > Balance::default() {
+                                <br/>
+                                <small>{"Locks on different tracks overlap, so this vote doesn't add to the locked balance."}</small>
+                            }
+                            if let (Some(schedule), Some(chain_params)) = (&self.lock_schedule, &self.chain_params) {
+                                <LockGraph
+                                    schedule={schedule.clone()}
+                                    pending={lock_impact.track.map(|_| self.pending_lock(schedule.current_block, chain_params.vote_locking_period))}
+                                    expected_block_time={chain_params.expected_block_time}
+                                    chain_info={self.chain_info.clone()}
+                                />
+                            }
+                        </div>
+                    ),
+                };
+                html!(
+                    <div class="mb">
+                        <b>{"Conviction voting lock: "}</b> <br/>
+                        {lock_impact_html}
                     </div>
                 )
             }
             _ => html!(<></>),
         };
 
-        let stage_html: Html = match &self.stage {
+        let stage_html: Html = match &self.flow.stage {
             SigningStage::Error(error_message) => {
-                html!(<div class="error"> {"Error: "} {error_message} </div>)
+                html!(
+                    <>
+                        <div class="error"> {"Error: "} {error_message} </div>
+                        // why a signed extrinsic was blocked
+                        if let Some(Ok(comparison)) = &self.flow.call_comparison {
+                            if !comparison.matches {
+                                {comparison_html(comparison)}
+                            }
+                        }
+                    </>
+                )
             }
             SigningStage::CreatingOnlineClient => {
                 html!(
@@ -459,7 +2770,9 @@ impl Component for VoteComponent {
                 )
             }
             SigningStage::EnterMessage => {
-                let get_accounts_click = ctx.link().callback(|_| Message::RequestAccounts);
+                let continue_click = ctx.link().callback(|_| Message::ConfirmMessage);
+                let skip_click = ctx.link().callback(|_| Message::SkipMessage);
+                let tag = remark_tag(self.referendum);
                 let on_input = ctx.link().callback(move |event: InputEvent| {
                     let input_element = event.target_dyn_into::<HtmlInputElement>().unwrap();
                     let value = input_element.value();
@@ -468,10 +2781,24 @@ impl Component for VoteComponent {
 
                 html!(
                     <>
-                        <div class="mb"><b>{"Enter a message for the \"remark\" call in the \"System\" pallet:"}</b></div>
+                        <div class="mb"><b>{"Optionally attach a message to your vote (\"remark\" call in the \"System\" pallet):"}</b></div>
                         <input oninput={on_input} class="mb" value={AttrValue::from(self.message.clone())}/>
-                        {message_as_hex_html()}
-                        <button class="button-primary" onclick={get_accounts_click}> {"=> Select an Account for Signing"} </button>
+                        if self.message != tag {
+                            <div class="mb">
+                                <button onclick={ctx.link().callback(|_| Message::UseRemarkTag)}>{format!("Use the tag {tag}")}</button>
+                                <small>{" A standard tag, so campaigns can index the votes. You can still edit it."}</small>
+                            </div>
+                        }
+                        <label class="mb">
+                            <input type="checkbox" checked={self.remark_with_event} onchange={ctx.link().callback(|_| Message::ToggleRemarkWithEvent)}/>
+                            <span class="label-body">{"Emit an event with the message (remark_with_event), so indexers can tie it to your vote. Its fee is slightly higher."}</span>
+                        </label>
+                        if !self.message.is_empty() {
+                            {message_as_hex_html()}
+                            <div class="mb"><small>{"The remark and the vote are submitted together with utility.batch_all, so neither is included without the other."}</small></div>
+                        }
+                        <button class="button-primary" disabled={self.message.is_empty()} onclick={continue_click}> {"=> Continue with message"} </button>
+                        <button onclick={skip_click}> {"Skip message"} </button>
                     </>
                 )
             }
@@ -482,28 +2809,130 @@ impl Component for VoteComponent {
                     let value = input_element.value();
                     Message::ChangeBalance(value)
                 });
+                let on_input_conviction = ctx.link().callback(move |event: InputEvent| {
+                    let input_element = event.target_dyn_into::<HtmlInputElement>().unwrap();
+                    let position = input_element.value().parse::<u8>().unwrap_or_default();
+                    Message::ChangeConviction(Conviction::from_slider(position))
+                });
+
+                let toggle_multisig = ctx.link().callback(|_| Message::ToggleMultisig);
+                let on_input_signatories = ctx.link().callback(move |event: InputEvent| {
+                    let input_element = event.target_dyn_into::<HtmlTextAreaElement>().unwrap();
+                    Message::ChangeMultisigSignatories(input_element.value())
+                });
+                let on_input_threshold = ctx.link().callback(move |event: InputEvent| {
+                    let input_element = event.target_dyn_into::<HtmlInputElement>().unwrap();
+                    Message::ChangeMultisigThreshold(input_element.value())
+                });
+                let offline_html = match &self.offline {
+                    Some(offline) => {
+                        let fields = [
+                            (OfflineField::GenesisHash, "Genesis hash:"),
+                            (OfflineField::SpecVersion, "Spec version:"),
+                            (OfflineField::TransactionVersion, "Transaction version:"),
+                            (OfflineField::Nonce, "Account nonce:"),
+                        ];
+                        html!(
+                            <div class="advanced mb">
+                                <h6>{"Offline mode (advanced)"}</h6>
+                                <div class="mb">
+                                    <small>
+                                        {"Nothing is read from or submitted to the network. Enter the values of the chain and of the signing account yourself, "}
+                                        {"the signed extrinsic is shown for you to submit later. Signed extensions are taken from the metadata bundled with this app, so the spec version must match it."}
+                                    </small>
+                                </div>
+                                { for fields.into_iter().map(|(field, label)| {
+                                    let on_input = ctx.link().callback(move |event: InputEvent| {
+                                        let input_element = event.target_dyn_into::<HtmlInputElement>().unwrap();
+                                        Message::ChangeOfflineInput(field, input_element.value())
+                                    });
+                                    html! {
+                                        <>
+                                            <div class="mb"><b>{label}</b></div>
+                                            <input type="text" class="u-full-width mb" oninput={on_input} value={AttrValue::from(offline.get(field).to_string())}/>
+                                        </>
+                                    }
+                                }) }
+                            </div>
+                        )
+                    }
+                    None => html!(<></>),
+                };
+                let unsupported = self
+                    .chain_params
+                    .as_ref()
+                    .map(|chain_params| {
+                        unsupported_signed_extensions(&chain_params.signed_extensions)
+                    })
+                    .unwrap_or_default();
 
                 html!(
                     <>
-                        <div class="mb"><b>{"Enter vote value in KSM:"}</b></div>
-                        <input type="text" oninput={on_input_balance} class="mb" value={AttrValue::from(self.balance.to_string())}/>
+                        if !unsupported.is_empty() {
+                            <div class="error mb">
+                                {format!("{} requires the signed extension(s) {}, which older wallet extensions don't support. ", self.chain_info.name, unsupported.join(", "))}
+                                {"Update your wallet extension to a version supporting them, otherwise signing will fail."}
+                            </div>
+                        }
+                        {offline_html}
+                        <div class="mb"><b>{format!("Enter vote value in {}:", self.chain_info.symbol)}</b></div>
+                        <input type="text" inputmode="decimal" oninput={on_input_balance} class="mb" value={AttrValue::from(self.balance.format_units(self.chain_info.decimals))}/>
+                        if let Some(err) = &self.balance_error {
+                            <div class="error mb">{err}</div>
+                        }
+                        if let Some(percent) = self.balance_percentage {
+                            <div class="mb"><small>{format!("{percent}% of the free balance of the account you select, resolved before signing.")}</small></div>
+                        }
                         <div class="mb"><b>{"Conviction:"}</b></div>
                         <div class="convictions" style="display: flex;">
-                            { for Conviction::iter().map(|conviction| {
-                                    let label = format!("Lock {}", conviction.clone());
+                            { for Conviction::iter().filter(|conviction| conviction.slider_position() <= self.max_conviction.slider_position()).map(|conviction| {
+                                    let label = conviction.label();
                                     let class = format!("button-primary{}", self.is_selected(conviction.clone()));
+                                    let style = conviction_style(&conviction);
                                     let on_click_conviction = ctx.link().callback(move |_| Message::ChangeConviction(conviction.clone()));
                                     html! {
-                                        <button class={class} onclick={on_click_conviction}>
+                                        <button class={class} style={style} onclick={on_click_conviction}>
                                             {label}
                                         </button>
                                     }
                                 })
                             }
                         </div>
+                        <input type="range" class="conviction-slider" min="0" max={self.max_conviction.slider_position().to_string()} step="1"
+                            value={self.conviction.slider_position().to_string()}
+                            oninput={on_input_conviction}/>
+                        if self.max_conviction != Conviction::Lock6X {
+                            <div class="mb"><small>{format!("Convictions above {} are disabled on this site.", self.max_conviction)}</small></div>
+                        }
+                        {lock_explainer_html()}
+                        {projection_html}
+                        if self.offline.is_none() {
+                            <label class="mb">
+                                <input type="checkbox" checked={self.multisig_enabled} onchange={toggle_multisig}/>
+                                <span class="label-body">{"Vote through a multisig"}</span>
+                            </label>
+                        }
+                        if self.multisig_enabled {
+                            <div class="mb"><b>{"Other signatories (one address per line):"}</b></div>
+                            <textarea class="u-full-width" oninput={on_input_signatories} value={AttrValue::from(self.multisig_signatories.clone())}/>
+                            <div class="mb"><b>{"Threshold:"}</b></div>
+                            <input type="text" oninput={on_input_threshold} class="mb" value={AttrValue::from(self.multisig_threshold.clone())}/>
+                            <div class="mb">
+                                <small>
+                                    {"The first approval opens the multisig operation and reserves a deposit. "}
+                                    {"Every other approval must reference the timepoint (block height and extrinsic index) of that first approval, which is looked up automatically. "}
+                                    {"The approval reaching the threshold executes the vote below on behalf of the multisig account."}
+                                </small>
+                            </div>
+                        }
                         {vote_as_hex_html()}
-                        <br/>
-                        <button class="button-primary" onclick={get_accounts_click}> {"=> Select an Account for Signing"} </button>
+                        if let Some(mismatch) = &self.metadata_mismatch {
+                            <div class="error mb">
+                                {format!("This app may be outdated for the current {} runtime: {mismatch}. ", self.chain_info.name)}
+                                {"Signing is disabled to avoid signing a different call than the vote shown."}
+                            </div>
+                        }
+                        <button class="button-primary" disabled={self.balance_error.is_some() || self.verify_vote().is_err() || self.metadata_mismatch.is_some()} onclick={get_accounts_click}> {"=> Select an Account for Signing"} </button>
                     </>
                 )
             }
@@ -511,111 +2940,1122 @@ impl Component for VoteComponent {
                 html!(<div>{"Querying extensions for accounts..."}</div>)
             }
             SigningStage::SelectAccount(accounts) => {
+                let refresh_accounts = ctx.link().callback(|_| Message::RefreshAccounts);
+                let refresh_html = html!(
+                    <div class="mb">
+                        <LoadingButton label="Refresh accounts" loading_label="Refreshing accounts..." loading={self.refreshing_accounts} onclick={refresh_accounts}/>
+                        if let Some(err) = &self.refresh_accounts_error {
+                            <div class="error">{format!("Could not refresh accounts: {err}")}</div>
+                        }
+                    </div>
+                );
                 if accounts.is_empty() {
-                    html!(<div>{"No Web3 extension accounts found. Install Talisman or the Polkadot.js extension and add an account."}</div>)
+                    html!(
+                        <>
+                            <div class="mb">
+                                {match &self.extension_status {
+                                    None => "No accounts found, checking your wallet extensions...",
+                                    Some(Ok(status)) => status.empty_accounts_hint(),
+                                    Some(Err(_)) => "No Web3 extension accounts found. Install Talisman or the Polkadot.js extension and add an account, or unlock it and click Refresh.",
+                                }}
+                            </div>
+                            {refresh_html}
+                        </>
+                    )
                 } else {
+                    let groups = group_accounts_by_source(accounts, self.last_source.as_deref());
+                    let recent = available_recent_accounts(&self.recent_accounts, accounts);
+                    // multisig steps and offline signing stay one account at a time
+                    let multi_account = accounts.len() > 1
+                        && !self.multisig_enabled
+                        && self.offline.is_none()
+                        && !self.signer_only;
+                    let show_all = ctx.link().callback(|_| Message::FilterSource(None));
                     html!(
                         <>
                             <div class="mb"><b>{"Select an account you want to use for signing:"}</b></div>
-                            <div class="accounts">
-                                { for accounts.iter().enumerate().map(|(i, account)| {
-                                    let sign_with_account = ctx.link().callback(move |_| Message::SignWithAccount(i));
-                                    html! {
-                                        <button class="button-primary" onclick={sign_with_account}>
-                                            {&account.source} {" | "} {&account.name}<br/>
-                                        </button>
-                                    }
+                            if !recent.is_empty() {
+                                <div class="recent-accounts mb">
+                                    <small>{"Recently used: "}</small>
+                                    { for recent.iter().map(|(i, account)| {
+                                        let i = *i;
+                                        html! {
+                                            <button title={format!("{} ({})", self.chain_info.display_address(&account.address), account.source)} onclick={ctx.link().callback(move |_| Message::ReviewWithAccount(i))}>
+                                                {&account.name}
+                                            </button>
+                                        }
+                                    }) }
+                                </div>
+                            }
+                            if groups.len() > 1 {
+                                <div class="source-filter mb">
+                                    <button class={classes!(self.source_filter.is_none().then_some("selected"))} onclick={show_all}>{"All"}</button>
+                                    { for groups.iter().map(|group| {
+                                        let source = group.source.to_string();
+                                        let selected = self.source_filter.as_deref() == Some(group.source);
+                                        let filter_click = ctx.link().callback(move |_| Message::FilterSource(Some(source.clone())));
+                                        html! {
+                                            <button class={classes!(selected.then_some("selected"))} onclick={filter_click}>
+                                                {group.source}
+                                            </button>
+                                        }
+                                    }) }
+                                </div>
+                            }
+                            { for groups.iter()
+                                .filter(|group| self.source_filter.as_deref().is_none_or(|source| source == group.source))
+                                .map(|group| html! {
+                                    <div class="accounts">
+                                        <h6>
+                                            {group.source}
+                                            if self.last_source.as_deref() == Some(group.source) {
+                                                {" (last used)"}
+                                            }
+                                        </h6>
+                                        { for group.accounts.iter().map(|(i, account)| {
+                                            let i = *i;
+                                            let sign_with_account = ctx.link().callback(move |_| Message::ReviewWithAccount(i));
+                                            let toggle_selection = ctx.link().callback(move |_| Message::ToggleAccountSelection(i));
+                                            let duplicates = duplicate_sources(accounts, i);
+                                            html! {
+                                                <div class="account">
+                                                    if multi_account {
+                                                        <input type="checkbox" aria-label={format!("Vote with {}", account.name)} checked={self.selected_accounts.contains(&i)} onchange={toggle_selection}/>
+                                                    }
+                                                    <LoadingButton class={classes!("button-primary", account.is_default.then_some("selected"))} label={account.name.clone()} onclick={sign_with_account}/>
+                                                    <small class="address">
+                                                        if account.is_default {
+                                                            <b>{"Recommended by the extension • "}</b>
+                                                        }
+                                                        {self.chain_info.display_address(&account.address)}
+                                                        if !duplicates.is_empty() {
+                                                            <br/>
+                                                            {format!("Also in {}, this entry signs with {}", duplicates.join(", "), account.source)}
+                                                        }
+                                                    </small>
+                                                </div>
+                                            }
+                                        }) }
+                                    </div>
                                 }) }
-                            </div>
+                            if multi_account {
+                                <div class="mb">
+                                    <small>{"Check several accounts to vote with each of them, one after another."}</small> <br/>
+                                    <LoadingButton
+                                        label={format!("Vote with {} selected accounts", self.selected_accounts.len())}
+                                        disabled={self.selected_accounts.len() < 2}
+                                        onclick={ctx.link().callback(|_| Message::VoteWithSelected)}/>
+                                </div>
+                            }
+                            {refresh_html}
                         </>
                     )
                 }
             }
+            SigningStage::ReviewVote(account) => {
+                let lock_duration = match (&self.conviction, &self.chain_params) {
+                    (Conviction::None, _) => NO_LOCK_NOTICE.to_string(),
+                    (conviction, Some(chain_params)) => format!(
+                        "up to {} days after the referendum ends",
+                        format_days(conviction.lock_duration(
+                            chain_params.vote_locking_period,
+                            chain_params.expected_block_time,
+                        ))
+                    ),
+                    (conviction, None) => {
+                        format!("{} vote locking periods", conviction.lock_periods())
+                    }
+                };
+                let fee = match (&self.fee_estimate, self.multisig_enabled) {
+                    (_, true) => "depends on the multisig step, shown by the extension".to_string(),
+                    (None, _) if self.offline.is_some() => "not estimated offline".to_string(),
+                    (None, _) => "estimating...".to_string(),
+                    (Some(Ok(fee)), _) if self.remark_with_event && !self.message.is_empty() => {
+                        format!(
+                            "~{} (includes the remark's event)",
+                            self.chain_info.format_balance(*fee)
+                        )
+                    }
+                    (Some(Ok(fee)), _) => format!("~{}", self.chain_info.format_balance(*fee)),
+                    (Some(Err(err)), _) => format!("could not be estimated: {err}"),
+                };
+                let balance = match (self.balance_percentage, &self.free_balance) {
+                    (None, _) => self.chain_info.format_balance(self.balance),
+                    (Some(percent), None) => {
+                        format!("{percent}% of the free balance, resolving...")
+                    }
+                    (Some(percent), Some(Ok(free))) => format!(
+                        "{} ({percent}% of the free balance of {})",
+                        self.chain_info.format_balance(self.balance),
+                        self.chain_info.format_balance(*free)
+                    ),
+                    (Some(percent), Some(Err(err))) => {
+                        format!("{percent}% of the free balance, which could not be read: {err}")
+                    }
+                };
+                let balance_resolved = self.balance_resolved();
+                let shortfall = match (&self.free_balance, &self.fee_estimate, balance_resolved) {
+                    (Some(Ok(free)), Some(Ok(fee)), true) => {
+                        fee_shortfall(*free, self.balance, *fee)
+                    }
+                    _ => None,
+                };
+                // only relevant if the fee can be paid at all
+                let reaping_risk = match (
+                    &self.free_balance,
+                    &self.fee_estimate,
+                    &self.chain_params,
+                    shortfall,
+                ) {
+                    (Some(Ok(free)), Some(Ok(fee)), Some(chain_params), None) => {
+                        below_existential_deposit(
+                            *free,
+                            *fee,
+                            Balance::from_planck(chain_params.existential_deposit),
+                        )
+                    }
+                    _ => false,
+                };
+                let chain_params = match &self.offline {
+                    Some(offline) => offline.parse().ok().map(|(chain_params, _)| chain_params),
+                    None => self.chain_params.clone(),
+                };
+                // a multisig call is only known once its step is looked up when signing
+                let payload_html = match (
+                    self.show_advanced && !self.multisig_enabled,
+                    chain_params,
+                    &self.review_nonce,
+                ) {
+                    (false, _, _) | (_, None, _) => html!(<></>),
+                    (true, Some(_), None) => {
+                        html!(<div class="mb">{"Fetching the nonce to preview the signing payload..."}</div>)
+                    }
+                    (true, Some(_), Some(Err(err))) => {
+                        html!(<div class="mb">{format!("The signing payload can't be previewed: {err}")}</div>)
+                    }
+                    (true, Some(chain_params), Some(Ok(nonce))) => {
+                        let payload = signing_payload(
+                            &self.call_bytes(),
+                            &chain_params,
+                            *nonce,
+                            &account.address,
+                        );
+                        html!(
+                            <div class="mb">
+                                <b>{"Signing payload sent to the extension:"}</b>
+                                {json_html(&payload)}
+                            </div>
+                        )
+                    }
+                };
+                let needs_metadata = match (&self.extension_metadata, &self.chain_params) {
+                    (Some(metadata), Some(chain_params)) => metadata.needs_update(chain_params),
+                    _ => false,
+                };
+                let metadata_html = html!(
+                    <>
+                        if needs_metadata {
+                            <div class="mb">
+                                <small>{format!("Your {} extension doesn't have the current Kusama metadata, so its popup may show the vote as raw bytes. ", account.source)}</small>
+                                <LoadingButton label="Update extension metadata" loading_label="Waiting for the extension..." loading={self.providing_metadata} onclick={ctx.link().callback(|_| Message::ProvideMetadata)}/>
+                            </div>
+                        }
+                        {match &self.metadata_provided {
+                            None => html!(<></>),
+                            Some(Ok(true)) => html!(<div class="verified mb">{"✓ The extension now decodes the vote"}</div>),
+                            Some(Ok(false)) => html!(<div class="mb">{"The metadata update was declined in the extension"}</div>),
+                            Some(Err(err)) => html!(<div class="error mb">{format!("The metadata could not be updated: {err}")}</div>),
+                        }}
+                    </>
+                );
+                let back_click = ctx.link().callback(|_| Message::RequestAccounts);
+                let sign_click = ctx.link().callback(|_| Message::SignWithAccount);
+                html!(
+                    <div class="review mb">
+                        <h6>{"Review your vote"}</h6>
+                        if let Some(reason) = &self.flow.resign_reason {
+                            <div class="error mb">{reason}</div>
+                        }
+                        <div class="mb">
+                            <b>{"Account: "}</b>{format!("{} ({})", account.name, account.source)} <br/>
+                            <b>{"Address: "}</b>{self.chain_info.display_address(&account.address)} <br/>
+                            <b>{"Vote: "}</b>{format!("AYE on referendum #{}", self.referendum)} <br/>
+                            <b>{"Balance: "}</b>{balance} <br/>
+                            <b>{"Conviction: "}</b><span class="conviction-tag" style={conviction_style(&self.conviction)}>{self.conviction.label()}</span> <br/>
+                            <b>{"Lock: "}</b>{lock_duration} <br/>
+                            if let Some(vote) = self.existing_vote() {
+                                <b>{"Replaces: "}</b>{format!("your current vote, {}", vote.format(&self.chain_info))} <br/>
+                            }
+                            <b>{"Estimated fee: "}</b>{fee}
+                        </div>
+                        {payload_html}
+                        {metadata_html}
+                        if let Some(percent) = self.batch_weight.map(|weight| weight.percent_of_limit()).filter(|percent| *percent >= self.weight_warning_percent as u64) {
+                            <div class="error mb">
+                                {format!("The remark and vote batch uses ~{percent}% of the maximum weight of an extrinsic and may fail. Shorten the message, or submit the remark and the vote separately.")}
+                            </div>
+                        }
+                        if reaping_risk {
+                            <div class="error mb">
+                                {format!("After paying the fee, less than the existential deposit of {} would remain free, so the transaction would be rejected. Add funds to the account before voting.", self.chain_info.format_balance(Balance::from_planck(self.chain_params.as_ref().map_or(0, |params| params.existential_deposit))))}
+                            </div>
+                        }
+                        if let Some(shortfall) = shortfall {
+                            <div class="error mb">
+                                {format!("Your free balance can't cover both the vote and the fee, the transaction would fail. Reduce the vote by {}. ", self.chain_info.format_balance(shortfall))}
+                                <button onclick={ctx.link().callback(|_| Message::ReduceBalanceForFee)}>{"Reduce vote"}</button>
+                            </div>
+                        }
+                        if self.offline.is_none() && !self.signer_only {
+                            <label class="mb">
+                                <input type="checkbox" checked={self.auto_submit} onchange={ctx.link().callback(|_| Message::ToggleAutoSubmit)}/>
+                                <span class="label-body">{"Submit automatically once signed"}</span>
+                            </label>
+                            <label class="mb">
+                                <input type="checkbox" checked={self.wait_for_in_block} onchange={ctx.link().callback(|_| Message::ToggleWaitForInBlock)}/>
+                                <span class="label-body">{"Done once in a block"}</span> <br/>
+                                <small>{"Shows the result after ~6 s instead of waiting ~12-18 s for finalization. Until finalized, the block may still be replaced and the vote with it (rarely)."}</small>
+                            </label>
+                        }
+                        if self.multi_vote.is_some() {
+                            <button onclick={ctx.link().callback(|_| Message::SkipQueuedAccount)}>{"Skip this account"}</button>
+                        } else {
+                            <button onclick={back_click}>{"Back"}</button>
+                        }
+                        if !self.delegation_checked {
+                            <div class="loading mb">{"Checking the account for delegations..."}</div>
+                        }
+                        <button class="button-primary" disabled={!balance_resolved || !self.delegation_checked || self.referendum_missing()} onclick={sign_click}>{if self.auto_submit && !self.signer_only { "Confirm, Sign & Submit" } else { "Confirm & Sign" }}</button>
+                    </div>
+                )
+            }
             SigningStage::Signing(_) => {
-                html!(<div>{"Singing message with browser extension..."}</div>)
+                html!(<div>{format!("Signing message with {}...", self.signer.name())}</div>)
+            }
+            SigningStage::SigningDeclined { error, .. } => {
+                let retry_click = ctx.link().callback(|_| Message::RetrySigning);
+                let back_click = ctx.link().callback(|_| Message::RequestAccounts);
+                html!(
+                    <div class="mb">
+                        <div class="error mb">{"The extension didn't sign the vote: "}{error}</div>
+                        <div class="mb"><small>{"If you closed the extension popup by mistake, the same signing request can be sent again."}</small></div>
+                        <button class="button-primary" onclick={retry_click}>{"Try signing again"}</button>
+                        <button onclick={back_click}>{"Choose another account"}</button>
+                    </div>
+                )
+            }
+            SigningStage::DelegationBlocked {
+                delegation,
+                undelegate_call_hex,
+                ..
+            } => {
+                let back_click = ctx.link().callback(|_| Message::RequestAccounts);
+                let url = decode_call_url(self.chain_info.explorer, undelegate_call_hex);
+                html!(
+                    <>
+                        <div class="error mb">
+                            {format!("This account is delegating {} ({}) to {} on {}. ", self.chain_info.format_balance(delegation.balance), delegation.conviction, self.chain_info.format_address(&delegation.target), self.track_label(delegation.track))}
+                            {"Direct votes are rejected on a track while delegating, so this vote would fail."}
+                        </div>
+                        <div class="mb">
+                            <b>{"Undelegate first, then come back to vote:"}</b> <br/>
+                            <a class="encoded" href={url} target="_blank">{"=> Undelegate on polkadot.js apps"}</a>
+                        </div>
+                        <button onclick={back_click}> {"<= Choose another account"} </button>
+                    </>
+                )
+            }
+            SigningStage::OfflineSigned {
+                signed_extrinsic_hex,
+                ..
+            } => {
+                html!(
+                    <>
+                        <hr/>
+                        {self.handoff_html(signed_extrinsic_hex)}
+                    </>
+                )
             }
             SigningStage::SigningSuccess {
                 signature,
                 signed_extrinsic_hex,
                 submitting_stage,
+                replaced_vote,
                 ..
             } => {
                 let submitting_stage_html = match submitting_stage {
                     SubmittingStage::Initial { .. } => {
-                        let submit_extrinsic_click =
-                            ctx.link().callback(move |_| Message::SubmitSigned);
-                        html!(<button onclick={submit_extrinsic_click}> {"=> Submit the signed extrinsic"} </button>)
+                        let submit_extrinsic_click = ctx
+                            .link()
+                            .callback(|_| Message::SubmitSigned { watch: true });
+                        let submit_without_watching_click = ctx
+                            .link()
+                            .callback(|_| Message::SubmitSigned { watch: false });
+                        html!(
+                            <>
+                                if let SubmissionBackend::Relay(url) = &self.submission_backend {
+                                    <div class="mb"><small>{format!("Submitted through the sponsor relay {url}, which pays the fee. The result is checked on-chain.")}</small></div>
+                                }
+                                <LoadingButton label="=> Submit the signed extrinsic" onclick={submit_extrinsic_click}/>
+                                if self.submission_backend == SubmissionBackend::Direct {
+                                    <LoadingButton label="Submit without waiting" onclick={submit_without_watching_click}/>
+                                }
+                            </>
+                        )
+                    }
+                    SubmittingStage::Submitting { .. } => {
+                        let cancel_click = ctx.link().callback(|_| Message::CancelSubmission);
+                        html!(
+                            <>
+                                <div class="loading mb"><b>{"Submitting Extrinsic... (please wait a few seconds)"}</b></div>
+                                <button onclick={cancel_click}> {"Stop watching"} </button>
+                                <div class="mb"><small>{"The extrinsic has already been broadcast. Stopping only stops watching for the result, it does not cancel the vote on-chain."}</small></div>
+                            </>
+                        )
+                    }
+                    SubmittingStage::Finalized(outcome) => {
+                        let block_hash = format!("{:?}", outcome.block_hash);
+                        let extrinsic_hash = format!("{:?}", outcome.extrinsic_hash);
+                        let title = match (&outcome.dispatch_error, outcome.vote_executed) {
+                            (Some(_), _) => "Extrinsic failed ✗",
+                            (None, true) if replaced_vote.is_some() => "Vote replaced ✓",
+                            (None, true) => "Vote recorded ✓",
+                            (None, false) => "Approval recorded, waiting for the other signatories",
+                        };
+                        html!(
+                            <div class={classes!("outcome", (!outcome.is_success()).then_some("failed"))}>
+                                <h6>{title}</h6>
+                                if !outcome.finalized {
+                                    <div class="mb"><small>{"Included in a block, not finalized yet."}</small></div>
+                                }
+                                if let Some(error) = &outcome.dispatch_error {
+                                    <div class="mb">{"Dispatch error: "}{error}</div>
+                                }
+                                if let (true, Some(replaced_vote)) = (outcome.is_success() && outcome.vote_executed, replaced_vote) {
+                                    <div class="mb">
+                                        {"Your previous vote was replaced with the new one."} <br/>
+                                        <small>{format!("Previous vote: {}", replaced_vote.format(&self.chain_info))}</small>
+                                    </div>
+                                }
+                                <div class="outcome-row">
+                                    <b>{"Block: "}</b>
+                                    <a href={block_url(&self.explorer(), &block_hash)} target="_blank">{&block_hash}</a>
+                                </div>
+                                <div class="outcome-row">
+                                    <b>{"Extrinsic: "}</b>
+                                    <a href={extrinsic_url(&self.explorer(), &extrinsic_hash)} target="_blank">{format!("#{} in block", outcome.extrinsic_index)}</a>
+                                </div>
+                                if let Some(fee_paid) = outcome.fee_paid {
+                                    <div class="outcome-row"><b>{"Fee paid: "}</b>{self.chain_info.format_balance(fee_paid)}</div>
+                                }
+                                <div class="outcome-row">
+                                    <a class="footer-link" onclick={ctx.link().callback(|_| Message::ToggleEvents)}>
+                                        {if self.show_events { "Hide events".to_string() } else { format!("Show all {} events", outcome.events.len()) }}
+                                    </a>
+                                </div>
+                                if self.show_events {
+                                    <ol class="events">
+                                        { for outcome.events.iter().map(|event| html! {
+                                            <li>
+                                                <b>{format!("{}.{}", event.pallet, event.variant)}</b>
+                                                if !event.fields.is_empty() {
+                                                    <br/><small>{&event.fields}</small>
+                                                }
+                                            </li>
+                                        }) }
+                                    </ol>
+                                }
+                                if outcome.is_success() && outcome.vote_executed {
+                                    <ShareCard
+                                        summary={format!("I voted AYE with {}, {} conviction on {} ref #{}", self.chain_info.format_balance(self.balance), self.conviction, self.chain_info.name, self.referendum)}
+                                        link={referendum_url(&self.explorer(), self.referendum)}
+                                        file_name={format!("ref-{}-vote.png", self.referendum)}
+                                    />
+                                }
+                            </div>
+                        )
                     }
-                    SubmittingStage::Submitting => {
-                        html!(<div class="loading"><b>{"Submitting Extrinsic... (please wait a few seconds)"}</b></div>)
+                    SubmittingStage::Submitted { extrinsic_hash } => {
+                        let extrinsic_hash = format!("{extrinsic_hash:?}");
+                        html!(
+                            <div>
+                                <b>{"Extrinsic submitted."}</b> <br/>
+                                {"The result is not watched, follow it on the explorer: "}
+                                <a href={extrinsic_url(&self.explorer(), &extrinsic_hash)} target="_blank">{&extrinsic_hash}</a>
+                            </div>
+                        )
                     }
-                    SubmittingStage::Success { remark_event } => {
-                        html!(<div style="overflow-wrap: break-word;"> <b>{"Successfully submitted Extrinsic. Event:"}</b> <br/> {format!("{:?}", remark_event)} </div>)
+                    SubmittingStage::Cancelled => {
+                        html!(<div><b>{"Stopped watching the extrinsic."}</b> <br/> {"It may still be included on-chain, check the referendum page to confirm your vote."}</div>)
                     }
                     SubmittingStage::Error(err) => {
-                        html!(<div class="error"> {"Error: "} {err.to_string()} </div>)
+                        let retry_click = ctx.link().callback(|_| Message::RetrySubmission);
+                        let rejection = SubmissionError::pool_rejection_of(err);
+                        html!(
+                            <>
+                                <div class="error mb"> {"Error: "} {err.to_string()} </div>
+                                if let Some(rejection) = rejection.filter(PoolRejection::is_congestion) {
+                                    <div class="mb">
+                                        <b>{"The network is congested: "}</b>{rejection.description()}{"."} <br/>
+                                        <small>{"Votes are signed without a tip, so their priority can't be raised. Wait a few blocks for the pool to drain, then retry."}</small>
+                                    </div>
+                                } else if rejection.is_some() {
+                                    <div class="mb">
+                                        <b>{"This extrinsic was already submitted: "}</b>{"the node recently saw it and ignores it for a while."} <br/>
+                                        <small>{"Check recent blocks for your vote before submitting again."}</small>
+                                    </div>
+                                }
+                                if self.flow.submitted_extrinsic.is_some() && self.online_client.is_some() {
+                                    <button onclick={retry_click}> {"Retry submission"} </button>
+                                    <div class="mb"><small>{"Recent blocks are checked for the extrinsic first, it's only submitted again if it wasn't included."}</small></div>
+                                }
+                            </>
+                        )
+                    }
+                };
+
+                let call_comparison_html = match &self.flow.call_comparison {
+                    Some(Ok(comparison)) if self.show_advanced || !comparison.matches => {
+                        comparison_html(comparison)
+                    }
+                    Some(Err(err)) if self.show_advanced => html!(
+                        <div class="mb">{"The signed call could not be decoded: "}{err}</div>
+                    ),
+                    _ => html!(<></>),
+                };
+
+                let apps_link_html = match decode_extrinsic_url(
+                    self.chain_info.explorer,
+                    signed_extrinsic_hex,
+                ) {
+                    Some(url) => {
+                        let copy_click = ctx
+                            .link()
+                            .callback(move |_| Message::CopyAppsLink(url.clone()));
+                        html!(
+                            <div class="mb">
+                                <button onclick={copy_click}>
+                                    {match self.flow.apps_link_copied {
+                                        None => "Copy polkadot.js apps link",
+                                        Some(true) => "Copied ✓",
+                                        Some(false) => "Copy failed",
+                                    }}
+                                </button>
+                                <small>{" Opens the signed extrinsic in polkadot.js apps, to inspect it and submit it from there."}</small>
+                            </div>
+                        )
+                    }
+                    None => html!(
+                        <div class="mb">
+                            <small>{format!("The signed extrinsic is too long for a polkadot.js apps link (over {MAX_LINK_LENGTH} characters). Copy it above instead and paste it in apps under Developer > Extrinsics > Decode.")}</small>
+                        </div>
+                    ),
+                };
+
+                // only relevant until submitted
+                let validation_html = match (submitting_stage, &self.flow.validation) {
+                    (SubmittingStage::Initial { .. }, None) => {
+                        html!(<div class="loading mb">{"Validating the extrinsic..."}</div>)
                     }
+                    (SubmittingStage::Initial { .. }, Some(Ok(validity))) => html!(
+                        <div class="mb"><span class="verified">{"Valid extrinsic ✓"}</span>{format!(" {validity}")}</div>
+                    ),
+                    (SubmittingStage::Initial { .. }, Some(Err(reason))) => html!(
+                        <div class="error mb">{format!("Invalid extrinsic: {reason}")}</div>
+                    ),
+                    _ => html!(<></>),
                 };
 
                 html!(
                     <>
-                        <div style="overflow-wrap: break-word;" class="mb">
-                            <b>{"Received signature: "}</b><br/>
-                            {hex::encode(signature.encode())}
-                        </div>
-                        <div style="overflow-wrap: break-word;" class="mb">
-                            <b>{"Hex representation of signed extrinsic: "}</b> <br/>
-                            {signed_extrinsic_hex}
-                        </div>
-                        {submitting_stage_html}
+                        <hr/>
+                        {call_comparison_html}
+                        {validation_html}
+                        <HexDisplay label="Received signature: " value={hex_value(signature.encode())}/>
+                        if self.signer_only {
+                            {self.handoff_html(signed_extrinsic_hex)}
+                        } else {
+                            <HexDisplay label="Signed extrinsic: " hint={SIGNED_EXTRINSIC_HINT} value={signed_extrinsic_hex.clone()}/>
+                            {apps_link_html}
+                            {submitting_stage_html}
+                        }
                     </>
                 )
             }
         };
 
+        let multi_vote_html: Html = match &self.multi_vote {
+            None => html!(<></>),
+            Some(multi_vote) => {
+                let succeeded = multi_vote
+                    .results
+                    .iter()
+                    .filter(|(_, result)| result.is_ok())
+                    .count();
+                let done = multi_vote.current.is_none() && multi_vote.pending.is_empty();
+                let total = multi_vote.results.len()
+                    + multi_vote.pending.len()
+                    + usize::from(multi_vote.current.is_some());
+                html!(
+                    <div class="multi-vote mb">
+                        <b>
+                            if done {
+                                {format!("Voted with {succeeded} of {} accounts", multi_vote.results.len())}
+                            } else {
+                                {format!("Voting with account {} of {total}", multi_vote.results.len() + 1)}
+                            }
+                        </b>
+                        <ul>
+                            { for multi_vote.results.iter().map(|(account, result)| html! {
+                                <li>
+                                    {format!("{}: ", account.name)}
+                                    {match result {
+                                        Ok(outcome) => format!("✓ {outcome}"),
+                                        Err(err) => format!("✗ {err}"),
+                                    }}
+                                </li>
+                            }) }
+                            if let Some(account) = &multi_vote.current {
+                                <li>{format!("{}: in progress...", account.name)}</li>
+                            }
+                            { for multi_vote.pending.iter().map(|account| html! {
+                                <li>{format!("{}: waiting", account.name)}</li>
+                            }) }
+                        </ul>
+                    </div>
+                )
+            }
+        };
+
+        let stuck_html: Html = if self.stage_stuck {
+            let tip = match self.stage_label() {
+                "creating_online_client" => "The RPC endpoint may be slow or unreachable. Check your internet connection.",
+                "requesting_accounts" => "Make sure your wallet extension is installed, unlocked and that this site is authorized.",
+                "signing" => "The extension popup may be hidden behind another window, or waiting for your password.",
+                _ => "The network may be congested. Your extrinsic may still be finalized.",
+            };
+            let retry_click = ctx.link().callback(|_| Message::RetryStage);
+            let cancel_click = ctx.link().callback(|_| Message::CancelStage);
+            html!(
+                <div class="stuck mb">
+                    <b>{"Taking longer than expected — need help?"}</b> <br/>
+                    {tip} <br/>
+                    if self.stage_label() != "submitting" {
+                        <button onclick={retry_click}> {"Retry"} </button>
+                    }
+                    <button onclick={cancel_click}> {"Cancel"} </button>
+                </div>
+            )
+        } else {
+            html!(<></>)
+        };
+
+        let rpc_status_html: Html = match &self.rpc_latency {
+            None => html!(<></>),
+            Some(Ok(latency)) => {
+                let slow = *latency > SLOW_RPC_LATENCY;
+                html!(
+                    <div class={classes!("rpc-status", "mb", slow.then_some("slow"))}>
+                        <small>
+                            {format!("Connected via {} • {} ms", self.rpc_endpoint, latency.as_millis())}
+                            if slow {
+                                {" • The connection is slow, signing and submitting may take longer than usual."}
+                            }
+                        </small>
+                    </div>
+                )
+            }
+            Some(Err(err)) => html!(
+                <div class="rpc-status slow mb">
+                    <small>
+                        {format!("{} is not responding: {}", self.rpc_endpoint, err)}
+                        if Self::injected_client(ctx) {
+                            {" • This client is provided by the host page and can't be reconnected here, reload the page to connect again."}
+                        }
+                    </small>
+                </div>
+            ),
+        };
+
+        // a client passed in by the host page may not be the public endpoint
+        let public_rpc_notice_html = match (&self.online_client, &ctx.props().client) {
+            (Some(_), None) if !self.public_rpc_notice_dismissed => html!(
+                <div class="notice mb">
+                    <small>
+                        {format!("Votes are signed in your browser, but read and submitted through the public RPC {}. ", self.rpc_endpoint)}
+                        {"It can't alter a signed vote, but it sees your IP address and what you submit, and could withhold it. "}
+                        {"To avoid trusting it, sign in offline mode ("}<a href="?offline=1">{"?offline=1"}</a>{") and submit the signed extrinsic through your own node."}
+                    </small>
+                    <button onclick={ctx.link().callback(|_| Message::DismissPublicRpcNotice)}>{"Got it"}</button>
+                </div>
+            ),
+            _ => html!(<></>),
+        };
+
+        let on_input_explorer_url = ctx.link().callback(|event: InputEvent| {
+            let input_element = event.target_dyn_into::<HtmlInputElement>().unwrap();
+            Message::ChangeExplorerUrl(input_element.value())
+        });
+        let advanced_html: Html = match (&self.chain_params, self.show_advanced) {
+            (Some(chain_params), true) => html!(
+                <div class="advanced">
+                    <h6>{"Advanced"}</h6>
+                    <div class="mb">
+                        <b>{"Network: "}</b>{format!("{} (ss58 prefix {}, {} with {} decimals)", self.chain_info.name, self.chain_info.ss58_prefix, self.chain_info.symbol, self.chain_info.decimals)} <br/>
+                        <b>{"Genesis hash: "}</b>{format!("{:?}", chain_params.genesis_hash)} <br/>
+                        <b>{"Spec version: "}</b>{chain_params.spec_version} <br/>
+                        <b>{"Transaction version: "}</b>{chain_params.transaction_version}
+                    </div>
+                    <div class="mb">
+                        <b>{"Signed extensions required by the chain:"}</b>
+                        <ul>
+                            { for chain_params.signed_extensions.iter().map(|identifier| {
+                                let supported = SUPPORTED_SIGNED_EXTENSIONS.contains(&identifier.as_str());
+                                html! {
+                                    <li>
+                                        {identifier} {if supported { " ✓" } else { " ✗ not supported by older wallet extensions" }}
+                                    </li>
+                                }
+                            }) }
+                        </ul>
+                    </div>
+                    if !self.tracks.is_empty() {
+                        <div class="mb">
+                            <b>{"Referenda tracks:"}</b>
+                            <ul>
+                                { for self.tracks.iter().map(|track| {
+                                    let lock = Duration::from_millis(track.lock_period as u64 * chain_params.expected_block_time);
+                                    html! {
+                                        <li>{format!("{} {}: a 1x conviction locks for {} days", track.id, track.name, format_days(lock))}</li>
+                                    }
+                                }) }
+                            </ul>
+                        </div>
+                    }
+                    {finalized_block_html}
+                    <div class="mb">
+                        <b>{"Account nonces:"}</b>
+                        if let Some(nonce) = self.flow.signed_nonce {
+                            <br/>{format!("Last extrinsic signed with nonce {nonce}")}
+                        }
+                        if self.flow.next_nonces.is_empty() {
+                            <br/>{"Fetched from chain when signing"}
+                        } else {
+                            <ul>
+                                { for self.flow.next_nonces.iter().map(|(address, nonce)| html! {
+                                    <li>{format!("{address}: next nonce {nonce} (tracked locally)")}</li>
+                                }) }
+                            </ul>
+                            <button onclick={ctx.link().callback(|_| Message::ResyncNonces)}>{"Re-sync from chain"}</button>
+                        }
+                    </div>
+                    <div class="mb">
+                        <b>{"Explorer base URL:"}</b>
+                        <div><small>{"Links to extrinsics, accounts, blocks and referenda use Subscan's paths on this URL. Leave empty for Subscan."}</small></div>
+                        <input type="text" placeholder={self.chain_info.explorer.subscan_base_url()} oninput={on_input_explorer_url} value={AttrValue::from(self.explorer_url_input.clone())}/>
+                        <button onclick={ctx.link().callback(|_| Message::SaveExplorerUrl)}>{"Save"}</button>
+                        if self.custom_explorer_url.is_some() {
+                            <button onclick={ctx.link().callback(|_| Message::ClearExplorerUrl)}>{"Use Subscan"}</button>
+                        }
+                        if let Some(err) = &self.explorer_url_error {
+                            <div class="error">{err}</div>
+                        }
+                    </div>
+                </div>
+            ),
+            _ => html!(<></>),
+        };
+        let toggle_advanced = ctx.link().callback(|_| Message::ToggleAdvanced);
+
+        let diagnostics_html: Html = if self.show_diagnostics {
+            let yes_no = |ok: bool| if ok { "yes ✓" } else { "no ✗" };
+            let connection = match (&self.online_client, &self.offline) {
+                (_, Some(_)) => "offline mode".to_string(),
+                (Some(_), None) => format!("connected to {}", self.rpc_endpoint),
+                (None, None) => format!("not connected to {}", self.rpc_endpoint),
+            };
+            let latency = match &self.rpc_latency {
+                None => "not measured yet".to_string(),
+                Some(Ok(latency)) => format!("{} ms", latency.as_millis()),
+                Some(Err(err)) => format!("not responding: {err}"),
+            };
+            html!(
+                <div class="advanced">
+                    <h6>{"Diagnostics"}</h6>
+                    <div class="mb">
+                        <b>{"Endpoint: "}</b>{connection} <br/>
+                        <b>{"Latency: "}</b>{latency} <br/>
+                        if let Some(chain_params) = &self.chain_params {
+                            <b>{"Genesis hash: "}</b>
+                            {format!("{:?} ", chain_params.genesis_hash)}
+                            {if check_kusama_genesis(chain_params.genesis_hash).is_ok() { "matches Kusama ✓" } else { "is not Kusama ✗" }} <br/>
+                            <b>{"Spec / transaction version: "}</b>{format!("{} / {}", chain_params.spec_version, chain_params.transaction_version)} <br/>
+                            <b>{"Signed extensions supported by wallet extensions: "}</b>
+                            {match unsupported_signed_extensions(&chain_params.signed_extensions).as_slice() {
+                                [] => "all ✓".to_string(),
+                                unsupported => format!("missing {} ✗", unsupported.join(", ")),
+                            }} <br/>
+                        } else {
+                            <b>{"Chain parameters: "}</b>{"not read yet"} <br/>
+                        }
+                        if let Some(mismatch) = &self.metadata_mismatch {
+                            <b>{"Metadata: "}</b>{format!("{mismatch} ✗")} <br/>
+                        }
+                        <b>{"localStorage available: "}</b>{yes_no(self.browser_support.local_storage)} <br/>
+                        <b>{"Clipboard available: "}</b>{yes_no(self.browser_support.clipboard)} <br/>
+                        <b>{"Stage: "}</b>{self.stage_label()}
+                    </div>
+                </div>
+            )
+        } else {
+            html!(<></>)
+        };
+
         html! {
-            <div class="container">
+            <div class={classes!("container", self.embed.then_some("embed"))}>
                 <div class="top">
-                    <div class="header">
-                        <span class="kusama-logo">
-                            <img src="https://raw.githubusercontent.com/turboflakes/ref-275/main/assets/kusama_icon_shadow.svg" alt="kusama logo" />
-                        </span>
-                        <h1>{"ref. "}<a class="header-link" href="https://kusama.subsquare.io/referenda/275" target="_blank">{"#275"}</a></h1>
-                    </div>
-                    <h4>
-                        {"Vote AYE"}
-                    </h4>
-                    // {finalized_block_html}
+                    if !self.embed {
+                        <div class="header">
+                            <span class="kusama-logo">
+                                <img src="https://raw.githubusercontent.com/turboflakes/ref-275/main/assets/kusama_icon_shadow.svg" alt="kusama logo" />
+                            </span>
+                            <h1>{"ref. "}<a class="header-link" href={referendum_url(&self.explorer(), self.referendum)} target="_blank">{format!("#{}", self.referendum)}</a></h1>
+                        </div>
+                        <h4>
+                            {"Vote AYE"}
+                        </h4>
+                    }
+                    {rpc_status_html}
+                    {public_rpc_notice_html}
+                    if let Some(err) = &self.relay_error {
+                        <div class="error mb">{format!("{err}. Extrinsics are submitted directly, paying the fee from your account.")}</div>
+                    }
+                    if let (true, Some(count)) = (self.referendum_missing(), self.referendum_count) {
+                        <div class="error mb">{format!("Referendum #{} doesn't exist, the latest referendum is #{}.", self.referendum, count.saturating_sub(1))}</div>
+                    }
+                    {message_html}
                     {vote_html}
                     {signer_account_html}
+                    {account_votes_html}
+                    {lock_impact_html}
                     {stage_html}
+                    {multi_vote_html}
+                    <div class="visually-hidden" role="status" aria-live="polite">{self.status_announcement()}</div>
+                    {stuck_html}
+                    if !matches!(self.stage_label(), "creating_online_client" | "enter_message" | "enter_balance") {
+                        <button class="start-over" onclick={ctx.link().callback(|_| Message::Reset)}>{"Start over"}</button>
+                    }
                 </div>
-                <div class="footer">
-                    <div>{"© 2023 TurboFlakes"}</div>
-                    <a class="github-logo" href="https://github.com/turboflakes/ref-275" target="_blank">
-                        <img src="https://raw.githubusercontent.com/turboflakes/ref-275/main/assets/github.svg" alt="github logo" />
-                    </a>
-                </div>
+                if !self.embed {
+                    <div class="footer">
+                        <div>{"© 2023 TurboFlakes"}</div>
+                        <Link<Route> classes="footer-link" to={Route::Lookup { id: self.referendum }}>{"Check a vote"}</Link<Route>>
+                        <a class="footer-link" onclick={toggle_advanced}>
+                            {if self.show_advanced { "Hide advanced" } else { "Advanced" }}
+                        </a>
+                        <a class="footer-link" onclick={ctx.link().callback(|_| Message::ToggleDiagnostics)}>
+                            {if self.show_diagnostics { "Hide diagnostics" } else { "Diagnostics" }}
+                        </a>
+                        <a class="github-logo" href="https://github.com/turboflakes/ref-275" target="_blank">
+                            <img src="https://raw.githubusercontent.com/turboflakes/ref-275/main/assets/github.svg" alt="github logo" />
+                        </a>
+                    </div>
+                    {diagnostics_html}
+                    {advanced_html}
+                }
             </div>
         }
     }
+
+    fn destroy(&mut self, _ctx: &Context<Self>) {
+        if let Some(handle) = self.submission_handle.take() {
+            handle.abort();
+        }
+        if let Some(handle) = self.feed_handle.take() {
+            handle.abort();
+        }
+        for (handle, _) in self.tasks.drain(..) {
+            handle.abort();
+        }
+        if let Some(listener) = self.visibility_listener.take() {
+            remove_visibility_listener(&listener);
+        }
+        #[cfg(feature = "debug")]
+        crate::debug_console::set_vote_scope(None);
+    }
+}
+
+/// signs `call` with the browser extension and returns the signed extrinsic, ready to be submitted
+///
+/// `nonce` is the locally tracked next nonce of the account, the on-chain nonce is fetched if `None`.
+#[allow(clippy::too_many_arguments)]
+async fn sign_call<Call: TxPayload>(
+    api: OnlineClient<PolkadotConfig>,
+    chain_params: ChainParams,
+    call: Call,
+    account_id: AccountId32,
+    account_source: String,
+    account_address: String,
+    nonce: Option<u64>,
+    signer: Rc<dyn Signer>,
+) -> SigningResult {
+    let account_nonce = match nonce {
+        Some(nonce) => nonce,
+        None => match api.tx().account_nonce(&account_id).await {
+            Ok(nonce) => nonce,
+            Err(_) => return SigningResult::Failed("Fetching account nonce failed".to_string()),
+        },
+    };
+
+    let Ok(call_data) = api.tx().call_data(&call) else {
+        return SigningResult::Failed("could not encode call data".to_string());
+    };
+
+    let payload = signing_payload(&call_data, &chain_params, account_nonce, &account_address);
+    if let Err(err) = check_signing_payload(&payload, api.genesis_hash()) {
+        return SigningResult::Failed(err.to_string());
+    }
+
+    let request = SigningRequest {
+        call_data: call_data.clone(),
+        chain_params: chain_params.clone(),
+        nonce: account_nonce,
+        account_source,
+        account_address,
+    };
+    let multi_signature = match signer.sign(request).await {
+        Ok(multi_signature) => multi_signature,
+        Err(err) => return SigningResult::Declined(err.to_string(), account_nonce),
+    };
+
+    let Ok(partial_signed) =
+        api.tx()
+            .create_partial_signed_with_nonce(&call, account_nonce, Default::default())
+    else {
+        return SigningResult::Failed("PartialExtrinsic creation failed".to_string());
+    };
+
+    // Apply the signature
+    let signed_extrinsic =
+        partial_signed.sign_with_address_and_signature(&account_id.into(), &multi_signature);
+
+    // return the signature and signed extrinsic
+    SigningResult::Signed(
+        multi_signature,
+        signed_extrinsic.encoded().to_vec(),
+        account_nonce,
+    )
+}
+
+/// signs `call_data` with the browser extension without an online client and encodes the
+/// signed extrinsic, to be submitted later
+async fn sign_offline(
+    call_data: Vec<u8>,
+    chain_params: ChainParams,
+    account_nonce: u64,
+    account_id: AccountId32,
+    account_source: String,
+    account_address: String,
+    signer: Rc<dyn Signer>,
+) -> SigningResult {
+    // the entered genesis hash was checked to be Kusama's when parsing the offline input
+    let payload = signing_payload(&call_data, &chain_params, account_nonce, &account_address);
+    if let Err(err) = check_signing_payload(&payload, chain_params.genesis_hash) {
+        return SigningResult::Failed(err.to_string());
+    }
+
+    let request = SigningRequest {
+        call_data: call_data.clone(),
+        chain_params: chain_params.clone(),
+        nonce: account_nonce,
+        account_source,
+        account_address,
+    };
+    let multi_signature = match signer.sign(request).await {
+        Ok(multi_signature) => multi_signature,
+        Err(err) => return SigningResult::Declined(err.to_string(), account_nonce),
+    };
+
+    SigningResult::SignedOffline(signed_extrinsic_bytes(
+        &call_data,
+        account_id,
+        &multi_signature,
+        account_nonce,
+    ))
+}
+
+/// the previewed call side by side with the signed one
+fn comparison_html(comparison: &CallComparison) -> Html {
+    html!(
+        <div class={classes!("call-comparison", "mb", (!comparison.matches).then_some("mismatch"))}>
+            <b>
+                {if comparison.matches { "Signed call matches the preview ✓" } else { "Signed call differs from the preview ✗ Submission is blocked." }}
+            </b>
+            <div class="row">
+                <div class="six columns">
+                    <b>{"Preview:"}</b>
+                    <pre>{&comparison.preview}</pre>
+                </div>
+                <div class="six columns">
+                    <b>{"Signed:"}</b>
+                    <pre>{&comparison.signed}</pre>
+                </div>
+            </div>
+        </div>
+    )
+}
+
+/// tells a locked or unauthorized extension apart from one without accounts
+async fn check_extension_status() -> Message {
+    match get_extension_status().await {
+        Ok(status) => Message::ReceivedExtensionStatus(status),
+        Err(err) => Message::ExtensionStatusFailed(err),
+    }
+}
+
+/// renders a JSON object one field per line, with keys and values styled apart
+fn json_html(value: &serde_json::Value) -> Html {
+    let Some(object) = value.as_object() else {
+        return html!(<pre class="json">{value.to_string()}</pre>);
+    };
+    let last = object.len().saturating_sub(1);
+    html!(
+        <pre class="json">
+            {"{\n"}
+            { for object.iter().enumerate().map(|(i, (key, value))| {
+                let class = if value.is_string() { "json-string" } else { "json-value" };
+                html! {
+                    <>
+                        {"  "}<span class="json-key">{format!("\"{key}\"")}</span>{": "}
+                        <span class={class}>{value.to_string()}</span>
+                        {if i < last { ",\n" } else { "\n" }}
+                    </>
+                }
+            }) }
+            {"}"}
+        </pre>
+    )
+}
+
+/// formats a duration in days, e.g. "7" or "0.5"
+fn format_days(duration: Duration) -> String {
+    let days = duration.as_secs_f64() / 86_400.0;
+    if days.fract() == 0.0 {
+        format!("{days:.0}")
+    } else {
+        format!("{days:.1}")
+    }
 }
 
-async fn submit_wait_finalized_and_get_extrinsic_success_event(
+/// submits the extrinsic and returns its outcome once finalized, or as soon as it's in a block
+/// with `in_block`. A failed dispatch is part of the outcome, not an error.
+async fn submit_wait_and_get_outcome(
     extrinsic: SubmittableExtrinsic<PolkadotConfig, OnlineClient<PolkadotConfig>>,
-) -> Result<node_runtime::system::events::ExtrinsicSuccess, anyhow::Error> {
-    let events = extrinsic
+    metadata: Metadata,
+    in_block: bool,
+) -> Result<SubmissionOutcome, anyhow::Error> {
+    let progress = extrinsic
         .submit_and_watch()
-        .await?
-        .wait_for_finalized_success()
-        .await?;
+        .await
+        .map_err(SubmissionError::from)?;
+    let events = match in_block {
+        true => progress.wait_for_in_block().await?.fetch_events().await?,
+        false => progress.wait_for_finalized().await?.fetch_events().await?,
+    };
 
     let events_str = format!("{:?}", &events);
     web_sys::console::log_1(&events_str.into());
-    for event in events.find::<node_runtime::system::events::ExtrinsicSuccess>() {
-        web_sys::console::log_1(&format!("{:?}", event).into());
+
+    let outcome = SubmissionOutcome::from_events(&events, &metadata)?;
+    Ok(SubmissionOutcome {
+        finalized: !in_block,
+        ..outcome
+    })
+}
+
+/// submits the extrinsic through `backend` and waits for its outcome. The block a relay
+/// reports is checked through `api`, so the outcome shown is read from the chain either way.
+async fn submit_through(
+    backend: SubmissionBackend,
+    api: OnlineClient<PolkadotConfig>,
+    extrinsic: SubmittableExtrinsic<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+    in_block: bool,
+) -> Result<SubmissionOutcome, anyhow::Error> {
+    let url = match backend {
+        SubmissionBackend::Direct => {
+            return submit_wait_and_get_outcome(extrinsic, api.metadata(), in_block).await
+        }
+        SubmissionBackend::Relay(url) => url,
+    };
+    let encoded = extrinsic.encoded();
+    let receipt = relay_extrinsic(&url, encoded).await?;
+    let events = find_extrinsic_in_block(&api, receipt.block_hash, extrinsic_hash(encoded))
+        .await?
+        .ok_or(anyhow!(
+            "The relay reported block {:?}, but the extrinsic is not in it",
+            receipt.block_hash
+        ))?;
+    let outcome = SubmissionOutcome::from_events(&events, &api.metadata())?;
+    Ok(SubmissionOutcome {
+        finalized: receipt.finalized,
+        ..outcome
+    })
+}
+
+/// submits the extrinsic without watching it, returning its hash once the node accepted it.
+async fn submit_and_get_hash(
+    extrinsic: SubmittableExtrinsic<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+) -> Result<H256, SubmissionError> {
+    let hash = extrinsic.submit().await?;
+    #[cfg(feature = "debug")]
+    web_sys::console::log_1(&format!("Submitted extrinsic {hash:?}").into());
+    Ok(hash)
+}
+
+/// looks for the extrinsic in recent finalized blocks and only submits it again if it's not
+/// there, so retrying after a transient error can't submit an included extrinsic twice.
+async fn resubmit_unless_included(
+    api: OnlineClient<PolkadotConfig>,
+    encoded_extrinsic: Vec<u8>,
+    backend: SubmissionBackend,
+    in_block: bool,
+) -> Result<SubmissionOutcome, anyhow::Error> {
+    let hash = extrinsic_hash(&encoded_extrinsic);
+    if let Some(events) = find_finalized_extrinsic(&api, hash, RECOVERY_SEARCH_DEPTH).await? {
+        return Ok(SubmissionOutcome::from_events(&events, &api.metadata())?);
     }
+    let extrinsic = SubmittableExtrinsic::from_bytes(api.clone(), encoded_extrinsic);
+    submit_through(backend, api, extrinsic, in_block).await
+}
 
-    let success = events.find_first::<node_runtime::system::events::ExtrinsicSuccess>()?;
-    success.ok_or(anyhow!("ExtrinsicSuccess not found in events"))
+/// re-checks the connection after the page was hidden and looks up the submitted extrinsic
+/// in recently finalized blocks, so a result missed by a dropped watch can still be shown.
+/// A dropped connection is only replaced if `reconnect`.
+async fn recover_submission(
+    api: OnlineClient<PolkadotConfig>,
+    extrinsic_hash: H256,
+    reconnect: bool,
+) -> Vec<Message> {
+    let mut messages = vec![];
+    let api = match api.rpc().header(None).await {
+        Ok(_) => api,
+        Err(_) if !reconnect => return vec![Message::SubmissionNotFound],
+        Err(_) => match connect_with_retry(KUSAMA_RPC_ENDPOINTS, CONNECT_ATTEMPTS).await {
+            Ok((api, endpoint)) => {
+                messages.push(Message::Reconnected(api.clone(), endpoint));
+                api
+            }
+            Err(_) => return vec![Message::SubmissionNotFound],
+        },
+    };
+    let message = match find_finalized_extrinsic(&api, extrinsic_hash, RECOVERY_SEARCH_DEPTH).await
+    {
+        Ok(Some(events)) => match SubmissionOutcome::from_events(&events, &api.metadata()) {
+            Ok(outcome) => Message::ExtrinsicFinalized(outcome),
+            Err(err) => Message::ExtrinsicFailed(err.into()),
+        },
+        Ok(None) | Err(_) => Message::SubmissionNotFound,
+    };
+    messages.push(message);
+    messages
 }
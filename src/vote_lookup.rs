@@ -0,0 +1,207 @@
+use futures::FutureExt;
+use subxt::utils::AccountId32;
+use subxt::{OnlineClient, PolkadotConfig};
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+use crate::chain_info::{ChainInfo, TokenOverride};
+use crate::governance::{lookup_vote, read_tracks, Track, VoteLookup};
+use crate::services::{
+    check_kusama_genesis, connect_with_retry, query_param, CONNECT_ATTEMPTS, KUSAMA_RPC_ENDPOINTS,
+};
+use crate::Route;
+
+#[derive(Properties, PartialEq)]
+pub struct VoteLookupProps {
+    /// index of the referendum to look votes up on
+    #[prop_or(275)]
+    pub referendum_index: u32,
+}
+
+pub enum VoteLookupMessage {
+    OnlineClientCreated(OnlineClient<PolkadotConfig>),
+    ConnectFailed(String),
+    ChangeAddress(String),
+    Lookup,
+    /// the address the lookup is for, encoded with the network's prefix, to ignore stale results
+    ReceivedLookup(String, VoteLookup),
+    LookupFailed(String, String),
+}
+
+/// Read-only lookup of how any address voted on a referendum, nothing is signed.
+pub struct VoteLookupComponent {
+    online_client: Option<Result<OnlineClient<PolkadotConfig>, String>>,
+    tracks: Vec<Track>,
+    address_input: String,
+    /// address of the last lookup, encoded with the network's prefix and its result, `None` inside while loading
+    lookup: Option<(String, Option<Result<VoteLookup, String>>)>,
+    /// the address entered is not a valid ss58 address
+    address_error: Option<String>,
+    chain_info: ChainInfo,
+}
+
+impl Component for VoteLookupComponent {
+    type Message = VoteLookupMessage;
+
+    type Properties = VoteLookupProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        ctx.link().send_future(
+            connect_with_retry(KUSAMA_RPC_ENDPOINTS, CONNECT_ATTEMPTS).map(|res| match res {
+                Ok((online_client, _)) => VoteLookupMessage::OnlineClientCreated(online_client),
+                Err(err) => VoteLookupMessage::ConnectFailed(err.to_string()),
+            }),
+        );
+        VoteLookupComponent {
+            online_client: None,
+            tracks: vec![],
+            address_input: "".to_string(),
+            lookup: None,
+            address_error: None,
+            chain_info: ChainInfo::kusama().with_override(&TokenOverride::parse(
+                query_param("symbol"),
+                query_param("decimals"),
+            )),
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            VoteLookupMessage::OnlineClientCreated(online_client) => {
+                let tracks = check_kusama_genesis(online_client.genesis_hash())
+                    .and_then(|_| Ok(read_tracks(&online_client)?));
+                match tracks {
+                    Ok(tracks) => {
+                        self.tracks = tracks;
+                        self.online_client = Some(Ok(online_client));
+                    }
+                    Err(err) => self.online_client = Some(Err(err.to_string())),
+                }
+            }
+            VoteLookupMessage::ConnectFailed(err) => self.online_client = Some(Err(err)),
+            VoteLookupMessage::ChangeAddress(address) => {
+                self.address_input = address;
+                self.address_error = None;
+            }
+            VoteLookupMessage::Lookup => {
+                let Some(Ok(api)) = &self.online_client else {
+                    return false;
+                };
+                let address = self.address_input.trim();
+                let Ok(account_id) = address.parse::<AccountId32>() else {
+                    self.address_error = Some(format!("\"{address}\" is not a valid address"));
+                    return true;
+                };
+                let address = self.chain_info.format_address(&account_id);
+                self.lookup = Some((address.clone(), None));
+                let api = api.clone();
+                let tracks = self.tracks.clone();
+                let referendum = ctx.props().referendum_index;
+                ctx.link().send_future(async move {
+                    match lookup_vote(&api, &account_id, referendum, &tracks).await {
+                        Ok(lookup) => VoteLookupMessage::ReceivedLookup(address, lookup),
+                        Err(err) => VoteLookupMessage::LookupFailed(address, err.to_string()),
+                    }
+                });
+            }
+            VoteLookupMessage::ReceivedLookup(address, lookup) => {
+                self.set_result(address, Ok(lookup));
+            }
+            VoteLookupMessage::LookupFailed(address, err) => {
+                self.set_result(address, Err(err));
+            }
+        }
+        true
+    }
+
+    fn changed(&mut self, _ctx: &Context<Self>, _old_props: &Self::Properties) -> bool {
+        // results were for the previous referendum
+        self.lookup = None;
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let referendum = ctx.props().referendum_index;
+        let on_input = ctx.link().callback(|event: InputEvent| {
+            let input_element = event.target_dyn_into::<HtmlInputElement>().unwrap();
+            VoteLookupMessage::ChangeAddress(input_element.value())
+        });
+        let on_submit = ctx.link().callback(|event: SubmitEvent| {
+            event.prevent_default();
+            VoteLookupMessage::Lookup
+        });
+        let connected = matches!(self.online_client, Some(Ok(_)));
+
+        let result_html = match &self.lookup {
+            None => html!(<></>),
+            Some((_, None)) => html!(<div class="loading mb">{"Looking up the vote..."}</div>),
+            Some((_, Some(Err(err)))) => {
+                html!(<div class="error mb">{"The vote could not be looked up: "}{err}</div>)
+            }
+            Some((address, Some(Ok(lookup)))) => html!(
+                <div class="outcome mb">
+                    <small class="address">{address}</small>
+                    {match lookup {
+                        VoteLookup::Voted(vote) => html!(
+                            <>
+                                <h6>{format!("Voted on referendum #{referendum} ✓")}</h6>
+                                <div>{vote.vote.format(&self.chain_info)}</div>
+                                <small>{format!("on the {} track", vote.track_name)}</small>
+                            </>
+                        ),
+                        VoteLookup::Delegating(delegation) => html!(
+                            <>
+                                <h6>{"No direct vote, delegating"}</h6>
+                                <div>{format!("{} ({}) delegated to {}", self.chain_info.format_balance(delegation.balance), delegation.conviction, self.chain_info.format_address(&delegation.target))}</div>
+                                <small>{"Delegated votes count with the target's vote."}</small>
+                            </>
+                        ),
+                        VoteLookup::NotVoted => html!(
+                            <h6>{format!("Hasn't voted on referendum #{referendum}")}</h6>
+                        ),
+                    }}
+                </div>
+            ),
+        };
+
+        html! {
+            <div class="container">
+                <div class="top">
+                    <div class="header">
+                        <span class="kusama-logo">
+                            <img src="https://raw.githubusercontent.com/turboflakes/ref-275/main/assets/kusama_icon_shadow.svg" alt="kusama logo" />
+                        </span>
+                        <h1>{format!("Check a vote on #{referendum}")}</h1>
+                    </div>
+                    <div class="mb">{"Paste any Kusama address to see how it voted. Nothing is signed or submitted."}</div>
+                    {match &self.online_client {
+                        None => html!(<div class="loading mb">{"Connecting to Kusama..."}</div>),
+                        Some(Err(err)) => html!(<div class="error mb">{"Could not connect to Kusama: "}{err}</div>),
+                        Some(Ok(_)) => html!(<></>),
+                    }}
+                    <form onsubmit={on_submit}>
+                        <input class="mb" placeholder="Kusama address" oninput={on_input} value={self.address_input.clone()}/>
+                        if let Some(err) = &self.address_error {
+                            <div class="error mb">{err}</div>
+                        }
+                        <button class="button-primary" type="submit" disabled={!connected || self.address_input.trim().is_empty()}>{"Check vote"}</button>
+                    </form>
+                    {result_html}
+                    <Link<Route> to={Route::Referendum { id: referendum }}>{"=> Go to the voting page"}</Link<Route>>
+                </div>
+            </div>
+        }
+    }
+}
+
+impl VoteLookupComponent {
+    /// sets the result of the lookup for `address`, unless another address was looked up since
+    fn set_result(&mut self, address: String, result: Result<VoteLookup, String>) {
+        if let Some((current, lookup)) = &mut self.lookup {
+            if *current == address {
+                *lookup = Some(result);
+            }
+        }
+    }
+}